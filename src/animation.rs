@@ -0,0 +1,16 @@
+//! Skeletal animation of characters built from textured parts, also known as cut-out animation.
+//!
+//! A `Skeleton` is a hierarchy of bones, each positioned relative to its parent.
+//! Slots attach images to bones and `Animation`s move the bones over time with keyframes.
+//! A `SkeletonInstance` plays animations on a shared skeleton and draws the attachments through `DisplayArea::draw_ex`.
+//!
+//! Skeletons can be built in code or imported from the JSON export of Spine with `Skeleton::from_spine_json`.
+
+mod instance;
+mod skeleton;
+mod spine;
+mod timeline;
+
+pub use instance::*;
+pub use skeleton::*;
+pub use timeline::*;
@@ -0,0 +1,252 @@
+use super::{BonePose, Skeleton};
+use crate::quicksilver_compat::Background;
+use crate::{DisplayArea, ErrorMessage, PaddleResult, Rectangle, Transform};
+use std::rc::Rc;
+
+/// A character using a shared `Skeleton`, with its own animation state.
+///
+/// ```
+/// # use paddle::*;
+/// # use std::rc::Rc;
+/// let mut skeleton = Skeleton::new();
+/// let hip = skeleton.add_bone("hip", None, BonePose::IDENTITY).unwrap();
+/// let leg = skeleton.add_bone("leg", Some(hip), BonePose::new((0, 10), 0.0, (1, 1))).unwrap();
+/// let swing = BoneTrack::new(leg)
+///     .with_rotation(Keyframe::new(0.0, -30.0))
+///     .with_rotation(Keyframe::new(0.5, 30.0))
+///     .with_rotation(Keyframe::new(1.0, -30.0));
+/// skeleton.add_animation("walk", Animation::new(vec![swing]));
+///
+/// let mut character = SkeletonInstance::new(Rc::new(skeleton));
+/// character.play("walk", true).unwrap();
+/// character.update(1.25);
+/// assert!((character.pose(leg).rotation - 0.0).abs() < 1e-3);
+/// ```
+pub struct SkeletonInstance {
+    skeleton: Rc<Skeleton>,
+    animation: Option<String>,
+    time: f32,
+    looping: bool,
+    speed: f32,
+    poses: Vec<BonePose>,
+    world: Vec<Transform>,
+    attachments: Vec<Option<String>>,
+}
+
+impl SkeletonInstance {
+    /// An instance in the setup pose
+    pub fn new(skeleton: Rc<Skeleton>) -> Self {
+        let attachments = skeleton
+            .slots()
+            .iter()
+            .map(|slot| slot.setup_attachment.clone())
+            .collect();
+        let mut instance = SkeletonInstance {
+            skeleton,
+            animation: None,
+            time: 0.0,
+            looping: false,
+            speed: 1.0,
+            poses: Vec::new(),
+            world: Vec::new(),
+            attachments,
+        };
+        instance.refresh();
+        instance
+    }
+    pub fn skeleton(&self) -> &Rc<Skeleton> {
+        &self.skeleton
+    }
+    /// Start an animation from its beginning
+    pub fn play(&mut self, name: &str, looping: bool) -> PaddleResult<()> {
+        if self.skeleton.animation(name).is_none() {
+            return Err(ErrorMessage::technical(format!(
+                "Skeleton has no animation '{}'",
+                name
+            )));
+        }
+        self.animation = Some(name.to_owned());
+        self.time = 0.0;
+        self.looping = looping;
+        self.refresh();
+        Ok(())
+    }
+    /// Stop animating and return to the setup pose
+    pub fn stop(&mut self) {
+        self.animation = None;
+        self.time = 0.0;
+        self.refresh();
+    }
+    /// Name of the current animation
+    pub fn animation(&self) -> Option<&str> {
+        self.animation.as_deref()
+    }
+    /// Seconds since the current animation started, wrapped for looping animations
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+    /// A non-looping animation has reached its end
+    pub fn is_finished(&self) -> bool {
+        match self.current() {
+            Some(animation) => !self.looping && self.time >= animation.duration,
+            None => true,
+        }
+    }
+    /// Play animations faster or slower
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed;
+    }
+    /// Advance the current animation by `dt` seconds
+    pub fn update(&mut self, dt: f32) {
+        let duration = match self.current() {
+            Some(animation) => animation.duration,
+            None => return,
+        };
+        self.time += dt * self.speed;
+        if self.looping && duration > 0.0 {
+            self.time = self.time.rem_euclid(duration);
+        } else {
+            self.time = self.time.clamp(0.0, duration);
+        }
+        self.refresh();
+    }
+    /// Jump to a point in time of the current animation
+    pub fn seek(&mut self, time: f32) {
+        self.time = time;
+        self.refresh();
+    }
+    /// Show another attachment in a slot, or none at all
+    pub fn set_attachment(&mut self, slot: usize, attachment: Option<&str>) -> PaddleResult<()> {
+        let slot_data = self
+            .skeleton
+            .slots()
+            .get(slot)
+            .ok_or_else(|| ErrorMessage::technical(format!("No slot with index {}", slot)))?;
+        if let Some(name) = attachment {
+            if !slot_data.attachments.contains_key(name) {
+                return Err(ErrorMessage::technical(format!(
+                    "Slot '{}' has no attachment '{}'",
+                    slot_data.name, name
+                )));
+            }
+        }
+        self.attachments[slot] = attachment.map(str::to_owned);
+        Ok(())
+    }
+    /// Current local pose of a bone
+    pub fn pose(&self, bone: usize) -> BonePose {
+        self.poses[bone]
+    }
+    /// Current transformation from bone space to skeleton space
+    pub fn world_transform(&self, bone: usize) -> Transform {
+        self.world[bone]
+    }
+    /// Draw all visible attachments, with `trans` placing the skeleton origin in the frame
+    pub fn draw(&self, display: &mut DisplayArea, trans: Transform, z: i16) {
+        for (slot, attachment) in self.skeleton.slots().iter().zip(&self.attachments) {
+            let attachment = match attachment.as_ref().and_then(|a| slot.attachments.get(a)) {
+                Some(attachment) => attachment,
+                None => continue,
+            };
+            let area = Rectangle::new(-attachment.size / 2.0, attachment.size);
+            display.draw_ex(
+                &area,
+                Background::Blended(&attachment.image, slot.color),
+                trans * self.world[slot.bone()] * attachment.pose.to_transform(),
+                z,
+            );
+        }
+    }
+
+    fn current(&self) -> Option<&super::Animation> {
+        self.animation
+            .as_ref()
+            .and_then(|name| self.skeleton.animation(name))
+    }
+    /// Recompute poses for the current time
+    fn refresh(&mut self) {
+        let mut poses: Vec<BonePose> = self.skeleton.bones().iter().map(|b| b.setup).collect();
+        if let Some(animation) = self.current() {
+            animation.apply(self.time, &mut poses);
+        }
+        self.world = self.skeleton.world_transforms(&poses);
+        self.poses = poses;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+    use super::*;
+    use crate::Vector;
+
+    fn arm() -> Skeleton {
+        let mut skeleton = Skeleton::new();
+        let shoulder = skeleton
+            .add_bone(
+                "shoulder",
+                None,
+                BonePose::new((100, 100), 0.0, Vector::ONE),
+            )
+            .unwrap();
+        let elbow = skeleton
+            .add_bone(
+                "elbow",
+                Some(shoulder),
+                BonePose::new((10, 0), 0.0, Vector::ONE),
+            )
+            .unwrap();
+        skeleton
+            .add_bone(
+                "hand",
+                Some(elbow),
+                BonePose::new((10, 0), 0.0, Vector::ONE),
+            )
+            .unwrap();
+        let raise = BoneTrack::new(shoulder)
+            .with_rotation(Keyframe::new(0.0, 0.0))
+            .with_rotation(Keyframe::new(1.0, 90.0));
+        let bend = BoneTrack::new(elbow)
+            .with_rotation(Keyframe::new(0.0, 0.0))
+            .with_rotation(Keyframe::new(1.0, 90.0));
+        skeleton.add_animation("raise", Animation::new(vec![raise, bend]));
+        skeleton
+    }
+
+    #[test]
+    fn hierarchy() {
+        let mut arm = SkeletonInstance::new(Rc::new(arm()));
+        let hand = 2;
+        assert_eq!(
+            arm.world_transform(hand) * Vector::ZERO,
+            Vector::new(120, 100)
+        );
+        arm.play("raise", false).unwrap();
+        arm.update(2.0);
+        assert!(arm.is_finished());
+        assert_eq!(arm.time(), 1.0);
+        // The shoulder turns the elbow down, the elbow turns the hand back to the left
+        assert_eq!(arm.world_transform(1) * Vector::ZERO, Vector::new(100, 110));
+        assert_eq!(
+            arm.world_transform(hand) * Vector::ZERO,
+            Vector::new(90, 110)
+        );
+        arm.stop();
+        assert_eq!(
+            arm.world_transform(hand) * Vector::ZERO,
+            Vector::new(120, 100)
+        );
+        assert!(arm.play("wave", true).is_err());
+    }
+
+    #[test]
+    fn looping() {
+        let mut arm = SkeletonInstance::new(Rc::new(arm()));
+        arm.play("raise", true).unwrap();
+        arm.set_speed(0.5);
+        arm.update(2.5);
+        assert_eq!(arm.time(), 0.25);
+        assert!(!arm.is_finished());
+        assert!((arm.pose(0).rotation - 22.5).abs() < 1e-4);
+    }
+}
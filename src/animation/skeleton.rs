@@ -0,0 +1,204 @@
+use super::Animation;
+use crate::quicksilver_compat::Color;
+use crate::{ErrorMessage, Image, PaddleResult, Transform, Vector};
+use std::collections::HashMap;
+
+/// Translation, rotation and scale of a bone relative to its parent
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BonePose {
+    pub translation: Vector,
+    /// Clockwise on screen, in degrees
+    pub rotation: f32,
+    pub scale: Vector,
+}
+
+/// A node of the bone hierarchy
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bone {
+    pub name: String,
+    parent: Option<usize>,
+    /// Pose when no animation is applied
+    pub setup: BonePose,
+    /// Only used for debugging, bones have no visible extent
+    pub length: f32,
+}
+
+/// A textured part attached to a bone
+#[derive(Clone, Debug, PartialEq)]
+pub struct Attachment {
+    /// The region of a texture to draw, usually a `subimage` of an atlas
+    pub image: Image,
+    /// Size in bone space, the image is centered on the pose
+    pub size: Vector,
+    /// Placement relative to the bone
+    pub pose: BonePose,
+}
+
+/// Draws one of several attachments on a bone.
+///
+/// Slots are drawn in order, later slots on top of earlier ones.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Slot {
+    pub name: String,
+    bone: usize,
+    pub color: Color,
+    /// All attachments that can be shown in this slot, by name
+    pub attachments: HashMap<String, Attachment>,
+    /// Attachment shown when no other has been selected
+    pub setup_attachment: Option<String>,
+}
+
+/// Bones, slots and animations of a character, shared by all instances of it.
+///
+/// Use a `SkeletonInstance` to play animations.
+#[derive(Clone, Debug, Default)]
+pub struct Skeleton {
+    bones: Vec<Bone>,
+    slots: Vec<Slot>,
+    animations: HashMap<String, Animation>,
+}
+
+impl BonePose {
+    pub const IDENTITY: BonePose = BonePose {
+        translation: Vector::ZERO,
+        rotation: 0.0,
+        scale: Vector::ONE,
+    };
+
+    pub fn new(translation: impl Into<Vector>, rotation: f32, scale: impl Into<Vector>) -> Self {
+        BonePose {
+            translation: translation.into(),
+            rotation,
+            scale: scale.into(),
+        }
+    }
+    /// Scale first, then rotate, then translate
+    pub fn to_transform(&self) -> Transform {
+        Transform::translate(self.translation)
+            * Transform::rotate(self.rotation)
+            * Transform::scale(self.scale)
+    }
+}
+
+impl Default for BonePose {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Bone {
+    /// Index of the parent bone, which always comes before its children
+    pub fn parent(&self) -> Option<usize> {
+        self.parent
+    }
+}
+
+impl Slot {
+    pub fn new(name: impl Into<String>, bone: usize) -> Self {
+        Slot {
+            name: name.into(),
+            bone,
+            color: Color::WHITE,
+            attachments: HashMap::new(),
+            setup_attachment: None,
+        }
+    }
+    /// Add an attachment, which is also shown by default if it is the first one
+    pub fn with_attachment(mut self, name: impl Into<String>, attachment: Attachment) -> Self {
+        let name = name.into();
+        if self.setup_attachment.is_none() {
+            self.setup_attachment = Some(name.clone());
+        }
+        self.attachments.insert(name, attachment);
+        self
+    }
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+    /// Index of the bone the slot is attached to
+    pub fn bone(&self) -> usize {
+        self.bone
+    }
+}
+
+impl Skeleton {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Add a bone and return its index. The parent must have been added before.
+    pub fn add_bone(
+        &mut self,
+        name: impl Into<String>,
+        parent: Option<usize>,
+        setup: BonePose,
+    ) -> PaddleResult<usize> {
+        let name = name.into();
+        if let Some(parent) = parent {
+            if parent >= self.bones.len() {
+                return Err(ErrorMessage::technical(format!(
+                    "Parent of bone '{}' does not exist",
+                    name
+                )));
+            }
+        }
+        self.bones.push(Bone {
+            name,
+            parent,
+            setup,
+            length: 0.0,
+        });
+        Ok(self.bones.len() - 1)
+    }
+    /// Add a slot on top of all existing slots
+    pub fn add_slot(&mut self, slot: Slot) -> PaddleResult<usize> {
+        if slot.bone >= self.bones.len() {
+            return Err(ErrorMessage::technical(format!(
+                "Bone of slot '{}' does not exist",
+                slot.name
+            )));
+        }
+        self.slots.push(slot);
+        Ok(self.slots.len() - 1)
+    }
+    pub fn add_animation(&mut self, name: impl Into<String>, animation: Animation) {
+        self.animations.insert(name.into(), animation);
+    }
+    pub fn bones(&self) -> &[Bone] {
+        &self.bones
+    }
+    pub fn bones_mut(&mut self) -> &mut [Bone] {
+        &mut self.bones
+    }
+    pub fn slots(&self) -> &[Slot] {
+        &self.slots
+    }
+    pub fn slots_mut(&mut self) -> &mut [Slot] {
+        &mut self.slots
+    }
+    pub fn bone_index(&self, name: &str) -> Option<usize> {
+        self.bones.iter().position(|bone| bone.name == name)
+    }
+    pub fn slot_index(&self, name: &str) -> Option<usize> {
+        self.slots.iter().position(|slot| slot.name == name)
+    }
+    pub fn animation(&self, name: &str) -> Option<&Animation> {
+        self.animations.get(name)
+    }
+    pub fn animation_names(&self) -> impl Iterator<Item = &str> {
+        self.animations.keys().map(String::as_str)
+    }
+    /// Transformations from bone space to skeleton space, for the given local poses
+    pub fn world_transforms(&self, poses: &[BonePose]) -> Vec<Transform> {
+        let mut world: Vec<Transform> = Vec::with_capacity(self.bones.len());
+        for (bone, pose) in self.bones.iter().zip(poses) {
+            let local = pose.to_transform();
+            let transform = match bone.parent {
+                Some(parent) => world[parent] * local,
+                None => local,
+            };
+            world.push(transform);
+        }
+        world
+    }
+}
@@ -0,0 +1,285 @@
+//! Import of skeletons exported from [Spine](http://esotericsoftware.com/) in its JSON format.
+//!
+//! Supported are bones with translation, rotation and scale, slots with region attachments of the default skin,
+//! and bone timelines of animations. Meshes, constraints, events and other timelines are ignored.
+//! Bezier curves are approximated by `Interpolation::Smooth`.
+//!
+//! Spine uses a y axis pointing up with counter-clockwise rotations, which is converted to the screen coordinates used in paddle.
+
+use super::*;
+use crate::quicksilver_compat::Color;
+use crate::{ErrorMessage, Image, PaddleResult, Vector};
+use serde_json::{Map, Value};
+
+impl Skeleton {
+    /// Load a skeleton with its animations from the JSON export of Spine.
+    ///
+    /// `images` is called with the path of each region attachment, which defaults to the attachment name.
+    /// Usually, it returns a `subimage` of a texture atlas.
+    pub fn from_spine_json(
+        json: &str,
+        images: impl Fn(&str) -> Option<Image>,
+    ) -> PaddleResult<Skeleton> {
+        let root: Value = serde_json::from_str(json)?;
+        let mut skeleton = Skeleton::new();
+        for bone in array(&root, "bones") {
+            let name = string(bone, "name")?;
+            let parent = match bone.get("parent").and_then(Value::as_str) {
+                Some(parent) => Some(skeleton.bone_index(parent).ok_or_else(|| {
+                    ErrorMessage::technical(format!("Unknown parent bone '{}'", parent))
+                })?),
+                None => None,
+            };
+            let index = skeleton.add_bone(name, parent, pose(bone))?;
+            skeleton.bones_mut()[index].length = number(bone, "length", 0.0);
+        }
+        let skin = default_skin(&root);
+        for slot in array(&root, "slots") {
+            let name = string(slot, "name")?;
+            let bone = bone_by_name(&skeleton, string(slot, "bone")?)?;
+            let mut data = Slot::new(name, bone);
+            if let Some(color) = slot.get("color").and_then(Value::as_str) {
+                data.color = parse_color(color)?;
+            }
+            if let Some(attachments) = skin.and_then(|s| s.get(name)).and_then(Value::as_object) {
+                for (attachment_name, attachment) in attachments {
+                    if let Some(attachment) = region(attachment_name, attachment, &images)? {
+                        data.attachments.insert(attachment_name.clone(), attachment);
+                    }
+                }
+            }
+            data.setup_attachment = slot
+                .get("attachment")
+                .and_then(Value::as_str)
+                .filter(|a| data.attachments.contains_key(*a))
+                .map(str::to_owned);
+            skeleton.add_slot(data)?;
+        }
+        if let Some(animations) = root.get("animations").and_then(Value::as_object) {
+            for (name, animation) in animations {
+                let animation = convert_animation(&skeleton, animation)?;
+                skeleton.add_animation(name.clone(), animation);
+            }
+        }
+        Ok(skeleton)
+    }
+}
+
+/// Skins are an object in Spine 3.7 and older, an array afterwards
+fn default_skin(root: &Value) -> Option<&Map<String, Value>> {
+    match root.get("skins")? {
+        Value::Array(skins) => skins
+            .iter()
+            .find(|s| s.get("name").and_then(Value::as_str) == Some("default"))?
+            .get("attachments")?
+            .as_object(),
+        Value::Object(skins) => skins.get("default")?.as_object(),
+        _ => None,
+    }
+}
+
+fn region(
+    name: &str,
+    attachment: &Value,
+    images: &impl Fn(&str) -> Option<Image>,
+) -> PaddleResult<Option<Attachment>> {
+    let kind = attachment
+        .get("type")
+        .and_then(Value::as_str)
+        .unwrap_or("region");
+    if kind != "region" {
+        return Ok(None);
+    }
+    let path = attachment
+        .get("path")
+        .or_else(|| attachment.get("name"))
+        .and_then(Value::as_str)
+        .unwrap_or(name);
+    let image = images(path)
+        .ok_or_else(|| ErrorMessage::technical(format!("No image for attachment '{}'", path)))?;
+    Ok(Some(Attachment {
+        image,
+        size: Vector::new(
+            number(attachment, "width", 0.0),
+            number(attachment, "height", 0.0),
+        ),
+        pose: pose(attachment),
+    }))
+}
+
+fn convert_animation(skeleton: &Skeleton, animation: &Value) -> PaddleResult<Animation> {
+    let mut tracks = Vec::new();
+    if let Some(bones) = animation.get("bones").and_then(Value::as_object) {
+        for (bone_name, timelines) in bones {
+            let bone = bone_by_name(skeleton, bone_name)?;
+            let setup = skeleton.bones()[bone].setup;
+            let mut track = BoneTrack::new(bone);
+            for key in array(timelines, "rotate") {
+                // Spine 4 calls it value, older versions angle
+                let angle = key
+                    .get("value")
+                    .or_else(|| key.get("angle"))
+                    .and_then(Value::as_f64)
+                    .unwrap_or(0.0) as f32;
+                track.rotation.push(keyframe(key, setup.rotation - angle));
+            }
+            for key in array(timelines, "translate") {
+                let offset = Vector::new(number(key, "x", 0.0), -number(key, "y", 0.0));
+                track
+                    .translation
+                    .push(keyframe(key, setup.translation + offset));
+            }
+            for key in array(timelines, "scale") {
+                let factor = Vector::new(number(key, "x", 1.0), number(key, "y", 1.0));
+                track.scale.push(keyframe(key, setup.scale.times(factor)));
+            }
+            tracks.push(track);
+        }
+    }
+    Ok(Animation::new(tracks))
+}
+
+fn keyframe<T>(key: &Value, value: T) -> Keyframe<T> {
+    let interpolation = match key.get("curve") {
+        None => Interpolation::Linear,
+        Some(Value::String(s)) if s == "stepped" => Interpolation::Stepped,
+        Some(Value::String(_)) => Interpolation::Linear,
+        Some(_) => Interpolation::Smooth,
+    };
+    Keyframe::new(number(key, "time", 0.0), value).with_interpolation(interpolation)
+}
+
+/// Local transformation stored in bones and attachments, converted to a y axis pointing down
+fn pose(value: &Value) -> BonePose {
+    BonePose::new(
+        (number(value, "x", 0.0), -number(value, "y", 0.0)),
+        -number(value, "rotation", 0.0),
+        (number(value, "scaleX", 1.0), number(value, "scaleY", 1.0)),
+    )
+}
+
+/// Colors in the format RRGGBBAA
+fn parse_color(hex: &str) -> PaddleResult<Color> {
+    let channel = |i: usize| {
+        hex.get(i..i + 2)
+            .and_then(|c| u8::from_str_radix(c, 16).ok())
+            .ok_or_else(|| ErrorMessage::technical(format!("Invalid color '{}'", hex)))
+    };
+    Ok(Color::from_rgba(
+        channel(0)?,
+        channel(2)?,
+        channel(4)?,
+        channel(6)? as f32 / 255.0,
+    ))
+}
+
+fn bone_by_name(skeleton: &Skeleton, name: &str) -> PaddleResult<usize> {
+    skeleton
+        .bone_index(name)
+        .ok_or_else(|| ErrorMessage::technical(format!("Unknown bone '{}'", name)))
+}
+
+fn array<'a>(value: &'a Value, key: &str) -> impl Iterator<Item = &'a Value> {
+    value
+        .get(key)
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+}
+
+fn string<'a>(value: &'a Value, key: &str) -> PaddleResult<&'a str> {
+    value
+        .get(key)
+        .and_then(Value::as_str)
+        .ok_or_else(|| ErrorMessage::technical(format!("Missing '{}' in {}", key, value)))
+}
+
+fn number(value: &Value, key: &str, default: f32) -> f32 {
+    value
+        .get(key)
+        .and_then(Value::as_f64)
+        .map_or(default, |n| n as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPINE: &str = r#"{
+        "skeleton": { "spine": "3.8.99" },
+        "bones": [
+            { "name": "root" },
+            { "name": "torso", "parent": "root", "y": 40, "rotation": 90, "length": 30 },
+            { "name": "head", "parent": "torso", "x": 30, "scaleX": 2 }
+        ],
+        "slots": [
+            { "name": "body", "bone": "torso", "attachment": "shirt" },
+            { "name": "face", "bone": "head", "color": "ff000080", "attachment": "smile" }
+        ],
+        "animations": {
+            "nod": {
+                "bones": {
+                    "head": {
+                        "rotate": [
+                            { "angle": 0 },
+                            { "time": 0.5, "angle": 20, "curve": "stepped" },
+                            { "time": 1, "angle": 0 }
+                        ],
+                        "translate": [{ "time": 0.25, "x": 1, "y": 2, "curve": [0.25, 0, 0.75, 1] }]
+                    }
+                }
+            }
+        }
+    }"#;
+
+    /// Attachments need images, which require a WebGL context
+    const SKINS: &str = r#"
+        "skins": [{
+            "name": "default",
+            "attachments": {
+                "body": {
+                    "shirt": { "x": 15, "rotation": -90, "width": 20, "height": 30 },
+                    "hitbox": { "type": "boundingbox", "vertexCount": 0, "vertices": [] }
+                },
+                "face": {
+                    "smile": { "path": "faces/smile", "width": 10, "height": 10 },
+                    "frown": { "path": "faces/frown", "width": 10, "height": 10 }
+                }
+            }
+        }]"#;
+
+    #[test]
+    fn import() {
+        let with_skins =
+            SPINE.replacen(r#""animations""#, &format!("{}, \"animations\"", SKINS), 1);
+        let missing_image = Skeleton::from_spine_json(&with_skins, |_| None);
+        assert!(missing_image.is_err());
+        let skeleton = Skeleton::from_spine_json(SPINE, |_| None).unwrap();
+
+        let torso = &skeleton.bones()[1];
+        assert_eq!(torso.parent(), Some(0));
+        assert_eq!(torso.setup, BonePose::new((0, -40), -90.0, (1, 1)));
+        assert_eq!(torso.length, 30.0);
+        // The torso points up on screen, so the head sits above it
+        let world = skeleton.world_transforms(&[
+            skeleton.bones()[0].setup,
+            skeleton.bones()[1].setup,
+            skeleton.bones()[2].setup,
+        ]);
+        assert_eq!(world[2] * Vector::ZERO, Vector::new(0, -70));
+
+        let body = &skeleton.slots()[0];
+        assert_eq!(body.bone(), 1);
+        assert!(body.setup_attachment.is_none());
+        let face = &skeleton.slots()[skeleton.slot_index("face").unwrap()];
+        assert_eq!(face.color, Color::from_rgba(255, 0, 0, 128.0 / 255.0));
+
+        let nod = skeleton.animation("nod").unwrap();
+        assert_eq!(nod.duration, 1.0);
+        let head = &nod.tracks[0];
+        assert_eq!(head.rotation[1].value, -20.0);
+        assert_eq!(head.rotation[1].interpolation, Interpolation::Stepped);
+        assert_eq!(head.translation[0].value, Vector::new(31, -2));
+        assert_eq!(head.translation[0].interpolation, Interpolation::Smooth);
+    }
+}
@@ -0,0 +1,183 @@
+use super::BonePose;
+use crate::quicksilver_compat::geom::{lerp, lerp_angle};
+use crate::Vector;
+
+/// How values change between a keyframe and the next
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Interpolation {
+    Linear,
+    /// Keep the value until the next keyframe
+    Stepped,
+    /// Start and end slowly
+    Smooth,
+}
+
+/// A value at a point in time
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Keyframe<T> {
+    /// In seconds since the start of the animation
+    pub time: f32,
+    pub value: T,
+    /// Interpolation towards the next keyframe
+    pub interpolation: Interpolation,
+}
+
+/// Keyframes of one bone, replacing the values of its setup pose while an animation plays.
+///
+/// Keyframes must be ordered by time. Properties without keyframes keep their setup value.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BoneTrack {
+    pub bone: usize,
+    pub translation: Vec<Keyframe<Vector>>,
+    /// In degrees, interpolated along the shorter way around
+    pub rotation: Vec<Keyframe<f32>>,
+    pub scale: Vec<Keyframe<Vector>>,
+}
+
+/// Keyframed poses of a skeleton over time
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Animation {
+    /// In seconds
+    pub duration: f32,
+    pub tracks: Vec<BoneTrack>,
+}
+
+impl<T> Keyframe<T> {
+    pub fn new(time: f32, value: T) -> Self {
+        Keyframe {
+            time,
+            value,
+            interpolation: Interpolation::Linear,
+        }
+    }
+    pub fn with_interpolation(mut self, interpolation: Interpolation) -> Self {
+        self.interpolation = interpolation;
+        self
+    }
+}
+
+impl BoneTrack {
+    pub fn new(bone: usize) -> Self {
+        BoneTrack {
+            bone,
+            ..Default::default()
+        }
+    }
+    pub fn with_translation(mut self, keyframe: Keyframe<Vector>) -> Self {
+        self.translation.push(keyframe);
+        self
+    }
+    pub fn with_rotation(mut self, keyframe: Keyframe<f32>) -> Self {
+        self.rotation.push(keyframe);
+        self
+    }
+    pub fn with_scale(mut self, keyframe: Keyframe<Vector>) -> Self {
+        self.scale.push(keyframe);
+        self
+    }
+    /// Overwrite the animated properties of a pose with their values at the given time
+    pub fn apply(&self, time: f32, pose: &mut BonePose) {
+        if let Some(v) = sample(&self.translation, time, lerp_vector) {
+            pose.translation = v;
+        }
+        if let Some(v) = sample(&self.rotation, time, lerp_angle) {
+            pose.rotation = v;
+        }
+        if let Some(v) = sample(&self.scale, time, lerp_vector) {
+            pose.scale = v;
+        }
+    }
+    /// Time of the last keyframe
+    fn end(&self) -> f32 {
+        last_time(&self.translation)
+            .max(last_time(&self.rotation))
+            .max(last_time(&self.scale))
+    }
+}
+
+impl Animation {
+    /// An animation lasting until its last keyframe
+    pub fn new(tracks: Vec<BoneTrack>) -> Self {
+        let duration = tracks.iter().map(BoneTrack::end).fold(0.0, f32::max);
+        Animation { duration, tracks }
+    }
+    pub fn with_duration(mut self, duration: f32) -> Self {
+        self.duration = duration;
+        self
+    }
+    /// Overwrite the local bone poses with the animated values at a time
+    pub fn apply(&self, time: f32, poses: &mut [BonePose]) {
+        for track in &self.tracks {
+            if let Some(pose) = poses.get_mut(track.bone) {
+                track.apply(time, pose);
+            }
+        }
+    }
+}
+
+fn sample<T: Copy>(frames: &[Keyframe<T>], time: f32, lerp: impl Fn(T, T, f32) -> T) -> Option<T> {
+    let next = frames.partition_point(|k| k.time <= time);
+    if next == 0 {
+        return frames.first().map(|k| k.value);
+    }
+    let prev = &frames[next - 1];
+    let next = match frames.get(next) {
+        Some(next) => next,
+        None => return Some(prev.value),
+    };
+    let t = (time - prev.time) / (next.time - prev.time);
+    let t = match prev.interpolation {
+        Interpolation::Linear => t,
+        Interpolation::Stepped => 0.0,
+        Interpolation::Smooth => t * t * (3.0 - 2.0 * t),
+    };
+    Some(lerp(prev.value, next.value, t))
+}
+
+fn last_time<T>(frames: &[Keyframe<T>]) -> f32 {
+    frames.last().map_or(0.0, |k| k.time)
+}
+
+fn lerp_vector(a: Vector, b: Vector, t: f32) -> Vector {
+    Vector::new(lerp(a.x, b.x, t), lerp(a.y, b.y, t))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interpolation() {
+        let frames = [
+            Keyframe::new(0.0, 0.0),
+            Keyframe::new(1.0, 10.0).with_interpolation(Interpolation::Stepped),
+            Keyframe::new(2.0, 20.0).with_interpolation(Interpolation::Smooth),
+            Keyframe::new(3.0, 30.0),
+        ];
+        let at = |time| sample(&frames, time, lerp).unwrap();
+        assert_eq!(at(-1.0), 0.0);
+        assert_eq!(at(0.5), 5.0);
+        assert_eq!(at(1.9), 10.0);
+        assert_eq!(at(2.5), 25.0);
+        assert!(at(2.25) < 22.5);
+        assert_eq!(at(5.0), 30.0);
+        assert_eq!(sample::<f32>(&[], 1.0, lerp), None);
+    }
+
+    #[test]
+    fn apply_to_pose() {
+        let track = BoneTrack::new(0)
+            .with_rotation(Keyframe::new(0.0, 350.0))
+            .with_rotation(Keyframe::new(1.0, 30.0))
+            .with_scale(Keyframe::new(0.0, Vector::ONE))
+            .with_scale(Keyframe::new(2.0, Vector::new(3, 1)));
+        let animation = Animation::new(vec![track]);
+        assert_eq!(animation.duration, 2.0);
+        let mut poses = [BonePose::new((5, 5), 0.0, Vector::ONE)];
+        animation.apply(0.5, &mut poses);
+        // Rotates the short way across 0 degrees
+        assert!((poses[0].rotation - 10.0).abs() < 1e-4);
+        assert_eq!(poses[0].scale, Vector::new(1.5, 1));
+        assert_eq!(poses[0].translation, Vector::new(5, 5));
+    }
+}
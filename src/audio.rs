@@ -0,0 +1,172 @@
+//! Sound playback through the Web Audio API.
+//!
+//! Sounds are loaded as `AudioClip`s, typically registered in a `LoadScheduler`, and played through the `Audio` functions from anywhere in the game.
+//! Each sound is played on a bus (music, effects or a custom bus), which allows changing the volume of a group of sounds at once.
+//!
+//! Browsers only allow audio playback after the user interacted with the page.
+//! Paddle resumes audio on the first input and shares an `AudioUnlocked` event with all frames.
+//! Until then, one-shot sounds are skipped while looping sounds start as soon as audio is unlocked.
+//!
+//! Sounds played at a position in game coordinates are panned and attenuated relative to the `SoundListener`.
+//!
+//! ```no_run
+//! # use paddle::*;
+//! # fn load() {
+//! LoadScheduler::new()
+//!     .with(AudioClip::load("click.ogg"), "Loading sounds")
+//!     .attach_to_domain();
+//! # }
+//! # fn play(click: &AudioClip, song: &AudioClip) {
+//! Audio::play(click);
+//! let music = Audio::play_ex(song, PlayOptions::music().with_fade_in(2000.0));
+//! Audio::fade_out(music, 500.0);
+//! # }
+//! ```
+
+mod clip;
+mod mixer;
+mod spatial;
+mod system;
+
+pub use clip::AudioClip;
+pub use mixer::{decibel_to_gain, AudioBus, Fade};
+pub use spatial::*;
+pub(crate) use system::{AudioSystem, AudioUnlock};
+
+use crate::{Rectangle, Vector};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use system::AudioCommand;
+
+/// Facade to the audio system. Commands are forwarded to the audio system and executed asynchronously.
+///
+/// Volumes are linear factors, where 1.0 is the original volume. Fade durations are in milliseconds.
+pub struct Audio;
+
+/// Refers to a sound that has been started, to stop it or change its volume later.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SoundHandle(usize);
+
+/// Shared with all frames once the browser allows audio playback.
+///
+/// Listen to it with `FrameHandle::listen`.
+#[derive(Clone, Copy, Debug)]
+pub struct AudioUnlocked;
+
+/// How a sound is played
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PlayOptions {
+    pub bus: AudioBus,
+    pub volume: f32,
+    pub looping: bool,
+    pub fade_in_ms: f64,
+    /// Position in game coordinates, for panning and attenuation relative to the listener
+    pub position: Option<Vector>,
+    pub distance_model: DistanceModel,
+}
+
+impl Default for PlayOptions {
+    fn default() -> Self {
+        Self {
+            bus: AudioBus::EFFECTS,
+            volume: 1.0,
+            looping: false,
+            fade_in_ms: 0.0,
+            position: None,
+            distance_model: DistanceModel::default(),
+        }
+    }
+}
+
+impl PlayOptions {
+    /// Looping playback on the music bus
+    pub fn music() -> Self {
+        Self {
+            bus: AudioBus::MUSIC,
+            looping: true,
+            ..Default::default()
+        }
+    }
+    pub fn on_bus(mut self, bus: AudioBus) -> Self {
+        self.bus = bus;
+        self
+    }
+    pub fn with_volume(mut self, volume: f32) -> Self {
+        self.volume = volume;
+        self
+    }
+    pub fn looping(mut self) -> Self {
+        self.looping = true;
+        self
+    }
+    pub fn with_fade_in(mut self, fade_in_ms: f64) -> Self {
+        self.fade_in_ms = fade_in_ms;
+        self
+    }
+    pub fn at_position(mut self, position: impl Into<Vector>) -> Self {
+        self.position = Some(position.into());
+        self
+    }
+    pub fn with_distance_model(mut self, model: DistanceModel) -> Self {
+        self.distance_model = model;
+        self
+    }
+}
+
+impl Audio {
+    /// Play a clip once, on the effects bus
+    pub fn play(clip: &AudioClip) -> SoundHandle {
+        Self::play_ex(clip, PlayOptions::default())
+    }
+    /// Play a clip in a loop, on the music bus
+    pub fn play_music(clip: &AudioClip) -> SoundHandle {
+        Self::play_ex(clip, PlayOptions::music())
+    }
+    pub fn play_ex(clip: &AudioClip, options: PlayOptions) -> SoundHandle {
+        let handle = SoundHandle::next();
+        send(AudioCommand::Play(handle, clip.clone(), options));
+        handle
+    }
+    pub fn stop(sound: SoundHandle) {
+        Self::fade_out(sound, 0.0);
+    }
+    /// Stop a sound after fading it out
+    pub fn fade_out(sound: SoundHandle, fade_ms: f64) {
+        send(AudioCommand::Stop(sound, fade_ms / 1000.0));
+    }
+    pub fn set_volume(sound: SoundHandle, volume: f32, fade_ms: f64) {
+        send(AudioCommand::SetVolume(sound, volume, fade_ms / 1000.0));
+    }
+    pub fn set_bus_volume(bus: AudioBus, volume: f32, fade_ms: f64) {
+        send(AudioCommand::SetBusVolume(bus, volume, fade_ms / 1000.0));
+    }
+    pub fn set_master_volume(volume: f32, fade_ms: f64) {
+        send(AudioCommand::SetMasterVolume(volume, fade_ms / 1000.0));
+    }
+    /// Play a clip once at a position in game coordinates
+    pub fn play_at(clip: &AudioClip, position: impl Into<Vector>) -> SoundHandle {
+        Self::play_ex(clip, PlayOptions::default().at_position(position))
+    }
+    /// Move a sound that has been started with a position
+    pub fn set_position(sound: SoundHandle, position: impl Into<Vector>) {
+        send(AudioCommand::SetPosition(sound, position.into()));
+    }
+    pub fn set_listener(listener: SoundListener) {
+        send(AudioCommand::SetListener(listener));
+    }
+    /// Place the listener in the center of a region, e.g. the area visible through a camera or `DisplayArea::region()`.
+    /// Call this again whenever the region moves.
+    pub fn follow_region(region: &Rectangle) {
+        Self::set_listener(SoundListener::following(region));
+    }
+}
+
+impl SoundHandle {
+    fn next() -> Self {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        SoundHandle(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+fn send(cmd: AudioCommand) {
+    nuts::send_to::<AudioSystem, _>(cmd);
+}
@@ -0,0 +1,57 @@
+use crate::{load_file, ErrorMessage, JsError, PaddleResult};
+use js_sys::{ArrayBuffer, Promise, Uint8Array};
+use std::{cell::Cell, rc::Rc};
+use wasm_bindgen::JsCast;
+use web_sys::AudioBuffer;
+
+/// A decoded sound, ready to be played.
+///
+/// Clips are cheap to clone, the audio data is shared.
+#[derive(Clone, Debug)]
+pub struct AudioClip {
+    pub(super) buffer: AudioBuffer,
+}
+
+// Message sent after the encoded audio has been downloaded and it is ready to be decoded.
+// The AudioSystem handles these messages, since decoding requires the audio context.
+pub(super) struct DecodeAudioMessage {
+    pub(super) payload: Rc<Cell<DecodeAudioPayload>>,
+}
+#[derive(Default)]
+pub(super) enum DecodeAudioPayload {
+    Request(ArrayBuffer),
+    Response(Promise),
+    #[default]
+    Done,
+}
+
+impl AudioClip {
+    /// Download and decode a sound file. All formats supported by the browser can be used.
+    ///
+    /// The returned future can be registered in a `LoadScheduler`.
+    pub async fn load(src: &str) -> PaddleResult<Self> {
+        let data = load_file(src).await?;
+        let array_buffer = Uint8Array::from(data.as_slice()).buffer();
+
+        let cell = Rc::new(Cell::new(DecodeAudioPayload::Request(array_buffer)));
+        let msg = DecodeAudioMessage {
+            payload: cell.clone(),
+        };
+        nuts::publish_awaiting_response(msg).await;
+
+        let promise = match cell.take() {
+            DecodeAudioPayload::Response(promise) => promise,
+            _ => return Err(ErrorMessage::technical("Audio decoding failed".to_owned())),
+        };
+        let buffer = wasm_bindgen_futures::JsFuture::from(promise)
+            .await
+            .map_err(JsError::from_js_value)?
+            .dyn_into::<AudioBuffer>()
+            .map_err(JsError::from_js_value)?;
+        Ok(AudioClip { buffer })
+    }
+    /// Length of the clip in milliseconds
+    pub fn duration_ms(&self) -> f64 {
+        self.buffer.duration() * 1000.0
+    }
+}
@@ -0,0 +1,162 @@
+//! Volume bookkeeping for buses and sounds, independent of Web Audio.
+//!
+//! Web Audio parameters can be scheduled to ramp between values but reading them back while a ramp is active is not reliable across browsers.
+//! Therefore, the volume of every gain node is tracked here, which allows starting a new fade from wherever the previous one has been interrupted.
+
+use std::collections::HashMap;
+
+/// A group of sounds which share a volume setting, e.g. music or sound effects.
+///
+/// Every bus is connected to the master volume.
+/// Custom buses are created on first use.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct AudioBus(pub &'static str);
+
+impl AudioBus {
+    pub const MUSIC: AudioBus = AudioBus("music");
+    pub const EFFECTS: AudioBus = AudioBus("effects");
+}
+
+impl Default for AudioBus {
+    fn default() -> Self {
+        AudioBus::EFFECTS
+    }
+}
+
+/// Linear transition between two volumes. Times are in seconds.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Fade {
+    pub from: f32,
+    pub to: f32,
+    pub start: f64,
+    pub duration: f64,
+}
+
+impl Fade {
+    pub fn new(from: f32, to: f32, start: f64, duration: f64) -> Self {
+        Self {
+            from,
+            to,
+            start,
+            duration: duration.max(0.0),
+        }
+    }
+    /// A volume that does not change over time
+    pub fn constant(volume: f32) -> Self {
+        Self::new(volume, volume, 0.0, 0.0)
+    }
+    pub fn value_at(&self, t: f64) -> f32 {
+        if t >= self.end() {
+            self.to
+        } else if t <= self.start {
+            self.from
+        } else {
+            let progress = ((t - self.start) / self.duration) as f32;
+            self.from + (self.to - self.from) * progress
+        }
+    }
+    pub fn end(&self) -> f64 {
+        self.start + self.duration
+    }
+    pub fn is_done(&self, t: f64) -> bool {
+        t >= self.end()
+    }
+    /// Starts a new fade towards `to`, beginning at the value this fade has at time `now`.
+    pub fn retarget(&self, to: f32, now: f64, duration: f64) -> Fade {
+        Fade::new(self.value_at(now), to, now, duration)
+    }
+}
+
+/// Volumes of the master output and of all buses
+#[derive(Clone, Debug)]
+pub(crate) struct Mixer {
+    master: Fade,
+    buses: HashMap<AudioBus, Fade>,
+}
+
+impl Mixer {
+    pub fn new() -> Self {
+        Self {
+            master: Fade::constant(1.0),
+            buses: HashMap::new(),
+        }
+    }
+    pub fn master_volume(&self, t: f64) -> f32 {
+        self.master.value_at(t)
+    }
+    pub fn bus_volume(&self, bus: AudioBus, t: f64) -> f32 {
+        self.buses.get(&bus).map(|f| f.value_at(t)).unwrap_or(1.0)
+    }
+    /// Returns the fade to be scheduled on the master gain node
+    pub fn fade_master(&mut self, to: f32, now: f64, duration: f64) -> Fade {
+        self.master = self.master.retarget(to, now, duration);
+        self.master
+    }
+    /// Returns the fade to be scheduled on the gain node of the bus
+    pub fn fade_bus(&mut self, bus: AudioBus, to: f32, now: f64, duration: f64) -> Fade {
+        let fade = self.buses.entry(bus).or_insert_with(|| Fade::constant(1.0));
+        *fade = fade.retarget(to, now, duration);
+        *fade
+    }
+}
+
+/// Converts a volume given in decibel to a linear gain factor. (0 dB is unchanged volume, -6 dB is roughly half)
+pub fn decibel_to_gain(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fade_interpolation() {
+        let fade = Fade::new(0.0, 1.0, 2.0, 4.0);
+        assert_eq!(fade.value_at(0.0), 0.0);
+        assert_eq!(fade.value_at(3.0), 0.25);
+        assert_eq!(fade.value_at(4.0), 0.5);
+        assert_eq!(fade.value_at(7.0), 1.0);
+        assert!(!fade.is_done(5.9));
+        assert!(fade.is_done(6.0));
+    }
+
+    #[test]
+    fn instant_fade() {
+        let fade = Fade::new(1.0, 0.0, 1.0, 0.0);
+        assert_eq!(fade.value_at(0.5), 1.0);
+        assert_eq!(fade.value_at(1.0), 0.0);
+        let negative = Fade::new(1.0, 0.0, 1.0, -3.0);
+        assert_eq!(negative.duration, 0.0);
+    }
+
+    #[test]
+    fn retarget_mid_fade() {
+        let fade_out = Fade::new(1.0, 0.0, 0.0, 2.0);
+        let fade_in = fade_out.retarget(1.0, 1.0, 1.0);
+        assert_eq!(fade_in.from, 0.5);
+        assert_eq!(fade_in.value_at(1.5), 0.75);
+        assert_eq!(fade_in.value_at(2.0), 1.0);
+    }
+
+    #[test]
+    fn mixing() {
+        let mut mixer = Mixer::new();
+        assert_eq!(mixer.bus_volume(AudioBus::MUSIC, 0.0), 1.0);
+        mixer.fade_bus(AudioBus::MUSIC, 0.5, 0.0, 0.0);
+        mixer.fade_master(0.5, 0.0, 2.0);
+        assert_eq!(mixer.master_volume(1.0), 0.75);
+        assert_eq!(mixer.bus_volume(AudioBus::MUSIC, 1.0), 0.5);
+        assert_eq!(mixer.bus_volume(AudioBus::EFFECTS, 1.0), 1.0);
+        assert_eq!(mixer.bus_volume(AudioBus("ui"), 1.0), 1.0);
+        let fade = mixer.fade_bus(AudioBus::MUSIC, 0.0, 1.0, 1.0);
+        assert_eq!(fade, Fade::new(0.5, 0.0, 1.0, 1.0));
+        assert_eq!(mixer.bus_volume(AudioBus::MUSIC, 1.5), 0.25);
+    }
+
+    #[test]
+    fn decibel() {
+        assert_eq!(decibel_to_gain(0.0), 1.0);
+        assert!((decibel_to_gain(-20.0) - 0.1).abs() < 1e-6);
+        assert!((decibel_to_gain(-6.0) - 0.501).abs() < 1e-3);
+    }
+}
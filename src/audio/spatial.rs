@@ -0,0 +1,200 @@
+//! Panning and attenuation of sounds by their position in game coordinates.
+//!
+//! The distance models follow the formulas of the Web Audio `PannerNode`, but only stereo panning is applied.
+
+use crate::quicksilver_compat::Shape;
+use crate::{Rectangle, Vector};
+
+/// Smallest `ref_distance` used by the inverse and exponential models, which divide by it
+const MIN_REF_DISTANCE: f32 = 1e-3;
+
+/// Defines how the volume of a sound decreases with distance to the listener.
+///
+/// Distances are in game coordinates.
+/// Sounds closer than `ref_distance` are played at full volume.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DistanceModel {
+    /// Volume decreases linearly until `max_distance`, where it is reduced by the factor `rolloff` (1.0 for silence).
+    Linear {
+        ref_distance: f32,
+        max_distance: f32,
+        rolloff: f32,
+    },
+    /// Volume decreases with the inverse of the distance, which is how sound behaves physically.
+    Inverse { ref_distance: f32, rolloff: f32 },
+    /// Volume decreases exponentially with the distance. Higher `rolloff` makes it drop faster.
+    Exponential { ref_distance: f32, rolloff: f32 },
+}
+
+impl Default for DistanceModel {
+    fn default() -> Self {
+        DistanceModel::Inverse {
+            ref_distance: 100.0,
+            rolloff: 1.0,
+        }
+    }
+}
+
+impl DistanceModel {
+    /// Volume factor between 0.0 and 1.0 for a sound at the given distance
+    pub fn gain(&self, distance: f32) -> f32 {
+        let gain = match *self {
+            DistanceModel::Linear {
+                ref_distance,
+                max_distance,
+                rolloff,
+            } => {
+                if max_distance <= ref_distance {
+                    return if distance <= ref_distance { 1.0 } else { 0.0 };
+                }
+                let d = distance.max(ref_distance).min(max_distance);
+                1.0 - rolloff.min(1.0) * (d - ref_distance) / (max_distance - ref_distance)
+            }
+            DistanceModel::Inverse {
+                ref_distance,
+                rolloff,
+            } => {
+                let ref_distance = ref_distance.max(MIN_REF_DISTANCE);
+                let d = distance.max(ref_distance);
+                ref_distance / (ref_distance + rolloff * (d - ref_distance))
+            }
+            DistanceModel::Exponential {
+                ref_distance,
+                rolloff,
+            } => {
+                let ref_distance = ref_distance.max(MIN_REF_DISTANCE);
+                let d = distance.max(ref_distance);
+                (d / ref_distance).powf(-rolloff)
+            }
+        };
+        gain.clamp(0.0, 1.0)
+    }
+}
+
+/// The position sounds are heard from, in game coordinates.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SoundListener {
+    pub position: Vector,
+    /// Horizontal distance at which a sound is panned completely to one side
+    pub pan_distance: f32,
+}
+
+/// Volume and stereo panning of a positioned sound
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Spatialized {
+    /// Volume factor between 0.0 and 1.0
+    pub gain: f32,
+    /// From -1.0 (left) to 1.0 (right)
+    pub pan: f32,
+}
+
+impl Default for SoundListener {
+    fn default() -> Self {
+        Self {
+            position: Vector::ZERO,
+            pan_distance: 500.0,
+        }
+    }
+}
+
+impl SoundListener {
+    pub fn new(position: impl Into<Vector>, pan_distance: f32) -> Self {
+        Self {
+            position: position.into(),
+            pan_distance,
+        }
+    }
+    /// A listener in the center of the region, which pans sounds at its left and right edges completely to one side.
+    ///
+    /// Use this with the region of a camera or a frame.
+    pub fn following(region: &Rectangle) -> Self {
+        Self::new(region.center(), region.width() / 2.0)
+    }
+    pub fn spatialize(&self, source: Vector, model: &DistanceModel) -> Spatialized {
+        let offset = source - self.position;
+        let pan = if self.pan_distance > 0.0 {
+            (offset.x / self.pan_distance).clamp(-1.0, 1.0)
+        } else if offset.x != 0.0 {
+            offset.x.signum()
+        } else {
+            0.0
+        };
+        Spatialized {
+            gain: model.gain(offset.len()),
+            pan,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-5
+    }
+
+    #[test]
+    fn linear_model() {
+        let model = DistanceModel::Linear {
+            ref_distance: 10.0,
+            max_distance: 110.0,
+            rolloff: 1.0,
+        };
+        assert_eq!(model.gain(0.0), 1.0);
+        assert_eq!(model.gain(10.0), 1.0);
+        assert!(close(model.gain(60.0), 0.5));
+        assert_eq!(model.gain(500.0), 0.0);
+    }
+
+    #[test]
+    fn inverse_model() {
+        let model = DistanceModel::Inverse {
+            ref_distance: 10.0,
+            rolloff: 1.0,
+        };
+        assert_eq!(model.gain(5.0), 1.0);
+        assert!(close(model.gain(20.0), 0.5));
+        assert!(close(model.gain(40.0), 0.25));
+    }
+
+    #[test]
+    fn exponential_model() {
+        let model = DistanceModel::Exponential {
+            ref_distance: 10.0,
+            rolloff: 2.0,
+        };
+        assert_eq!(model.gain(1.0), 1.0);
+        assert!(close(model.gain(20.0), 0.25));
+    }
+
+    #[test]
+    fn zero_ref_distance() {
+        for model in [
+            DistanceModel::Inverse {
+                ref_distance: 0.0,
+                rolloff: 1.0,
+            },
+            DistanceModel::Exponential {
+                ref_distance: 0.0,
+                rolloff: 1.0,
+            },
+        ] {
+            assert_eq!(model.gain(0.0), 1.0);
+            let far = model.gain(10.0);
+            assert!(far.is_finite() && far < 0.01);
+        }
+    }
+
+    #[test]
+    fn panning() {
+        let listener = SoundListener::following(&Rectangle::new((0, 0), (200, 100)));
+        assert_eq!(listener.position, Vector::new(100, 50));
+        let model = DistanceModel::default();
+        assert_eq!(listener.spatialize(Vector::new(100, 0), &model).pan, 0.0);
+        assert_eq!(listener.spatialize(Vector::new(50, 50), &model).pan, -0.5);
+        assert_eq!(listener.spatialize(Vector::new(500, 50), &model).pan, 1.0);
+        let s = listener.spatialize(Vector::new(300, 50), &model);
+        assert!(close(s.gain, 0.5));
+    }
+}
@@ -0,0 +1,287 @@
+use super::clip::{DecodeAudioMessage, DecodeAudioPayload};
+use super::mixer::{AudioBus, Fade, Mixer};
+use super::{AudioClip, AudioUnlocked, DistanceModel, PlayOptions, SoundHandle, SoundListener};
+use crate::{share, ErrorMessage, EventGate, JsError, NutsCheck, PaddleResult, Vector};
+use std::collections::HashMap;
+use web_sys::{
+    AudioBufferSourceNode, AudioContext, AudioContextState, AudioParam, GainNode, StereoPannerNode,
+};
+
+/// Time constant for smoothing changes of position, in seconds
+const SPATIAL_SMOOTHING: f64 = 0.02;
+
+/// Requests sent to the audio system by the `Audio` facade
+pub(super) enum AudioCommand {
+    Play(SoundHandle, AudioClip, PlayOptions),
+    /// Stop a sound after fading out for the given number of seconds
+    Stop(SoundHandle, f64),
+    SetVolume(SoundHandle, f32, f64),
+    SetBusVolume(AudioBus, f32, f64),
+    SetMasterVolume(f32, f64),
+    SetPosition(SoundHandle, Vector),
+    SetListener(SoundListener),
+}
+
+/// Published by the JS gate, once the browser allows audio playback
+pub(crate) struct AudioUnlock;
+
+/// Owns the Web Audio graph.
+///
+/// Every sound has its own gain node, which is connected to the gain node of a bus, which is connected to the master gain node.
+/// Positioned sounds have an additional gain node for attenuation and a stereo panner in between.
+/// The audio context is only created when it is first needed.
+pub(crate) struct AudioSystem {
+    web_audio: Option<WebAudio>,
+    mixer: Mixer,
+    sounds: HashMap<SoundHandle, PlayingSound>,
+    listener: SoundListener,
+}
+
+struct WebAudio {
+    ctx: AudioContext,
+    master: GainNode,
+    buses: HashMap<AudioBus, GainNode>,
+}
+
+struct PlayingSound {
+    source: AudioBufferSourceNode,
+    gain: GainNode,
+    volume: Fade,
+    /// Time when a non-looping sound has finished playing
+    end: Option<f64>,
+    spatial: Option<SpatialSound>,
+}
+
+struct SpatialSound {
+    attenuation: GainNode,
+    panner: StereoPannerNode,
+    position: Vector,
+    model: DistanceModel,
+}
+
+impl AudioSystem {
+    pub(crate) fn init() {
+        let system = AudioSystem {
+            web_audio: None,
+            mixer: Mixer::new(),
+            sounds: HashMap::new(),
+            listener: SoundListener::default(),
+        };
+        let aid = nuts::new_activity(system);
+        aid.private_channel(|a, cmd| {
+            a.command(cmd).nuts_check();
+        });
+        aid.subscribe(|a, msg: &DecodeAudioMessage| {
+            if let DecodeAudioPayload::Request(data) = msg.payload.take() {
+                if let Some(web_audio) = a.web_audio().nuts_check() {
+                    if let Some(promise) = web_audio
+                        .ctx
+                        .decode_audio_data(&data)
+                        .map_err(JsError::from_js_value)
+                        .map_err(ErrorMessage::from)
+                        .nuts_check()
+                    {
+                        msg.payload.replace(DecodeAudioPayload::Response(promise));
+                    }
+                }
+            }
+        });
+        aid.subscribe(|_, _: &AudioUnlock| share(AudioUnlocked));
+    }
+
+    fn web_audio(&mut self) -> PaddleResult<&mut WebAudio> {
+        if self.web_audio.is_none() {
+            let ctx = AudioContext::new().map_err(JsError::from_js_value)?;
+            let master = ctx.create_gain().map_err(JsError::from_js_value)?;
+            master.gain().set_value(self.mixer.master_volume(0.0));
+            master
+                .connect_with_audio_node(&ctx.destination())
+                .map_err(JsError::from_js_value)?;
+            EventGate::unlock_audio(ctx.clone());
+            self.web_audio = Some(WebAudio {
+                ctx,
+                master,
+                buses: HashMap::new(),
+            });
+        }
+        Ok(self.web_audio.as_mut().unwrap())
+    }
+
+    fn command(&mut self, cmd: AudioCommand) -> PaddleResult<()> {
+        let now = self.web_audio()?.ctx.current_time();
+        match cmd {
+            AudioCommand::Play(handle, clip, options) => {
+                self.remove_finished(now);
+                self.play(handle, clip, options, now)?;
+            }
+            AudioCommand::Stop(handle, fade) => {
+                if let Some(sound) = self.sounds.remove(&handle) {
+                    let fade = sound.volume.retarget(0.0, now, fade);
+                    schedule(&sound.gain.gain(), &fade)?;
+                    stop_at(&sound.source, fade.end())?;
+                }
+            }
+            AudioCommand::SetVolume(handle, volume, fade) => {
+                if let Some(sound) = self.sounds.get_mut(&handle) {
+                    sound.volume = sound.volume.retarget(volume, now, fade);
+                    schedule(&sound.gain.gain(), &sound.volume)?;
+                }
+            }
+            AudioCommand::SetBusVolume(bus, volume, fade) => {
+                let fade = self.mixer.fade_bus(bus, volume, now, fade);
+                let gain = self.bus(bus, now)?;
+                schedule(&gain.gain(), &fade)?;
+            }
+            AudioCommand::SetMasterVolume(volume, fade) => {
+                let fade = self.mixer.fade_master(volume, now, fade);
+                schedule(&self.web_audio()?.master.gain(), &fade)?;
+            }
+            AudioCommand::SetPosition(handle, position) => {
+                let listener = self.listener;
+                if let Some(spatial) = self
+                    .sounds
+                    .get_mut(&handle)
+                    .and_then(|s| s.spatial.as_mut())
+                {
+                    spatial.position = position;
+                    spatial.update(&listener, now)?;
+                }
+            }
+            AudioCommand::SetListener(listener) => {
+                self.listener = listener;
+                for spatial in self.sounds.values().filter_map(|s| s.spatial.as_ref()) {
+                    spatial.update(&listener, now)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn play(
+        &mut self,
+        handle: SoundHandle,
+        clip: AudioClip,
+        options: PlayOptions,
+        now: f64,
+    ) -> PaddleResult<()> {
+        let bus = self.bus(options.bus, now)?;
+        let web_audio = self.web_audio()?;
+        // Effects played before the user interacted with the page would all be played at once when audio is unlocked.
+        // Music is kept, so that it starts playing as soon as possible.
+        if web_audio.ctx.state() != AudioContextState::Running && !options.looping {
+            return Ok(());
+        }
+        let ctx = &web_audio.ctx;
+        let source = ctx.create_buffer_source().map_err(JsError::from_js_value)?;
+        source.set_buffer(Some(&clip.buffer));
+        source.set_loop(options.looping);
+        let gain = ctx.create_gain().map_err(JsError::from_js_value)?;
+        let volume = Fade::new(0.0, options.volume, now, options.fade_in_ms / 1000.0);
+        schedule(&gain.gain(), &volume)?;
+        source
+            .connect_with_audio_node(&gain)
+            .map_err(JsError::from_js_value)?;
+        let spatial = match options.position {
+            Some(position) => {
+                let spatial = SpatialSound {
+                    attenuation: ctx.create_gain().map_err(JsError::from_js_value)?,
+                    panner: ctx.create_stereo_panner().map_err(JsError::from_js_value)?,
+                    position,
+                    model: options.distance_model,
+                };
+                let s = self.listener.spatialize(position, &spatial.model);
+                spatial.attenuation.gain().set_value(s.gain);
+                spatial.panner.pan().set_value(s.pan);
+                gain.connect_with_audio_node(&spatial.attenuation)
+                    .and_then(|_| spatial.attenuation.connect_with_audio_node(&spatial.panner))
+                    .and_then(|_| spatial.panner.connect_with_audio_node(&bus))
+                    .map_err(JsError::from_js_value)?;
+                Some(spatial)
+            }
+            None => {
+                gain.connect_with_audio_node(&bus)
+                    .map_err(JsError::from_js_value)?;
+                None
+            }
+        };
+        source.start().map_err(JsError::from_js_value)?;
+        let end = if options.looping {
+            None
+        } else {
+            Some(now + clip.buffer.duration())
+        };
+        self.sounds.insert(
+            handle,
+            PlayingSound {
+                source,
+                gain,
+                volume,
+                end,
+                spatial,
+            },
+        );
+        Ok(())
+    }
+
+    /// Gain node of the bus, created and connected to the master on first use
+    fn bus(&mut self, bus: AudioBus, now: f64) -> PaddleResult<GainNode> {
+        let volume = self.mixer.bus_volume(bus, now);
+        let web_audio = self.web_audio()?;
+        if let Some(gain) = web_audio.buses.get(&bus) {
+            return Ok(gain.clone());
+        }
+        let gain = web_audio
+            .ctx
+            .create_gain()
+            .map_err(JsError::from_js_value)?;
+        gain.gain().set_value(volume);
+        gain.connect_with_audio_node(&web_audio.master)
+            .map_err(JsError::from_js_value)?;
+        web_audio.buses.insert(bus, gain.clone());
+        Ok(gain)
+    }
+
+    /// Forget one-shot sounds which have ended, the browser cleans up their nodes.
+    fn remove_finished(&mut self, now: f64) {
+        self.sounds
+            .retain(|_, sound| sound.end.is_none_or(|end| end > now));
+    }
+}
+
+impl SpatialSound {
+    fn update(&self, listener: &SoundListener, now: f64) -> PaddleResult<()> {
+        let s = listener.spatialize(self.position, &self.model);
+        self.attenuation
+            .gain()
+            .set_target_at_time(s.gain, now, SPATIAL_SMOOTHING)
+            .and_then(|_| {
+                self.panner
+                    .pan()
+                    .set_target_at_time(s.pan, now, SPATIAL_SMOOTHING)
+            })
+            .map_err(JsError::from_js_value)?;
+        Ok(())
+    }
+}
+
+/// Stop playback at the given time of the audio context.
+///
+/// Goes through the parent type, the method on `AudioBufferSourceNode` is deprecated in newer web-sys versions.
+/// Older versions deprecate the parent type instead, hence the `allow`.
+#[allow(deprecated)]
+fn stop_at(source: &AudioBufferSourceNode, when: f64) -> PaddleResult<()> {
+    AsRef::<web_sys::AudioScheduledSourceNode>::as_ref(source)
+        .stop_with_when(when)
+        .map_err(JsError::from_js_value)?;
+    Ok(())
+}
+
+/// Replace all scheduled changes of the parameter with the fade
+fn schedule(param: &AudioParam, fade: &Fade) -> PaddleResult<()> {
+    param
+        .cancel_scheduled_values(fade.start)
+        .and_then(|p| p.set_value_at_time(fade.from, fade.start))
+        .and_then(|p| p.linear_ramp_to_value_at_time(fade.to, fade.end()))
+        .map_err(JsError::from_js_value)?;
+    Ok(())
+}
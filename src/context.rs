@@ -1,4 +1,7 @@
-use crate::{DisplayArea, Domain, PaddleResult, Rectangle, SchedulingContext, WebGLCanvas};
+use crate::{
+    AutoResizer, DisplayArea, Domain, FullscreenListener, PaddleResult, Rectangle,
+    SchedulingContext, WebGLCanvas,
+};
 use div::DivHandle;
 use nuts::DomainState;
 
@@ -14,12 +17,17 @@ pub(crate) struct Context {
 impl Context {
     pub(super) fn init(config: PaddleConfig) -> PaddleResult<()> {
         let scheduling = SchedulingContext::new(config.display.update_delay_ms)?;
+        let auto_resize = config.display.auto_resize;
         let display = Display::new(config.display)?.into();
         let ctx = Self {
             display,
             scheduling,
         };
         nuts::store_to_domain(&Domain::Frame, ctx);
+        FullscreenListener::init()?;
+        if let Some(auto_resize) = auto_resize {
+            AutoResizer::init(auto_resize)?;
+        }
         Ok(())
     }
     pub(crate) fn display_region(&mut self, region: Rectangle, div: DivHandle) -> &mut DisplayArea {
@@ -35,4 +43,4 @@ impl Display {
         let context = domain.get_mut::<Context>();
         context.display.full_mut()
     }
-}
\ No newline at end of file
+}
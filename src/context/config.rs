@@ -1,5 +1,7 @@
 use crate::{graphics::TextureConfig, quicksilver_compat::Color};
-use crate::{CanvasConfig, DisplayConfig, GpuConfig, Rectangle, Vector};
+use crate::{
+    AutoResizeConfig, CanvasConfig, DisplayConfig, GpuConfig, Rectangle, ScalingPolicy, Vector,
+};
 use web_sys::HtmlCanvasElement;
 
 #[derive(Default)]
@@ -24,6 +26,20 @@ impl PaddleConfig {
         self.display.pixels = pixels.into();
         self
     }
+    /// Coordinates used to draw and place objects, independent of the resolution. (Defaults to the resolution)
+    pub fn with_game_coordinates(mut self, size: impl Into<Vector>) -> Self {
+        self.display.game_coordinates = Some(size.into());
+        self
+    }
+    pub fn with_scaling_policy(mut self, scaling: ScalingPolicy) -> Self {
+        self.display.scaling = scaling;
+        self
+    }
+    /// Resize the display automatically when the browser window changes
+    pub fn with_auto_resize(mut self, config: AutoResizeConfig) -> Self {
+        self.display.auto_resize = Some(config);
+        self
+    }
     pub fn with_texture_config(mut self, texture_config: TextureConfig) -> Self {
         self.display.texture_config = texture_config;
         self
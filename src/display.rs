@@ -6,17 +6,23 @@
 //! In Paddle, drawing an object to the WebGL canvas consists of two separate phases on the CPU, tesselation + rendering.
 //! The display accepts pre-tessellated and raw objects, using either `draw_mesh()` or `draw()` (on DisplayArea).
 
+mod auto_resize;
 mod canvas;
 mod display_area;
+mod fullscreen;
 mod gpu;
 mod render;
+mod scaling;
 mod text;
 
+pub use auto_resize::*;
 pub use canvas::*;
 pub use display_area::*;
 use div::DivHandle;
+pub use fullscreen::*;
 pub use gpu::{GpuConfig, GpuMesh, GpuTriangle, GpuVertex};
 pub use render::*;
+pub use scaling::*;
 pub use text::*;
 
 use crate::quicksilver_compat::Background;
@@ -34,7 +40,12 @@ pub struct Display {
     /// Position relative to browser page (in browser coordinates)
     browser_region: Rectangle,
     /// Game / World coordinates. Used to refer to game objects and UI elements independently of resolution or size in the browser.
+    /// This is the visible area, which can be larger than the configured game coordinates, depending on the scaling policy.
     game_coordinates: Vector,
+    /// Game coordinates as configured, before applying the scaling policy
+    base_game_coordinates: Vector,
+    /// How the display fits into the browser window
+    scaling: ScalingPolicy,
     /// A canvas with WebGL capabilities (covering the full display area)
     canvas: WebGLCanvas,
     /// Screen background color. A clear to this color is invoked every frame.
@@ -48,22 +59,30 @@ pub struct Display {
 pub struct DisplayConfig {
     pub canvas: CanvasConfig,
     pub pixels: Vector,
+    /// Game coordinates, if they should differ from the pixel resolution
+    pub game_coordinates: Option<Vector>,
+    pub scaling: ScalingPolicy,
     pub texture_config: TextureConfig,
     pub gpu_config: GpuConfig,
     pub update_delay_ms: i32,
     pub background: Option<Color>,
     pub capture_touch: bool,
+    /// Listen for browser events and keep the display fitted automatically. (Disabled by default)
+    pub auto_resize: Option<AutoResizeConfig>,
 }
 impl Default for DisplayConfig {
     fn default() -> Self {
         Self {
             canvas: CanvasConfig::HtmlId("paddle-canvas"),
             pixels: Vector::new(1280, 720),
+            game_coordinates: None,
+            scaling: ScalingPolicy::default(),
             update_delay_ms: 8,
             texture_config: Default::default(),
             gpu_config: Default::default(),
             background: None,
             capture_touch: true,
+            auto_resize: None,
         }
     }
 }
@@ -93,10 +112,9 @@ impl Display {
                 .expect("Setting CSS failed");
         }
 
-        // For now the only option is game_coordinates = pixels
-        let game_coordinates = config.pixels;
+        let game_coordinates = config.game_coordinates.unwrap_or(config.pixels);
 
-        let canvas = WebGLCanvas::new(canvas, config.pixels, &config.gpu_config)?;
+        let canvas = WebGLCanvas::new(canvas, config.pixels, game_coordinates, &config.gpu_config)?;
         // Browser region is relative to window and needs to be known to handle input
         let browser_region = find_browser_region(canvas.html_element())?;
 
@@ -131,6 +149,8 @@ impl Display {
             canvas,
             browser_region,
             game_coordinates,
+            base_game_coordinates: game_coordinates,
+            scaling: config.scaling,
             background_color,
             div,
             tessellation_buffer: AbstractMesh::new(),
@@ -148,6 +168,17 @@ impl Display {
     pub fn resolution(&self) -> Vector {
         self.canvas.resolution()
     }
+    /// Size of the visible game area, in game coordinates
+    pub fn game_coordinates(&self) -> Vector {
+        self.game_coordinates
+    }
+    pub fn scaling_policy(&self) -> ScalingPolicy {
+        self.scaling
+    }
+    /// Takes effect on the next call to `fit_to_visible_area()`
+    pub fn set_scaling_policy(&mut self, scaling: ScalingPolicy) {
+        self.scaling = scaling;
+    }
 
     pub fn clear(&mut self) {
         if let Some(col) = self.background_color {
@@ -164,12 +195,15 @@ impl Display {
         ) * Transform::translate(self.browser_region.pos)
     }
 
-    /// Gives result for x axis (assuming y is the same)
-    pub fn browser_to_game_pixel_ratio(&self) -> f32 {
-        self.browser_region.width() / self.game_coordinates.x
+    /// Browser pixels per game coordinate unit, for each axis.
+    /// (Both axes are the same unless the scaling policy is `Stretch`)
+    pub fn browser_to_game_pixel_ratio(&self) -> Vector {
+        self.browser_region
+            .size
+            .times(self.game_coordinates.recip())
     }
 
-    /// Fixed to 16:9 ratio for now
+    /// Resize the display to the space available in the browser window, as defined by the scaling policy.
     pub fn fit_to_visible_area(&mut self, margin: f64) -> PaddleResult<()> {
         let web_window = web_sys::window().unwrap();
 
@@ -184,12 +218,17 @@ impl Display {
             .as_f64()
             .unwrap();
 
-        let (w, h) = scale_16_to_9(
+        let available = Vector::new(
             w - self.browser_region.x() as f64 - margin,
             h - self.browser_region.y() as f64 - margin,
         );
+        let fit = self.scaling.fit(self.base_game_coordinates, available);
 
-        self.canvas.set_size((w as f32, h as f32));
+        self.canvas.set_size(fit.browser_size);
+        if fit.game_size != self.game_coordinates {
+            self.game_coordinates = fit.game_size;
+            self.canvas.set_game_coordinates(fit.game_size);
+        }
 
         // Resizing might change position (How exactly can be completely unpredictable due to CSS, media-queries etc.)
         self.adjust_display()?;
@@ -203,10 +242,11 @@ impl Display {
 
         let (x, y) = self.div_offset()?;
         div::reposition(x, y)?;
-        div::resize(
-            self.browser_region.size.x as u32,
-            self.browser_region.size.y as u32,
-        )?;
+        // div has been initialized with the configured game coordinates, which might only cover part of the visible area
+        let div_size = self
+            .base_game_coordinates
+            .times(self.browser_to_game_pixel_ratio());
+        div::resize(div_size.x as u32, div_size.y as u32)?;
         Ok(())
     }
 
@@ -231,7 +271,8 @@ impl Display {
         z: i16,
     ) {
         // TODO: Keep tesselation for frame and apply transformation once per frame (potentially on GPU)
-        draw.tessellate(&mut self.tessellation_buffer, bkg.into());
+        let bkg = bkg.into();
+        draw.tessellate(&mut self.tessellation_buffer, bkg);
         self.canvas.render(&self.tessellation_buffer, trans, z);
         self.tessellation_buffer.clear();
     }
@@ -298,11 +339,3 @@ fn canvas_by_id(id: &str) -> PaddleResult<HtmlCanvasElement> {
         ))
     })
 }
-
-fn scale_16_to_9(w: f64, h: f64) -> (f64, f64) {
-    if w * 9.0 > h * 16.0 {
-        (h * 16.0 / 9.0, h)
-    } else {
-        (w, w * 9.0 / 16.0)
-    }
-}
@@ -0,0 +1,136 @@
+//! Keeps the display in sync with the browser window, if enabled in the `DisplayConfig`.
+//!
+//! Listens for window resizing, orientation changes and changes of the device pixel ratio (e.g. zooming or moving the window to another screen).
+
+use crate::{share, Display, Domain, ErrorMessage, JsError, NutsCheck, PaddleResult, Vector};
+use nuts::DomainState;
+use wasm_bindgen::{closure::Closure, JsCast};
+use web_sys::MediaQueryList;
+
+/// Settings for automatic resizing of the display.
+#[derive(Clone, Copy, Debug)]
+pub struct AutoResizeConfig {
+    /// If set, the display is fitted to the visible area (see `Display::fit_to_visible_area`) with the given margin.
+    /// Otherwise, the size is left to CSS and only the position and scaling are adjusted.
+    /// When `device_pixel_ratio` changes the resolution in that case, the current size is kept as CSS size of the canvas.
+    pub fit_margin: Option<f64>,
+    /// Render as many pixels as the canvas covers on the physical screen, which keeps the image sharp on HiDPI screens.
+    /// This overrides the configured resolution. (Game coordinates are not affected)
+    pub device_pixel_ratio: bool,
+}
+
+impl Default for AutoResizeConfig {
+    fn default() -> Self {
+        Self {
+            fit_margin: Some(0.0),
+            device_pixel_ratio: true,
+        }
+    }
+}
+
+/// Shared with all frames after the display has been resized automatically.
+///
+/// Listen to it with `FrameHandle::listen`.
+#[derive(Clone, Copy, Debug)]
+pub struct DisplayResized {
+    /// Size of the display, in browser pixels
+    pub browser_size: Vector,
+    /// How many pixels are rendered in the canvas
+    pub resolution: Vector,
+    /// Size of the visible game area, in game coordinates
+    pub game_coordinates: Vector,
+}
+
+/// Published by browser event listeners
+struct BrowserResize;
+
+pub(crate) struct AutoResizer {
+    config: AutoResizeConfig,
+    on_resize: Closure<dyn FnMut()>,
+    /// Matches the current device pixel ratio and fires when it changes
+    dpr_query: Option<MediaQueryList>,
+}
+
+impl AutoResizer {
+    pub(crate) fn init(config: AutoResizeConfig) -> PaddleResult<()> {
+        let on_resize =
+            Closure::wrap(Box::new(|| nuts::publish(BrowserResize)) as Box<dyn FnMut()>);
+        let window = web_sys::window().unwrap();
+        for event in &["resize", "orientationchange"] {
+            window
+                .add_event_listener_with_callback(event, on_resize.as_ref().unchecked_ref())
+                .map_err(JsError::from_js_value)?;
+        }
+        let resizer = AutoResizer {
+            config,
+            on_resize,
+            dpr_query: None,
+        };
+        let aid = nuts::new_domained_activity(resizer, &Domain::Frame);
+        aid.subscribe_domained(AutoResizer::resize);
+        // Apply settings right away
+        nuts::publish(BrowserResize);
+        Ok(())
+    }
+
+    fn resize(&mut self, domain: &mut DomainState, _: &BrowserResize) {
+        let display = Display::from_domain(domain);
+        match self.config.fit_margin {
+            Some(margin) => display.fit_to_visible_area(margin),
+            None => display.adjust_display(),
+        }
+        .nuts_check();
+        if self.config.device_pixel_ratio {
+            let dpr = web_sys::window().unwrap().device_pixel_ratio();
+            let browser_size = display.browser_region().size;
+            let pixels = device_pixels(browser_size, dpr);
+            if pixels != display.resolution() {
+                if self.config.fit_margin.is_none() {
+                    // Without a CSS size, the canvas attributes would become its layout size and it would grow with every resize
+                    display.canvas_mut().set_size(browser_size);
+                }
+                display.canvas_mut().set_resolution(pixels);
+            }
+            self.watch_device_pixel_ratio(dpr).nuts_check();
+        }
+        share(DisplayResized {
+            browser_size: display.browser_region().size,
+            resolution: display.resolution(),
+            game_coordinates: display.game_coordinates(),
+        });
+    }
+
+    /// A media query only fires once the ratio changes away from the value in the query, thus it has to be replaced after every change.
+    fn watch_device_pixel_ratio(&mut self, dpr: f64) -> PaddleResult<()> {
+        if let Some(old) = self.dpr_query.take() {
+            old.set_onchange(None);
+        }
+        let query = web_sys::window()
+            .unwrap()
+            .match_media(&format!("(resolution: {}dppx)", dpr))
+            .map_err(JsError::from_js_value)?
+            .ok_or_else(|| ErrorMessage::technical("matchMedia not supported".to_owned()))?;
+        query.set_onchange(Some(self.on_resize.as_ref().unchecked_ref()));
+        self.dpr_query = Some(query);
+        Ok(())
+    }
+}
+
+/// Number of physical pixels covered by an area of the given size in browser pixels
+fn device_pixels(browser_size: Vector, dpr: f64) -> Vector {
+    let pixels = browser_size * dpr as f32;
+    Vector::new(pixels.x.round(), pixels.y.round())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn device_pixels_are_rounded() {
+        let size = Vector::new(300, 150);
+        assert_eq!(device_pixels(size, 1.0), size);
+        assert_eq!(device_pixels(size, 2.0), Vector::new(600, 300));
+        assert_eq!(device_pixels(size, 1.25), Vector::new(375, 188));
+    }
+}
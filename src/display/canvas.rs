@@ -22,10 +22,12 @@ impl WebGLCanvas {
     /// Create a new webgl area in the given canvas.
     ///
     /// The pixels argument define how many webgl pixels should be rendered and has nothing to do with browser pixels.
+    /// The game coordinates define what area of the game world is mapped to the full canvas.
     /// Use `set_size()` or `fit_to_screen()` to change the size of the screen area taken by this element.
     pub fn new(
         canvas: HtmlCanvasElement,
         pixels: impl Into<Vector>,
+        game_coordinates: Vector,
         gpu_config: &GpuConfig,
     ) -> PaddleResult<Self> {
         let pixels = pixels.into();
@@ -41,12 +43,7 @@ impl WebGLCanvas {
 
         let buffer = WasmGpuBuffer::new();
 
-        // project screen space coordinates with origin at top left and y pointing down,
-        // to WebGL's [-1,-1] to [1,1] space with y pointing up
-        let projection = Transform::scale((1.0, -1.0))
-            * Transform::translate((-1.0, -1.0))
-            * Transform::scale(pixels.recip() * 2.0);
-        let gpu = Gpu::new(&gl, projection, gpu_config)?;
+        let gpu = Gpu::new(&gl, projection(game_coordinates), gpu_config)?;
 
         let window = WebGLCanvas {
             pixels,
@@ -70,6 +67,20 @@ impl WebGLCanvas {
         self.pixels
     }
 
+    /// Change how many pixels are rendered in the canvas. (Independent of the size in the browser)
+    pub(crate) fn set_resolution(&mut self, pixels: Vector) {
+        self.pixels = pixels;
+        self.canvas.set_width(pixels.x as u32);
+        self.canvas.set_height(pixels.y as u32);
+        self.gl.viewport(0, 0, pixels.x as i32, pixels.y as i32);
+    }
+
+    /// Change the area of the game world that is mapped to the full canvas
+    pub(crate) fn set_game_coordinates(&mut self, game_coordinates: Vector) {
+        self.gpu
+            .set_projection(&self.gl, projection(game_coordinates));
+    }
+
     /// Render object to the display buffer, to be forwarded to the GPU on the next flush
     pub fn render(&mut self, draw: &impl Render, trans: Transform, z: i16) {
         debug_assert!(z >= Z_MIN);
@@ -123,6 +134,14 @@ impl WebGLCanvas {
     }
 }
 
+/// Project game coordinates with origin at top left and y pointing down,
+/// to WebGL's [-1,-1] to [1,1] space with y pointing up
+fn projection(game_coordinates: Vector) -> Transform {
+    Transform::scale((1.0, -1.0))
+        * Transform::translate((-1.0, -1.0))
+        * Transform::scale(game_coordinates.recip() * 2.0)
+}
+
 impl Drop for WebGLCanvas {
     fn drop(&mut self) {
         self.gpu.custom_drop(&self.gl);
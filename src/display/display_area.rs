@@ -90,6 +90,10 @@ impl DisplayArea {
     pub fn size(&self) -> Vector {
         self.region.size()
     }
+    /// The selected area, in game coordinates of the full display
+    pub fn region(&self) -> Rectangle {
+        self.region
+    }
 }
 
 impl Into<DisplayArea> for Display {
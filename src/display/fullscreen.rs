@@ -0,0 +1,93 @@
+//! Fullscreen mode for the display, using the browser's Fullscreen API.
+//!
+//! The element put to fullscreen is the parent of the canvas, so that HTML elements of frames stay visible.
+
+use crate::{share, Display, Domain, ErrorMessage, JsError, NutsCheck, PaddleResult};
+use nuts::DomainState;
+use wasm_bindgen::{closure::Closure, JsCast};
+use web_sys::{Document, Element};
+
+/// Shared with all frames after the browser entered or left fullscreen mode.
+///
+/// Listen to it with `FrameHandle::listen`.
+#[derive(Clone, Copy, Debug)]
+pub struct FullscreenChanged {
+    /// True if the display is now in fullscreen mode
+    pub fullscreen: bool,
+}
+
+/// Published by the browser event listener
+struct BrowserFullscreenChange;
+
+pub(crate) struct FullscreenListener {
+    _on_change: Closure<dyn FnMut()>,
+}
+
+impl FullscreenListener {
+    pub(crate) fn init() -> PaddleResult<()> {
+        let on_change =
+            Closure::wrap(Box::new(|| nuts::publish(BrowserFullscreenChange)) as Box<dyn FnMut()>);
+        document()
+            .add_event_listener_with_callback(
+                "fullscreenchange",
+                on_change.as_ref().unchecked_ref(),
+            )
+            .map_err(JsError::from_js_value)?;
+        let listener = FullscreenListener {
+            _on_change: on_change,
+        };
+        let aid = nuts::new_domained_activity(listener, &Domain::Frame);
+        aid.subscribe_domained(FullscreenListener::changed);
+        Ok(())
+    }
+    fn changed(&mut self, domain: &mut DomainState, _: &BrowserFullscreenChange) {
+        let display = Display::from_domain(domain);
+        display.adjust_display().nuts_check();
+        share(FullscreenChanged {
+            fullscreen: display.is_fullscreen(),
+        });
+    }
+}
+
+impl Display {
+    /// Request fullscreen mode from the browser.
+    ///
+    /// Browsers only allow this in response to user input, such as a click or a key press.
+    /// The switch happens asynchronously, a `FullscreenChanged` event is shared once it is done.
+    pub fn enter_fullscreen(&self) -> PaddleResult<()> {
+        self.fullscreen_element()?
+            .request_fullscreen()
+            .map_err(JsError::from_js_value)?;
+        Ok(())
+    }
+    /// Leave fullscreen mode, if the display is in fullscreen.
+    pub fn exit_fullscreen(&self) {
+        if self.is_fullscreen() {
+            document().exit_fullscreen();
+        }
+    }
+    pub fn toggle_fullscreen(&self) -> PaddleResult<()> {
+        if self.is_fullscreen() {
+            self.exit_fullscreen();
+            Ok(())
+        } else {
+            self.enter_fullscreen()
+        }
+    }
+    pub fn is_fullscreen(&self) -> bool {
+        match (document().fullscreen_element(), self.fullscreen_element()) {
+            (Some(current), Ok(own)) => current == own,
+            _ => false,
+        }
+    }
+    fn fullscreen_element(&self) -> PaddleResult<Element> {
+        self.canvas
+            .html_element()
+            .parent_element()
+            .ok_or_else(|| ErrorMessage::technical("Canvas has no parent".to_owned()))
+    }
+}
+
+fn document() -> Document {
+    web_sys::window().unwrap().document().unwrap()
+}
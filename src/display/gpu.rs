@@ -166,6 +166,13 @@ impl Gpu {
         })
     }
 
+    /// Replace the transformation from game coordinates to WebGL's clip space
+    pub fn set_projection(&mut self, gl: &WebGlRenderingContext, projection: Transform) {
+        gl.use_program(Some(&self.program));
+        let projection_uloc = gl.get_uniform_location(&self.program, "Projection");
+        gl.uniform_matrix3fv_with_f32_array(projection_uloc.as_ref(), false, projection.as_slice());
+    }
+
     fn load_vertices(&mut self, gl: &WebGlRenderingContext, vertices: &[f32]) {
         let vertex_length = std::mem::size_of::<f32>() * vertices.len();
         // If the GPU can't store all of our data, re-create the GPU buffers so they can
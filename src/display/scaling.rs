@@ -0,0 +1,121 @@
+use crate::Vector;
+
+/// Defines how the display is scaled to fit into the space available in the browser.
+///
+/// Used by `Display::fit_to_visible_area`.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum ScalingPolicy {
+    /// Keep the aspect ratio of the game coordinates and fit the display into the available space.
+    /// Unused space is left empty (letterbox).
+    #[default]
+    FixedAspect,
+    /// Use all available space. The scale is the same on both axes, which means more of the game world is visible on one axis.
+    /// The configured game coordinates are at the top left and the visible game area extends to the right or to the bottom.
+    Expand,
+    /// Use all available space, scaling the two axes independently. The display is distorted if the aspect ratio does not match.
+    Stretch,
+    /// Like `FixedAspect`, but only scale with integer factors, so that every game coordinate unit maps to a whole number of browser pixels.
+    /// If the available space is smaller than the game coordinates, the display is shrunk with a fractional factor.
+    PixelPerfect,
+}
+
+/// Result of fitting the game coordinates into the available browser space
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScaledDisplay {
+    /// Size of the display, in browser pixels
+    pub browser_size: Vector,
+    /// Size of the visible game area, in game coordinates
+    pub game_size: Vector,
+}
+
+impl ScalingPolicy {
+    /// Computes display size and visible game area for the given game coordinates and available browser space.
+    pub fn fit(&self, game: Vector, available: Vector) -> ScaledDisplay {
+        let available = available.max(Vector::ZERO);
+        let scale = Vector::new(available.x / game.x, available.y / game.y);
+        let uniform = scale.x.min(scale.y);
+        match self {
+            ScalingPolicy::FixedAspect => ScaledDisplay {
+                browser_size: game * uniform,
+                game_size: game,
+            },
+            ScalingPolicy::Expand => ScaledDisplay {
+                browser_size: available,
+                // Nothing is visible without space, keep the game coordinates instead of dividing by zero
+                game_size: if uniform > 0.0 {
+                    available / uniform
+                } else {
+                    game
+                },
+            },
+            ScalingPolicy::Stretch => ScaledDisplay {
+                browser_size: available,
+                game_size: game,
+            },
+            ScalingPolicy::PixelPerfect => {
+                let factor = if uniform >= 1.0 {
+                    uniform.floor()
+                } else {
+                    uniform
+                };
+                ScaledDisplay {
+                    browser_size: game * factor,
+                    game_size: game,
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_aspect() {
+        let game = Vector::new(1600, 900);
+        let fit = ScalingPolicy::FixedAspect.fit(game, Vector::new(1000, 1000));
+        assert_eq!(fit.browser_size, Vector::new(1000, 562.5));
+        assert_eq!(fit.game_size, game);
+        let fit = ScalingPolicy::FixedAspect.fit(game, Vector::new(3200, 900));
+        assert_eq!(fit.browser_size, Vector::new(1600, 900));
+    }
+
+    #[test]
+    fn expand() {
+        let game = Vector::new(100, 100);
+        let fit = ScalingPolicy::Expand.fit(game, Vector::new(400, 200));
+        assert_eq!(fit.browser_size, Vector::new(400, 200));
+        assert_eq!(fit.game_size, Vector::new(200, 100));
+        let fit = ScalingPolicy::Expand.fit(game, Vector::new(50, 100));
+        assert_eq!(fit.game_size, Vector::new(100, 200));
+    }
+
+    #[test]
+    fn stretch() {
+        let game = Vector::new(100, 100);
+        let fit = ScalingPolicy::Stretch.fit(game, Vector::new(400, 200));
+        assert_eq!(fit.browser_size, Vector::new(400, 200));
+        assert_eq!(fit.game_size, game);
+    }
+
+    #[test]
+    fn pixel_perfect() {
+        let game = Vector::new(320, 180);
+        let fit = ScalingPolicy::PixelPerfect.fit(game, Vector::new(1000, 1000));
+        assert_eq!(fit.browser_size, Vector::new(960, 540));
+        let fit = ScalingPolicy::PixelPerfect.fit(game, Vector::new(160, 1000));
+        assert_eq!(fit.browser_size, Vector::new(160, 90));
+    }
+
+    #[test]
+    fn no_space() {
+        let fit = ScalingPolicy::FixedAspect.fit(Vector::new(16, 9), Vector::new(-5, 10));
+        assert_eq!(fit.browser_size, Vector::ZERO);
+        let fit = ScalingPolicy::Expand.fit(Vector::new(16, 9), Vector::new(-5, 10));
+        assert_eq!(fit.browser_size, Vector::new(0, 10));
+        assert_eq!(fit.game_size, Vector::new(16, 9));
+        let fit = ScalingPolicy::Expand.fit(Vector::new(16, 9), Vector::ZERO);
+        assert_eq!(fit.game_size, Vector::new(16, 9));
+    }
+}
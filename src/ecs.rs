@@ -0,0 +1,279 @@
+//! Optional, lightweight entity-component storage.
+//!
+//! A `World` holds entities and their components and can be used directly as the `State` of frames.
+//! Game logic is written as systems, plain functions over the world, which are run from `Frame::update`.
+//! Components carrying a shape and a paint can be drawn with `draw_components`.
+//!
+//! ```
+//! # use paddle::*;
+//! struct Velocity(Vector);
+//!
+//! fn movement(world: &mut World) {
+//!     world.for_each2_mut::<Position, Velocity, _>(|_, pos, vel| pos.0 = pos.0 + vel.0);
+//! }
+//!
+//! let mut world = World::new();
+//! let e = world.spawn();
+//! world.insert(e, Position(Vector::new(0, 0)));
+//! world.insert(e, Velocity(Vector::new(1, 2)));
+//!
+//! let mut systems = Systems::new().with(movement);
+//! systems.run(&mut world);
+//! assert_eq!(world.get::<Position>(e).unwrap().0, Vector::new(1, 2));
+//! ```
+
+mod draw;
+mod entity;
+mod storage;
+
+pub use draw::*;
+pub use entity::Entity;
+pub use storage::ComponentStorage;
+
+use entity::EntityAllocator;
+use std::any::TypeId;
+use std::collections::HashMap;
+use storage::AnyStorage;
+
+/// Entities with their components
+#[derive(Default)]
+pub struct World {
+    entities: EntityAllocator,
+    storages: HashMap<TypeId, Box<dyn AnyStorage>>,
+}
+
+impl World {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Create a new entity without components
+    pub fn spawn(&mut self) -> Entity {
+        self.entities.allocate()
+    }
+    /// Remove the entity and all its components. Returns false if the entity did not exist.
+    pub fn despawn(&mut self, entity: Entity) -> bool {
+        if !self.entities.free(entity) {
+            return false;
+        }
+        for storage in self.storages.values_mut() {
+            storage.remove_entity(entity);
+        }
+        true
+    }
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        self.entities.is_alive(entity)
+    }
+    /// Number of living entities
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    pub fn entities(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.entities.iter()
+    }
+
+    /// Add a component to the entity, replacing and returning an existing component of the same type.
+    ///
+    /// Components added to despawned entities are dropped.
+    pub fn insert<T: 'static>(&mut self, entity: Entity, component: T) -> Option<T> {
+        if !self.is_alive(entity) {
+            return None;
+        }
+        self.storage_mut::<T>().insert(entity, component)
+    }
+    pub fn remove<T: 'static>(&mut self, entity: Entity) -> Option<T> {
+        self.existing_storage_mut::<T>()?.remove(entity)
+    }
+    pub fn get<T: 'static>(&self, entity: Entity) -> Option<&T> {
+        self.storage::<T>()?.get(entity)
+    }
+    pub fn get_mut<T: 'static>(&mut self, entity: Entity) -> Option<&mut T> {
+        self.existing_storage_mut::<T>()?.get_mut(entity)
+    }
+    pub fn has<T: 'static>(&self, entity: Entity) -> bool {
+        self.get::<T>(entity).is_some()
+    }
+
+    /// All components of a type, if any has ever been inserted
+    pub fn storage<T: 'static>(&self) -> Option<&ComponentStorage<T>> {
+        self.storages
+            .get(&TypeId::of::<T>())
+            .map(|s| s.as_any().downcast_ref().unwrap())
+    }
+    /// All components of a type, the storage is created if necessary
+    pub fn storage_mut<T: 'static>(&mut self) -> &mut ComponentStorage<T> {
+        self.storages
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::new(ComponentStorage::<T>::default()))
+            .as_any_mut()
+            .downcast_mut()
+            .unwrap()
+    }
+    fn existing_storage_mut<T: 'static>(&mut self) -> Option<&mut ComponentStorage<T>> {
+        self.storages
+            .get_mut(&TypeId::of::<T>())
+            .map(|s| s.as_any_mut().downcast_mut().unwrap())
+    }
+
+    /// Iterate all entities with a component of type `A`
+    pub fn query<A: 'static>(&self) -> impl Iterator<Item = (Entity, &A)> {
+        self.storage::<A>().into_iter().flat_map(|s| s.iter())
+    }
+    /// Iterate all entities with components of both types `A` and `B`
+    pub fn query2<A: 'static, B: 'static>(&self) -> impl Iterator<Item = (Entity, &A, &B)> {
+        let b = self.storage::<B>();
+        self.query::<A>()
+            .filter_map(move |(e, a)| b.and_then(|b| b.get(e)).map(|b| (e, a, b)))
+    }
+    /// Iterate all entities with a component of type `A`, with mutable access
+    pub fn query_mut<A: 'static>(&mut self) -> impl Iterator<Item = (Entity, &mut A)> {
+        self.existing_storage_mut::<A>()
+            .into_iter()
+            .flat_map(|s| s.iter_mut())
+    }
+    /// Call `f` for all entities with a component of type `A`
+    pub fn for_each_mut<A: 'static, F: FnMut(Entity, &mut A)>(&mut self, mut f: F) {
+        for (e, a) in self.query_mut::<A>() {
+            f(e, a);
+        }
+    }
+    /// Call `f` for all entities with components of both types `A` and `B`, where `A` can be modified.
+    ///
+    /// Panics if `A` and `B` are the same type.
+    pub fn for_each2_mut<A: 'static, B: 'static, F: FnMut(Entity, &mut A, &B)>(
+        &mut self,
+        mut f: F,
+    ) {
+        assert_ne!(
+            TypeId::of::<A>(),
+            TypeId::of::<B>(),
+            "Cannot borrow a component storage mutably and immutably at once"
+        );
+        // Temporarily take out the storage of A, to allow borrowing the storage of B at the same time
+        let mut storage_a = match self.storages.remove(&TypeId::of::<A>()) {
+            Some(s) => s,
+            None => return,
+        };
+        if let Some(b) = self.storage::<B>() {
+            let a: &mut ComponentStorage<A> = storage_a.as_any_mut().downcast_mut().unwrap();
+            for (e, a) in a.iter_mut() {
+                if let Some(b) = b.get(e) {
+                    f(e, a, b);
+                }
+            }
+        }
+        self.storages.insert(TypeId::of::<A>(), storage_a);
+    }
+}
+
+/// A piece of game logic operating on the world
+pub trait System {
+    fn run(&mut self, world: &mut World);
+}
+
+impl<F: FnMut(&mut World)> System for F {
+    fn run(&mut self, world: &mut World) {
+        self(world)
+    }
+}
+
+/// An ordered list of systems, typically owned by a frame and run in `Frame::update`
+#[derive(Default)]
+pub struct Systems {
+    systems: Vec<Box<dyn System>>,
+}
+
+impl Systems {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn add(&mut self, system: impl System + 'static) {
+        self.systems.push(Box::new(system));
+    }
+    /// Builder pattern for `add`
+    pub fn with(mut self, system: impl System + 'static) -> Self {
+        self.add(system);
+        self
+    }
+    /// Run all systems in the order they have been added
+    pub fn run(&mut self, world: &mut World) {
+        for system in &mut self.systems {
+            system.run(world);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Health(u32);
+    #[derive(Debug, PartialEq)]
+    struct Name(&'static str);
+
+    #[test]
+    fn components() {
+        let mut world = World::new();
+        let a = world.spawn();
+        let b = world.spawn();
+        world.insert(a, Health(10));
+        world.insert(b, Health(20));
+        world.insert(b, Name("b"));
+        assert_eq!(world.insert(a, Health(11)), Some(Health(10)));
+        assert_eq!(world.get::<Health>(a), Some(&Health(11)));
+        assert!(!world.has::<Name>(a));
+        assert_eq!(world.remove::<Health>(a), Some(Health(11)));
+        assert_eq!(world.get::<Health>(a), None);
+        assert_eq!(world.get::<u8>(a), None);
+    }
+
+    #[test]
+    fn despawn_and_reuse() {
+        let mut world = World::new();
+        let a = world.spawn();
+        world.insert(a, Health(1));
+        assert!(world.despawn(a));
+        assert!(!world.despawn(a));
+        let c = world.spawn();
+        assert_eq!(c.index(), a.index());
+        assert_eq!(world.get::<Health>(c), None);
+        assert_eq!(world.insert(a, Health(2)), None);
+        assert_eq!(world.get::<Health>(c), None);
+        assert_eq!(world.len(), 1);
+    }
+
+    #[test]
+    fn queries() {
+        let mut world = World::new();
+        let a = world.spawn();
+        let b = world.spawn();
+        let c = world.spawn();
+        world.insert(a, Health(1));
+        world.insert(b, Health(2));
+        world.insert(b, Name("b"));
+        world.insert(c, Name("c"));
+        let names: Vec<_> = world.query::<Name>().map(|(_, n)| n.0).collect();
+        assert_eq!(names, vec!["b", "c"]);
+        let both: Vec<_> = world.query2::<Health, Name>().map(|(e, _, _)| e).collect();
+        assert_eq!(both, vec![b]);
+        world.for_each2_mut::<Health, Name, _>(|_, h, _| h.0 += 10);
+        world.for_each_mut::<Health, _>(|_, h| h.0 += 1);
+        assert_eq!(world.get::<Health>(a), Some(&Health(2)));
+        assert_eq!(world.get::<Health>(b), Some(&Health(13)));
+    }
+
+    #[test]
+    fn systems_run_in_order() {
+        let mut world = World::new();
+        let e = world.spawn();
+        world.insert(e, Health(1));
+        let mut systems = Systems::new()
+            .with(|w: &mut World| w.for_each_mut::<Health, _>(|_, h| h.0 *= 10))
+            .with(|w: &mut World| w.for_each_mut::<Health, _>(|_, h| h.0 += 1));
+        systems.run(&mut world);
+        assert_eq!(world.get::<Health>(e), Some(&Health(11)));
+    }
+}
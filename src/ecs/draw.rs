@@ -0,0 +1,96 @@
+use super::World;
+use crate::quicksilver_compat::{Background, Color};
+use crate::{DisplayArea, Gradient, Image, Pattern, Tessellate, Transform, Vector};
+
+/// Position of an entity, in the coordinates of the frame it is drawn in.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Position(pub Vector);
+
+/// Owned version of a `Background`, to be stored in components.
+#[derive(Clone, Debug)]
+pub enum Paint {
+    Color(Color),
+    Image(Image),
+    /// A color and image blended multiplicatively
+    Blended(Image, Color),
+    Gradient(Gradient),
+    Pattern(Pattern),
+}
+
+impl Paint {
+    pub fn background(&self) -> Background<'_> {
+        match self {
+            Paint::Color(col) => Background::Col(*col),
+            Paint::Image(img) => Background::Img(img),
+            Paint::Blended(img, col) => Background::Blended(img, *col),
+            Paint::Gradient(gradient) => Background::Gradient(gradient),
+            Paint::Pattern(pattern) => Background::Pattern(pattern),
+        }
+    }
+}
+
+impl From<Color> for Paint {
+    fn from(col: Color) -> Self {
+        Paint::Color(col)
+    }
+}
+
+impl From<Gradient> for Paint {
+    fn from(gradient: Gradient) -> Self {
+        Paint::Gradient(gradient)
+    }
+}
+
+impl From<Pattern> for Paint {
+    fn from(pattern: Pattern) -> Self {
+        Paint::Pattern(pattern)
+    }
+}
+
+impl From<Image> for Paint {
+    fn from(img: Image) -> Self {
+        Paint::Image(img)
+    }
+}
+
+/// Component for entities drawn with `draw_components`.
+///
+/// The shape is defined relative to the `Position` of the entity, if it has one.
+#[derive(Clone, Debug)]
+pub struct Drawable<S> {
+    pub shape: S,
+    pub paint: Paint,
+    pub z: i16,
+}
+
+impl<S: Tessellate> Drawable<S> {
+    pub fn new(shape: S, paint: impl Into<Paint>) -> Self {
+        Self {
+            shape,
+            paint: paint.into(),
+            z: 0,
+        }
+    }
+    pub fn with_z(mut self, z: i16) -> Self {
+        self.z = z;
+        self
+    }
+}
+
+/// Draw all entities with a `Drawable<S>` component to the display area.
+///
+/// Call this once for each shape type used, typically from `Frame::draw`.
+pub fn draw_components<S: Tessellate + 'static>(world: &World, area: &mut DisplayArea) {
+    for (entity, drawable) in world.query::<Drawable<S>>() {
+        let transform = world
+            .get::<Position>(entity)
+            .map(|pos| Transform::translate(pos.0))
+            .unwrap_or(Transform::IDENTITY);
+        area.draw_ex(
+            &drawable.shape,
+            drawable.paint.background(),
+            transform,
+            drawable.z,
+        );
+    }
+}
@@ -0,0 +1,98 @@
+/// Identifies an entity in a `World`.
+///
+/// The generation makes sure that an id of a despawned entity is never confused with a new entity reusing the same slot.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Entity {
+    index: u32,
+    generation: u32,
+}
+
+impl Entity {
+    pub fn index(&self) -> usize {
+        self.index as usize
+    }
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+}
+
+/// Hands out entity ids and keeps track of which are alive
+#[derive(Default, Debug)]
+pub(super) struct EntityAllocator {
+    /// Current generation of each slot
+    generations: Vec<u32>,
+    alive: Vec<bool>,
+    free: Vec<u32>,
+}
+
+impl EntityAllocator {
+    pub fn allocate(&mut self) -> Entity {
+        if let Some(index) = self.free.pop() {
+            let i = index as usize;
+            self.generations[i] += 1;
+            self.alive[i] = true;
+            Entity {
+                index,
+                generation: self.generations[i],
+            }
+        } else {
+            let index = self.generations.len() as u32;
+            self.generations.push(0);
+            self.alive.push(true);
+            Entity {
+                index,
+                generation: 0,
+            }
+        }
+    }
+    /// Returns false if the entity was not alive
+    pub fn free(&mut self, entity: Entity) -> bool {
+        if !self.is_alive(entity) {
+            return false;
+        }
+        self.alive[entity.index()] = false;
+        self.free.push(entity.index);
+        true
+    }
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        let i = entity.index();
+        i < self.alive.len() && self.alive[i] && self.generations[i] == entity.generation
+    }
+    pub fn len(&self) -> usize {
+        self.generations.len() - self.free.len()
+    }
+    pub fn iter(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.generations
+            .iter()
+            .zip(&self.alive)
+            .enumerate()
+            .filter(|(_, (_, alive))| **alive)
+            .map(|(index, (generation, _))| Entity {
+                index: index as u32,
+                generation: *generation,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generations() {
+        let mut entities = EntityAllocator::default();
+        let a = entities.allocate();
+        let b = entities.allocate();
+        assert_ne!(a, b);
+        assert!(entities.free(a));
+        assert!(!entities.free(a));
+        assert!(!entities.is_alive(a));
+        let c = entities.allocate();
+        assert_eq!(c.index(), a.index());
+        assert_eq!(c.generation(), 1);
+        assert!(entities.is_alive(c));
+        assert!(!entities.is_alive(a));
+        assert_eq!(entities.len(), 2);
+        assert_eq!(entities.iter().collect::<Vec<_>>(), vec![c, b]);
+    }
+}
@@ -0,0 +1,94 @@
+use super::Entity;
+use std::any::Any;
+
+/// Components of one type, indexed by entity.
+///
+/// Each slot remembers the entity it belongs to, so stale entity ids never access components of a newer entity.
+pub struct ComponentStorage<T> {
+    slots: Vec<Option<(Entity, T)>>,
+    len: usize,
+}
+
+impl<T> Default for ComponentStorage<T> {
+    fn default() -> Self {
+        Self {
+            slots: Vec::new(),
+            len: 0,
+        }
+    }
+}
+
+impl<T> ComponentStorage<T> {
+    /// Returns the previous component of the entity, if any
+    pub fn insert(&mut self, entity: Entity, component: T) -> Option<T> {
+        let i = entity.index();
+        if i >= self.slots.len() {
+            self.slots.resize_with(i + 1, || None);
+        }
+        let previous = self.slots[i].replace((entity, component));
+        match previous {
+            Some((owner, c)) if owner == entity => Some(c),
+            Some(_) => None,
+            None => {
+                self.len += 1;
+                None
+            }
+        }
+    }
+    pub fn remove(&mut self, entity: Entity) -> Option<T> {
+        let slot = self.slots.get_mut(entity.index())?;
+        match slot {
+            Some((owner, _)) if *owner == entity => {
+                self.len -= 1;
+                slot.take().map(|(_, c)| c)
+            }
+            _ => None,
+        }
+    }
+    pub fn get(&self, entity: Entity) -> Option<&T> {
+        match self.slots.get(entity.index())? {
+            Some((owner, c)) if *owner == entity => Some(c),
+            _ => None,
+        }
+    }
+    pub fn get_mut(&mut self, entity: Entity) -> Option<&mut T> {
+        match self.slots.get_mut(entity.index())? {
+            Some((owner, c)) if *owner == entity => Some(c),
+            _ => None,
+        }
+    }
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.get(entity).is_some()
+    }
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, &T)> {
+        self.slots.iter().flatten().map(|(e, c)| (*e, c))
+    }
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Entity, &mut T)> {
+        self.slots.iter_mut().flatten().map(|(e, c)| (*e, &mut *c))
+    }
+}
+
+/// Type-erased storage, to keep storages of all component types in one map.
+pub(super) trait AnyStorage {
+    fn remove_entity(&mut self, entity: Entity);
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T: 'static> AnyStorage for ComponentStorage<T> {
+    fn remove_entity(&mut self, entity: Entity) {
+        self.remove(entity);
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
@@ -15,6 +15,8 @@
 use crate::*;
 use nuts::*;
 
+mod focus;
+pub use focus::*;
 mod frame_manipulation;
 pub(crate) use frame_manipulation::*;
 mod frame_registration;
@@ -37,6 +39,10 @@ pub trait Frame {
     fn enter(&mut self, _state: &mut Self::State) {}
     fn key(&mut self, _state: &mut Self::State, _key: KeyEvent) {}
     fn pointer(&mut self, _state: &mut Self::State, _event: PointerEvent) {}
+    /// Called when the frame receives keyboard focus
+    fn focus(&mut self, _state: &mut Self::State) {}
+    /// Called when the frame loses keyboard focus
+    fn blur(&mut self, _state: &mut Self::State) {}
 
     #[inline(always)]
     fn size() -> Vector {
@@ -83,6 +89,28 @@ impl<FRAME> FrameHandle<FRAME> {
     pub fn set_z(&self, z: i32) {
         self.div.set_css("z-index", &z.to_string()).nuts_check();
     }
+    /// Give this frame keyboard focus, the frame that had it before loses it.
+    ///
+    /// Only frames that accept key events can be focused.
+    pub fn focus(&self) {
+        FocusManager::focus(self.activity_id);
+    }
+    /// Remove keyboard focus from this frame, if it has it
+    pub fn blur(&self) {
+        FocusManager::blur(self.activity_id);
+    }
+    /// Position in the tab order, lower values come first and equal values are ordered by registration.
+    ///
+    /// Frames with a negative tab index are skipped by Tab but can still be focused by clicking or programmatically.
+    pub fn set_tab_index(&self, tab_index: i32) {
+        FocusManager::set_tab_index(self.activity_id, tab_index);
+    }
+    /// Receive all key events, even when the frame is not focused.
+    ///
+    /// Only global listeners can receive Tab, which otherwise moves the focus between frames.
+    pub fn set_global_key_listener(&self, global: bool) {
+        FocusManager::set_global_listener(self.activity_id, global);
+    }
     #[cfg(debug_assertions)]
     fn set_id(&self, id: &str) {
         let parent = self.div.parent_element().unwrap();
@@ -0,0 +1,311 @@
+//! Keyboard focus decides which frame receives key events.
+//!
+//! All frames that accept key events can be focused, by clicking on them or programmatically through `FrameHandle::focus`.
+//! Key events are only delivered to the focused frame and to frames registered as global key listeners.
+//! Pressing Tab moves the focus to the next frame in tab order, Shift+Tab to the previous one.
+//! Tab is still delivered to global key listeners, but not to the focused frame.
+//! The browser's own focus movement on Tab is prevented while paddle listens to the keyboard.
+
+use crate::{Key, KeyEvent, KeyEventType};
+use nuts::UncheckedActivityId;
+
+/// Sent to a frame when it gains or loses keyboard focus
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum FocusChange {
+    Gained,
+    Lost,
+}
+
+/// Keeps track of the focused frame and routes key events
+pub(crate) struct FocusManager {
+    ring: FocusRing,
+}
+
+enum FocusRequest {
+    Register(usize),
+    Focus(usize),
+    Blur(usize),
+    Traverse { forward: bool },
+    TabIndex(usize, i32),
+    GlobalListener(usize, bool),
+    Active(usize, bool),
+    Key(KeyEvent),
+}
+
+impl FocusManager {
+    pub(crate) fn init() {
+        let manager = FocusManager {
+            ring: FocusRing::default(),
+        };
+        let aid = nuts::new_activity(manager);
+        aid.private_channel(Self::handle_request);
+    }
+    pub(crate) fn register(frame: impl Into<UncheckedActivityId>) {
+        Self::request(FocusRequest::Register(frame.into().as_usize()));
+    }
+    pub(crate) fn focus(frame: impl Into<UncheckedActivityId>) {
+        Self::request(FocusRequest::Focus(frame.into().as_usize()));
+    }
+    pub(crate) fn blur(frame: impl Into<UncheckedActivityId>) {
+        Self::request(FocusRequest::Blur(frame.into().as_usize()));
+    }
+    pub(crate) fn set_tab_index(frame: impl Into<UncheckedActivityId>, tab_index: i32) {
+        Self::request(FocusRequest::TabIndex(frame.into().as_usize(), tab_index));
+    }
+    pub(crate) fn set_global_listener(frame: impl Into<UncheckedActivityId>, global: bool) {
+        Self::request(FocusRequest::GlobalListener(
+            frame.into().as_usize(),
+            global,
+        ));
+    }
+    pub(crate) fn set_active(frame: impl Into<UncheckedActivityId>, active: bool) {
+        Self::request(FocusRequest::Active(frame.into().as_usize(), active));
+    }
+    pub(crate) fn key_event(event: KeyEvent) {
+        Self::request(FocusRequest::Key(event));
+    }
+    fn request(request: FocusRequest) {
+        nuts::send_to::<Self, _>(request);
+    }
+    fn handle_request(&mut self, request: FocusRequest) {
+        let changes = match request {
+            FocusRequest::Register(frame) => {
+                self.ring.register(frame);
+                vec![]
+            }
+            FocusRequest::Focus(frame) => self.ring.focus(frame),
+            FocusRequest::Blur(frame) => self.ring.blur(frame),
+            FocusRequest::Traverse { forward } => self.ring.traverse(forward),
+            FocusRequest::TabIndex(frame, tab_index) => {
+                self.ring.set_tab_index(frame, tab_index);
+                vec![]
+            }
+            FocusRequest::GlobalListener(frame, global) => {
+                self.ring.set_global(frame, global);
+                vec![]
+            }
+            FocusRequest::Active(frame, active) => self.ring.set_active(frame, active),
+            FocusRequest::Key(event) => {
+                let (recipients, changes) = self.ring.route(event);
+                for frame in recipients {
+                    UncheckedActivityId::forge_from_usize(frame).private_message(event);
+                }
+                changes
+            }
+        };
+        for (frame, change) in changes {
+            UncheckedActivityId::forge_from_usize(frame).private_message(change);
+        }
+    }
+}
+
+/// Move the keyboard focus to the next frame in tab order, as if Tab had been pressed
+pub fn focus_next() {
+    FocusManager::request(FocusRequest::Traverse { forward: true });
+}
+
+/// Move the keyboard focus to the previous frame in tab order, as if Shift+Tab had been pressed
+pub fn focus_previous() {
+    FocusManager::request(FocusRequest::Traverse { forward: false });
+}
+
+struct FocusEntry {
+    frame: usize,
+    tab_index: i32,
+    active: bool,
+    global: bool,
+}
+
+/// Focus state without any connection to the browser or to activities
+#[derive(Default)]
+struct FocusRing {
+    /// In order of registration
+    entries: Vec<FocusEntry>,
+    focused: Option<usize>,
+    shift_down: [bool; 2],
+}
+
+impl FocusRing {
+    fn register(&mut self, frame: usize) {
+        if self.entry(frame).is_none() {
+            self.entries.push(FocusEntry {
+                frame,
+                tab_index: 0,
+                active: true,
+                global: false,
+            });
+        }
+    }
+    fn entry(&mut self, frame: usize) -> Option<&mut FocusEntry> {
+        self.entries.iter_mut().find(|e| e.frame == frame)
+    }
+    fn set_tab_index(&mut self, frame: usize, tab_index: i32) {
+        if let Some(entry) = self.entry(frame) {
+            entry.tab_index = tab_index;
+        }
+    }
+    fn set_global(&mut self, frame: usize, global: bool) {
+        if let Some(entry) = self.entry(frame) {
+            entry.global = global;
+        }
+    }
+    /// Inactive frames cannot hold the focus
+    fn set_active(&mut self, frame: usize, active: bool) -> Vec<(usize, FocusChange)> {
+        if let Some(entry) = self.entry(frame) {
+            entry.active = active;
+        }
+        if !active && self.focused == Some(frame) {
+            self.change_focus(None)
+        } else {
+            vec![]
+        }
+    }
+    fn focus(&mut self, frame: usize) -> Vec<(usize, FocusChange)> {
+        match self.entry(frame) {
+            Some(entry) if entry.active => self.change_focus(Some(frame)),
+            _ => vec![],
+        }
+    }
+    fn blur(&mut self, frame: usize) -> Vec<(usize, FocusChange)> {
+        if self.focused == Some(frame) {
+            self.change_focus(None)
+        } else {
+            vec![]
+        }
+    }
+    /// Frames with a negative tab index can be focused but are skipped by traversal
+    fn traverse(&mut self, forward: bool) -> Vec<(usize, FocusChange)> {
+        let mut order: Vec<&FocusEntry> = self
+            .entries
+            .iter()
+            .filter(|e| e.active && e.tab_index >= 0)
+            .collect();
+        if order.is_empty() {
+            return vec![];
+        }
+        // Stable sort, registration order breaks ties
+        order.sort_by_key(|e| e.tab_index);
+        let n = order.len();
+        let current = self
+            .focused
+            .and_then(|focused| order.iter().position(|e| e.frame == focused));
+        let next = match (current, forward) {
+            (Some(i), true) => (i + 1) % n,
+            (Some(i), false) => (i + n - 1) % n,
+            (None, true) => 0,
+            (None, false) => n - 1,
+        };
+        let frame = order[next].frame;
+        self.change_focus(Some(frame))
+    }
+    /// Frames that should receive the key event, and focus changes caused by it
+    fn route(&mut self, event: KeyEvent) -> (Vec<usize>, Vec<(usize, FocusChange)>) {
+        let down = event.event_type() != KeyEventType::KeyUp;
+        match event.key() {
+            Key::ShiftLeft => self.shift_down[0] = down,
+            Key::ShiftRight => self.shift_down[1] = down,
+            Key::Tab if !self.entries.is_empty() => {
+                let recipients = self.recipients(None);
+                let changes = if event.event_type() == KeyEventType::KeyDown {
+                    let backwards = self.shift_down[0] || self.shift_down[1];
+                    self.traverse(!backwards)
+                } else {
+                    vec![]
+                };
+                return (recipients, changes);
+            }
+            _ => {}
+        }
+        (self.recipients(self.focused), vec![])
+    }
+    /// Active global listeners and the given focused frame
+    fn recipients(&self, focused: Option<usize>) -> Vec<usize> {
+        self.entries
+            .iter()
+            .filter(|e| e.active && (e.global || Some(e.frame) == focused))
+            .map(|e| e.frame)
+            .collect()
+    }
+    fn change_focus(&mut self, target: Option<usize>) -> Vec<(usize, FocusChange)> {
+        if self.focused == target {
+            return vec![];
+        }
+        let mut changes = vec![];
+        if let Some(old) = self.focused.take() {
+            changes.push((old, FocusChange::Lost));
+        }
+        if let Some(new) = target {
+            changes.push((new, FocusChange::Gained));
+        }
+        self.focused = target;
+        changes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use FocusChange::*;
+
+    fn ring(frames: &[usize]) -> FocusRing {
+        let mut ring = FocusRing::default();
+        for frame in frames {
+            ring.register(*frame);
+        }
+        ring
+    }
+
+    fn key(event_type: KeyEventType, key: Key) -> KeyEvent {
+        KeyEvent(event_type, key)
+    }
+
+    #[test]
+    fn focus_and_blur() {
+        let mut ring = ring(&[1, 2]);
+        assert_eq!(ring.focus(1), vec![(1, Gained)]);
+        assert_eq!(ring.focus(1), vec![]);
+        assert_eq!(ring.focus(2), vec![(1, Lost), (2, Gained)]);
+        // Unknown frames cannot take the focus
+        assert_eq!(ring.focus(3), vec![]);
+        assert_eq!(ring.blur(1), vec![]);
+        assert_eq!(ring.blur(2), vec![(2, Lost)]);
+        assert_eq!(ring.focused, None);
+    }
+
+    #[test]
+    fn tab_order() {
+        let mut ring = ring(&[1, 2, 3, 4]);
+        ring.set_tab_index(1, 5);
+        ring.set_tab_index(4, -1);
+        assert_eq!(ring.traverse(true), vec![(2, Gained)]);
+        assert_eq!(ring.traverse(true), vec![(2, Lost), (3, Gained)]);
+        assert_eq!(ring.traverse(true), vec![(3, Lost), (1, Gained)]);
+        assert_eq!(ring.traverse(true), vec![(1, Lost), (2, Gained)]);
+        assert_eq!(ring.traverse(false), vec![(2, Lost), (1, Gained)]);
+        // Negative tab index is still focusable directly
+        assert_eq!(ring.focus(4), vec![(1, Lost), (4, Gained)]);
+        ring.set_active(2, false);
+        assert_eq!(ring.traverse(true), vec![(4, Lost), (3, Gained)]);
+        assert_eq!(ring.set_active(3, false), vec![(3, Lost)]);
+    }
+
+    #[test]
+    fn key_routing() {
+        let mut ring = ring(&[1, 2, 3]);
+        ring.set_global(3, true);
+        let a = key(KeyEventType::KeyDown, Key::KeyA);
+        assert_eq!(ring.route(a), (vec![3], vec![]));
+        ring.focus(1);
+        assert_eq!(ring.route(a), (vec![1, 3], vec![]));
+        // Tab moves the focus instead of being delivered to the focused frame, global listeners still get it
+        let tab = key(KeyEventType::KeyDown, Key::Tab);
+        assert_eq!(ring.route(tab), (vec![3], vec![(1, Lost), (2, Gained)]));
+        assert_eq!(ring.route(a), (vec![2, 3], vec![]));
+        ring.route(key(KeyEventType::KeyDown, Key::ShiftLeft));
+        assert_eq!(ring.route(tab), (vec![3], vec![(2, Lost), (1, Gained)]));
+        ring.route(key(KeyEventType::KeyUp, Key::ShiftLeft));
+        assert_eq!(ring.route(tab), (vec![3], vec![(1, Lost), (2, Gained)]));
+        let tab_up = key(KeyEventType::KeyUp, Key::Tab);
+        assert_eq!(ring.route(tab_up), (vec![3], vec![]));
+    }
+}
@@ -54,12 +54,16 @@ impl<STATE: 'static, F: Frame<State = STATE> + Activity> FrameHandle<F> {
                 )
             });
         }
+        let focusable = (F::key as usize) != (Nop::<F::State>::key as usize);
         if (F::pointer as usize) != (Nop::<F::State>::pointer as usize) {
-            activity.private_domained_channel(|a, d, msg: PointerEvent| {
+            activity.private_domained_channel(move |a, d, msg: PointerEvent| {
+                if focusable {
+                    focus_on_click(activity, &msg);
+                }
                 let (global_state, ctx) = d.try_get_2_mut::<F::State, Context>();
                 let global_state: &mut F::State = global_state.expect("Activity State missing");
                 let display = ctx.expect("Context missing").display.full();
-                let projected_pos = msg.1 / display.browser_to_game_pixel_ratio();
+                let projected_pos = msg.1.times(display.browser_to_game_pixel_ratio().recip());
                 a.pointer(global_state, PointerEvent(msg.0, projected_pos))
             });
             // Clicks are available in all browsers and should be generated even from touchstart + touchend, as long as it is not cancelled explicitly.
@@ -72,6 +76,10 @@ impl<STATE: 'static, F: Frame<State = STATE> + Activity> FrameHandle<F> {
                 EventGate::listen(self, EventListenerType::Mouse);
                 EventGate::listen(self, EventListenerType::Touch);
             }
+        } else if focusable {
+            // Clicks are still needed to move the keyboard focus to this frame
+            activity.private_channel(move |_a, msg: PointerEvent| focus_on_click(activity, &msg));
+            EventGate::listen(self, EventListenerType::Click);
         }
         let div: div::DivHandle = self.div().clone();
         if (F::enter as usize) != (Nop::<F::State>::enter as usize) {
@@ -89,15 +97,37 @@ impl<STATE: 'static, F: Frame<State = STATE> + Activity> FrameHandle<F> {
                 a.leave(global_state)
             });
         }
-        if (F::key as usize) != (Nop::<F::State>::key as usize) {
+        if focusable {
             activity.private_domained_channel(|a, d, msg: KeyEvent| {
                 let global_state = d.try_get_mut::<F::State>().expect("Activity State missing");
                 a.key(global_state, msg)
             });
+            FocusManager::register(activity);
+            // Frames in the background cannot hold the focus
+            activity.on_enter(move |_| FocusManager::set_active(activity, true));
+            activity.on_leave(move |_| FocusManager::set_active(activity, false));
             EventGate::listen(self, EventListenerType::Keyboard)
         }
+        if (F::focus as usize) != (Nop::<F::State>::focus as usize)
+            || (F::blur as usize) != (Nop::<F::State>::blur as usize)
+        {
+            activity.private_domained_channel(|a, d, msg: FocusChange| {
+                let global_state = d.try_get_mut::<F::State>().expect("Activity State missing");
+                match msg {
+                    FocusChange::Gained => a.focus(global_state),
+                    FocusChange::Lost => a.blur(global_state),
+                }
+            });
+        }
     }
 }
+
+fn focus_on_click<F>(activity: ActivityId<F>, event: &PointerEvent) {
+    if let PointerEventType::Down | PointerEventType::PrimaryClick = event.event_type() {
+        FocusManager::focus(activity);
+    }
+}
+
 impl<STATE: 'static, FRAME: Frame<State = STATE>> FrameHandle<FRAME> {
     pub fn listen<F, MSG>(&self, f: F)
     where
@@ -1,12 +1,20 @@
 mod grid;
+mod hex_grid;
+mod iso_grid;
+mod layout;
 mod rectangle;
 mod scalar;
+mod spatial_hash;
 mod transform;
 mod vector;
 
 pub use grid::*;
+pub use hex_grid::*;
+pub use iso_grid::*;
+pub use layout::*;
 pub use rectangle::*;
 pub use scalar::*;
+pub use spatial_hash::*;
 pub use transform::*;
 pub use vector::*;
 
@@ -0,0 +1,267 @@
+use crate::Vector;
+
+const SQRT_3: f32 = 1.732_050_8;
+
+/// Position of a hexagon in axial coordinates.
+///
+/// `q` grows to the right and `r` grows down-right for pointy-top hexagons or down for flat-top hexagons.
+/// The third cube coordinate `s` is implied by `q + r + s = 0`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Hex {
+    pub q: i32,
+    pub r: i32,
+}
+
+/// Which way hexagons point
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HexOrientation {
+    /// Corners at the top and bottom, hexagons form rows
+    PointyTop,
+    /// Edges at the top and bottom, hexagons form columns
+    FlatTop,
+}
+
+/// Which rows (pointy-top) or columns (flat-top) are shifted by half a hexagon in offset coordinates
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HexOffset {
+    Odd,
+    Even,
+}
+
+/// Maps between hexagons and world space.
+///
+/// Hexagons are defined by the distance from their center to their corners.
+/// The center of `Hex::new(0, 0)` is at the origin.
+///
+/// ```
+/// # use paddle::*;
+/// let grid = HexGrid::new(HexOrientation::PointyTop, 10.0);
+/// let hex = Hex::new(2, -1);
+/// assert_eq!(grid.world_to_hex(grid.hex_to_world(hex)), hex);
+/// assert_eq!(hex.distance(Hex::new(0, 0)), 2);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HexGrid {
+    orientation: HexOrientation,
+    size: f32,
+    origin: Vector,
+    offset: HexOffset,
+}
+
+/// The hexagon of a single cell in world space, for drawing
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HexCell {
+    pub center: Vector,
+    /// Distance from the center to the corners
+    pub size: f32,
+    pub orientation: HexOrientation,
+}
+
+impl Hex {
+    /// Steps to the six neighbours, counter-clockwise starting to the right (pointy-top) or down-right (flat-top)
+    pub const DIRECTIONS: [Hex; 6] = [
+        Hex { q: 1, r: 0 },
+        Hex { q: 1, r: -1 },
+        Hex { q: 0, r: -1 },
+        Hex { q: -1, r: 0 },
+        Hex { q: -1, r: 1 },
+        Hex { q: 0, r: 1 },
+    ];
+
+    pub const fn new(q: i32, r: i32) -> Hex {
+        Hex { q, r }
+    }
+    /// The implied third cube coordinate
+    pub fn s(self) -> i32 {
+        -self.q - self.r
+    }
+    /// Number of steps between two hexagons
+    pub fn distance(self, other: Hex) -> u32 {
+        let (dq, dr, ds) = (self.q - other.q, self.r - other.r, self.s() - other.s());
+        ((dq.abs() + dr.abs() + ds.abs()) / 2) as u32
+    }
+    pub fn neighbour(self, direction: usize) -> Hex {
+        let step = Hex::DIRECTIONS[direction % 6];
+        Hex::new(self.q + step.q, self.r + step.r)
+    }
+    pub fn neighbours(self) -> [Hex; 6] {
+        [0, 1, 2, 3, 4, 5].map(|d| self.neighbour(d))
+    }
+    /// All hexagons at most `radius` steps away, including this one
+    pub fn range(self, radius: u32) -> impl Iterator<Item = Hex> {
+        let n = radius as i32;
+        (-n..=n).flat_map(move |dq| {
+            ((-n).max(-dq - n)..=n.min(-dq + n)).map(move |dr| Hex::new(self.q + dq, self.r + dr))
+        })
+    }
+    /// The hexagon containing fractional axial coordinates
+    pub fn round(q: f32, r: f32) -> Hex {
+        let s = -q - r;
+        let (mut rq, mut rr, rs) = (q.round(), r.round(), s.round());
+        let (dq, dr, ds) = ((rq - q).abs(), (rr - r).abs(), (rs - s).abs());
+        if dq > dr && dq > ds {
+            rq = -rr - rs;
+        } else if dr > ds {
+            rr = -rq - rs;
+        }
+        Hex::new(rq as i32, rr as i32)
+    }
+}
+
+impl HexGrid {
+    /// A grid of hexagons with the given distance from center to corners, using odd offset coordinates
+    pub fn new(orientation: HexOrientation, size: f32) -> Self {
+        HexGrid {
+            orientation,
+            size,
+            origin: Vector::ZERO,
+            offset: HexOffset::Odd,
+        }
+    }
+    /// Move the center of `Hex::new(0, 0)`
+    pub fn with_origin(mut self, origin: impl Into<Vector>) -> Self {
+        self.origin = origin.into();
+        self
+    }
+    pub fn with_offset(mut self, offset: HexOffset) -> Self {
+        self.offset = offset;
+        self
+    }
+    pub fn orientation(&self) -> HexOrientation {
+        self.orientation
+    }
+    pub fn size(&self) -> f32 {
+        self.size
+    }
+    /// Width and height of a single hexagon
+    pub fn hex_size(&self) -> Vector {
+        match self.orientation {
+            HexOrientation::PointyTop => Vector::new(SQRT_3 * self.size, 2.0 * self.size),
+            HexOrientation::FlatTop => Vector::new(2.0 * self.size, SQRT_3 * self.size),
+        }
+    }
+    /// Center of a hexagon
+    pub fn hex_to_world(&self, hex: Hex) -> Vector {
+        let (q, r) = (hex.q as f32, hex.r as f32);
+        let pos = match self.orientation {
+            HexOrientation::PointyTop => Vector::new(SQRT_3 * q + SQRT_3 / 2.0 * r, 1.5 * r),
+            HexOrientation::FlatTop => Vector::new(1.5 * q, SQRT_3 / 2.0 * q + SQRT_3 * r),
+        };
+        self.origin + pos * self.size
+    }
+    /// The hexagon containing a point
+    pub fn world_to_hex(&self, point: impl Into<Vector>) -> Hex {
+        let p = (point.into() - self.origin) * (1.0 / self.size);
+        match self.orientation {
+            HexOrientation::PointyTop => {
+                Hex::round(SQRT_3 / 3.0 * p.x - p.y / 3.0, 2.0 / 3.0 * p.y)
+            }
+            HexOrientation::FlatTop => Hex::round(2.0 / 3.0 * p.x, -p.x / 3.0 + SQRT_3 / 3.0 * p.y),
+        }
+    }
+    /// The area of a hexagon, which can be drawn
+    pub fn cell(&self, hex: Hex) -> HexCell {
+        HexCell {
+            center: self.hex_to_world(hex),
+            size: self.size,
+            orientation: self.orientation,
+        }
+    }
+    /// Convert to column and row in a rectangular layout, where every other row (pointy-top) or column (flat-top) is shifted
+    pub fn hex_to_offset(&self, hex: Hex) -> (i32, i32) {
+        match self.orientation {
+            HexOrientation::PointyTop => (hex.q + self.shift(hex.r), hex.r),
+            HexOrientation::FlatTop => (hex.q, hex.r + self.shift(hex.q)),
+        }
+    }
+    /// Convert from column and row in a rectangular layout
+    pub fn offset_to_hex(&self, (col, row): (i32, i32)) -> Hex {
+        match self.orientation {
+            HexOrientation::PointyTop => Hex::new(col - self.shift(row), row),
+            HexOrientation::FlatTop => Hex::new(col, row - self.shift(col)),
+        }
+    }
+    /// Offset of a shifted row or column in axial coordinates
+    fn shift(&self, n: i32) -> i32 {
+        match self.offset {
+            HexOffset::Odd => (n - (n & 1)) / 2,
+            HexOffset::Even => (n + (n & 1)) / 2,
+        }
+    }
+}
+
+impl HexCell {
+    /// Corners in clockwise order on screen, starting to the right of the top (pointy-top) or at the right (flat-top)
+    pub fn corners(&self) -> [Vector; 6] {
+        let start = match self.orientation {
+            HexOrientation::PointyTop => -30.0,
+            HexOrientation::FlatTop => 0.0,
+        };
+        [0, 1, 2, 3, 4, 5]
+            .map(|i| self.center + Vector::from_angle(start + 60.0 * i as f32) * self.size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distance_and_neighbours() {
+        let origin = Hex::new(0, 0);
+        assert_eq!(Hex::new(3, -1).distance(origin), 3);
+        assert_eq!(Hex::new(-2, -1).distance(Hex::new(1, 1)), 5);
+        for n in origin.neighbours().iter() {
+            assert_eq!(n.distance(origin), 1);
+        }
+        assert_eq!(origin.neighbour(7), Hex::new(1, -1));
+        let range: Vec<_> = Hex::new(5, 5).range(2).collect();
+        assert_eq!(range.len(), 19);
+        assert!(range.iter().all(|h| h.distance(Hex::new(5, 5)) <= 2));
+    }
+
+    #[test]
+    fn world_conversion() {
+        for orientation in [HexOrientation::PointyTop, HexOrientation::FlatTop] {
+            let grid = HexGrid::new(orientation, 10.0).with_origin((100, 50));
+            for hex in Hex::new(0, 0).range(3) {
+                let center = grid.hex_to_world(hex);
+                assert_eq!(grid.world_to_hex(center), hex);
+                // Points just inside the corners still belong to the hexagon
+                for corner in grid.cell(hex).corners().iter() {
+                    let inside = center + (*corner - center) * 0.95;
+                    assert_eq!(grid.world_to_hex(inside), hex);
+                }
+            }
+        }
+        let pointy = HexGrid::new(HexOrientation::PointyTop, 10.0);
+        assert_eq!(
+            pointy.hex_to_world(Hex::new(1, 0)),
+            Vector::new(SQRT_3 * 10.0, 0)
+        );
+        assert_eq!(pointy.hex_size(), Vector::new(SQRT_3 * 10.0, 20.0));
+        let flat = HexGrid::new(HexOrientation::FlatTop, 10.0);
+        assert_eq!(
+            flat.hex_to_world(Hex::new(0, 1)),
+            Vector::new(0, SQRT_3 * 10.0)
+        );
+    }
+
+    #[test]
+    fn offset_coordinates() {
+        let pointy = HexGrid::new(HexOrientation::PointyTop, 1.0);
+        // Odd rows are shifted right, so the hexagon below-right of (0, 0) is still in column 0
+        assert_eq!(pointy.hex_to_offset(Hex::new(0, 1)), (0, 1));
+        assert_eq!(pointy.hex_to_offset(Hex::new(-1, 2)), (0, 2));
+        let even = pointy.with_offset(HexOffset::Even);
+        assert_eq!(even.hex_to_offset(Hex::new(0, 1)), (1, 1));
+        let flat = HexGrid::new(HexOrientation::FlatTop, 1.0);
+        assert_eq!(flat.hex_to_offset(Hex::new(1, 0)), (1, 0));
+        assert_eq!(flat.hex_to_offset(Hex::new(2, -1)), (2, 0));
+        for grid in [pointy, even, flat, flat.with_offset(HexOffset::Even)] {
+            for hex in Hex::new(0, 0).range(4) {
+                assert_eq!(grid.offset_to_hex(grid.hex_to_offset(hex)), hex);
+            }
+        }
+    }
+}
@@ -0,0 +1,185 @@
+use crate::quicksilver_compat::geom::Neighbourhood;
+use crate::Vector;
+
+/// How the tiles of an isometric map are arranged
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IsoLayout {
+    /// The map forms one large diamond, the x axis runs down-right and the y axis down-left
+    Diamond,
+    /// The map forms a rectangle, rows zig-zag with every odd row shifted right by half a tile
+    Staggered,
+}
+
+/// Maps between isometric tile coordinates and world space.
+///
+/// Tiles are diamonds with the given width and height, usually twice as wide as high.
+/// Tile `(0, 0)` has its top corner at the origin for `IsoLayout::Diamond` and its bounding box at the origin for `IsoLayout::Staggered`.
+///
+/// ```
+/// # use paddle::*;
+/// let grid = IsoGrid::new(IsoLayout::Diamond, (64, 32));
+/// assert_eq!(grid.tile_to_world((1, 0)), Vector::new(32, 32));
+/// assert_eq!(grid.world_to_tile((32, 32)), (1, 0));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct IsoGrid {
+    layout: IsoLayout,
+    tile_size: Vector,
+    origin: Vector,
+}
+
+impl IsoGrid {
+    pub fn new(layout: IsoLayout, tile_size: impl Into<Vector>) -> Self {
+        IsoGrid {
+            layout,
+            tile_size: tile_size.into(),
+            origin: Vector::ZERO,
+        }
+    }
+    pub fn with_origin(mut self, origin: impl Into<Vector>) -> Self {
+        self.origin = origin.into();
+        self
+    }
+    pub fn layout(&self) -> IsoLayout {
+        self.layout
+    }
+    pub fn tile_size(&self) -> Vector {
+        self.tile_size
+    }
+    /// Center of a tile
+    pub fn tile_to_world(&self, tile: (i32, i32)) -> Vector {
+        let (a, b) = self.diamond_coord(tile);
+        let half = self.tile_size * 0.5;
+        let base = match self.layout {
+            IsoLayout::Diamond => Vector::ZERO,
+            IsoLayout::Staggered => half.x_comp(),
+        };
+        self.origin + base + Vector::new((a - b) as f32 * half.x, (a + b + 1) as f32 * half.y)
+    }
+    /// The tile containing a point
+    pub fn world_to_tile(&self, point: impl Into<Vector>) -> (i32, i32) {
+        let mut p = point.into() - self.origin;
+        if self.layout == IsoLayout::Staggered {
+            p.x -= self.tile_size.x * 0.5;
+        }
+        let u = p.x / (self.tile_size.x * 0.5);
+        let v = p.y / (self.tile_size.y * 0.5);
+        let a = ((v + u) * 0.5).floor() as i32;
+        let b = ((v - u) * 0.5).floor() as i32;
+        self.layout_coord((a, b))
+    }
+    /// Corners of a tile: top, right, bottom and left
+    pub fn corners(&self, tile: (i32, i32)) -> [Vector; 4] {
+        let center = self.tile_to_world(tile);
+        let half = self.tile_size * 0.5;
+        [
+            center - half.y_comp(),
+            center + half.x_comp(),
+            center + half.y_comp(),
+            center - half.x_comp(),
+        ]
+    }
+    /// Tiles adjacent to a tile, in clockwise order.
+    ///
+    /// With `Neighbourhood::Four`, only the tiles sharing an edge are returned, starting at the top right.
+    /// With `Neighbourhood::Eight`, tiles touching a corner are included, starting at the top.
+    pub fn neighbours(&self, tile: (i32, i32), neighbourhood: Neighbourhood) -> Vec<(i32, i32)> {
+        let offsets: &[(i32, i32)] = match neighbourhood {
+            Neighbourhood::Four => &[(0, -1), (1, 0), (0, 1), (-1, 0)],
+            Neighbourhood::Eight => &[
+                (-1, -1),
+                (0, -1),
+                (1, -1),
+                (1, 0),
+                (1, 1),
+                (0, 1),
+                (-1, 1),
+                (-1, 0),
+            ],
+        };
+        let (a, b) = self.diamond_coord(tile);
+        offsets
+            .iter()
+            .map(|(da, db)| self.layout_coord((a + da, b + db)))
+            .collect()
+    }
+    /// Number of steps between two tiles when moving to any of the eight neighbours
+    pub fn distance(&self, from: (i32, i32), to: (i32, i32)) -> u32 {
+        let (a0, b0) = self.diamond_coord(from);
+        let (a1, b1) = self.diamond_coord(to);
+        (a1 - a0).abs().max((b1 - b0).abs()) as u32
+    }
+
+    /// Convert to the axes of the diamond layout
+    fn diamond_coord(&self, (x, y): (i32, i32)) -> (i32, i32) {
+        match self.layout {
+            IsoLayout::Diamond => (x, y),
+            IsoLayout::Staggered => {
+                let odd = y.rem_euclid(2);
+                (x + (y + odd) / 2, (y - odd) / 2 - x)
+            }
+        }
+    }
+    /// Convert back from the axes of the diamond layout
+    fn layout_coord(&self, (a, b): (i32, i32)) -> (i32, i32) {
+        match self.layout {
+            IsoLayout::Diamond => (a, b),
+            IsoLayout::Staggered => {
+                let y = a + b;
+                ((a - b - y.rem_euclid(2)).div_euclid(2), y)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diamond() {
+        let grid = IsoGrid::new(IsoLayout::Diamond, (64, 32)).with_origin((100, 0));
+        assert_eq!(grid.tile_to_world((0, 0)), Vector::new(100, 16));
+        assert_eq!(grid.tile_to_world((0, 1)), Vector::new(68, 32));
+        assert_eq!(grid.world_to_tile((100, 1)), (0, 0));
+        // Just outside the top corner of the diamond
+        assert_eq!(grid.world_to_tile((80, 1)), (-1, 0));
+        assert_eq!(grid.distance((0, 0), (3, -2)), 3);
+        assert_eq!(
+            grid.neighbours((2, 2), Neighbourhood::Four),
+            vec![(2, 1), (3, 2), (2, 3), (1, 2)]
+        );
+    }
+
+    #[test]
+    fn staggered() {
+        let grid = IsoGrid::new(IsoLayout::Staggered, (64, 32));
+        assert_eq!(grid.tile_to_world((0, 0)), Vector::new(32, 16));
+        assert_eq!(grid.tile_to_world((1, 0)), Vector::new(96, 16));
+        // Odd rows are shifted right by half a tile
+        assert_eq!(grid.tile_to_world((0, 1)), Vector::new(64, 32));
+        assert_eq!(grid.tile_to_world((0, 2)), Vector::new(32, 48));
+        for y in -3..4 {
+            for x in -3..4 {
+                let center = grid.tile_to_world((x, y));
+                assert_eq!(grid.world_to_tile(center), (x, y));
+                for corner in grid.corners((x, y)).iter() {
+                    let inside = center + (*corner - center) * 0.9;
+                    assert_eq!(grid.world_to_tile(inside), (x, y));
+                }
+            }
+        }
+        // Edge neighbours of an even and an odd row tile
+        assert_eq!(
+            grid.neighbours((1, 2), Neighbourhood::Four),
+            vec![(1, 1), (1, 3), (0, 3), (0, 1)]
+        );
+        assert_eq!(
+            grid.neighbours((1, 1), Neighbourhood::Four),
+            vec![(2, 0), (2, 2), (1, 2), (1, 0)]
+        );
+        assert_eq!(grid.neighbours((1, 1), Neighbourhood::Eight).len(), 8);
+        assert_eq!(grid.distance((0, 0), (0, 2)), 1);
+        assert_eq!(grid.distance((0, 0), (2, 0)), 2);
+    }
+}
@@ -0,0 +1,425 @@
+//! Declarative layouts that resolve to rectangles.
+//!
+//! A layout is a tree of rows, columns, and stacks. Leaves (and containers) can carry a key, under which the resolved area can be looked up.
+//! Resolving the same tree for a different area gives a responsive layout.
+//!
+//! ```
+//! use paddle::{LayoutNode, LayoutSize, Rectangle};
+//! let layout = LayoutNode::column()
+//!     .with_child(LayoutNode::leaf("header").with_height(LayoutSize::Fixed(50.0)))
+//!     .with_child(
+//!         LayoutNode::row()
+//!             .with_child(LayoutNode::leaf("menu").with_width(LayoutSize::Percent(0.25)))
+//!             .with_child(LayoutNode::leaf("main").with_margin(10.0)),
+//!     );
+//! let areas = layout.resolve(&Rectangle::new_sized((800, 600)));
+//! assert_eq!(areas["menu"], Rectangle::new((0, 50), (200, 550)));
+//! assert_eq!(areas["main"], Rectangle::new((210, 60), (580, 530)));
+//! ```
+
+use crate::{FitStrategy, Rectangle, Vector};
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Size of a layout node along one axis
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum LayoutSize {
+    /// Absolute size, in the same units as the resolved area
+    Fixed(f32),
+    /// Relative to the inner size of the parent, from 0.0 to 1.0
+    Percent(f32),
+    /// Share of the space left after fixed and percentage sizes have been distributed, weighted by the given factor.
+    ///
+    /// On the cross axis of a row or column (and in stacks), any flex value fills the available space.
+    Flex(f32),
+}
+
+/// Space around the four sides of a rectangle
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Margin {
+    pub top: f32,
+    pub right: f32,
+    pub bottom: f32,
+    pub left: f32,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum LayoutKind {
+    Leaf,
+    /// Children placed from left to right
+    Row,
+    /// Children placed from top to bottom
+    Column,
+    /// Children placed on top of each other
+    Stack,
+}
+
+/// A node in a layout tree, see the module documentation.
+#[derive(Clone, Debug)]
+pub struct LayoutNode<K> {
+    kind: LayoutKind,
+    key: Option<K>,
+    children: Vec<LayoutNode<K>>,
+    width: LayoutSize,
+    height: LayoutSize,
+    margin: Margin,
+    padding: Margin,
+    spacing: f32,
+    align: FitStrategy,
+    aspect_ratio: Option<f32>,
+}
+
+impl Margin {
+    pub fn new(top: f32, right: f32, bottom: f32, left: f32) -> Self {
+        Self {
+            top,
+            right,
+            bottom,
+            left,
+        }
+    }
+    /// Same margin on all four sides
+    pub fn uniform(m: f32) -> Self {
+        Self::new(m, m, m, m)
+    }
+    /// Margin on the left and right, and on the top and bottom
+    pub fn symmetric(horizontal: f32, vertical: f32) -> Self {
+        Self::new(vertical, horizontal, vertical, horizontal)
+    }
+    pub fn horizontal(&self) -> f32 {
+        self.left + self.right
+    }
+    pub fn vertical(&self) -> f32 {
+        self.top + self.bottom
+    }
+    /// Shrinks the rectangle by the margin on each side. Sizes do not go below zero.
+    #[must_use]
+    pub fn shrink(&self, rect: &Rectangle) -> Rectangle {
+        let size = Vector::new(
+            (rect.width() - self.horizontal()).max(0.0),
+            (rect.height() - self.vertical()).max(0.0),
+        );
+        Rectangle::new(rect.pos + Vector::new(self.left, self.top), size)
+    }
+}
+
+impl From<f32> for Margin {
+    fn from(m: f32) -> Self {
+        Margin::uniform(m)
+    }
+}
+
+impl<K> LayoutNode<K> {
+    fn new(kind: LayoutKind, key: Option<K>) -> Self {
+        Self {
+            kind,
+            key,
+            children: vec![],
+            width: LayoutSize::Flex(1.0),
+            height: LayoutSize::Flex(1.0),
+            margin: Margin::default(),
+            padding: Margin::default(),
+            spacing: 0.0,
+            align: FitStrategy::TopLeft,
+            aspect_ratio: None,
+        }
+    }
+    /// A node without children, which can be looked up by its key after resolving
+    pub fn leaf(key: K) -> Self {
+        Self::new(LayoutKind::Leaf, Some(key))
+    }
+    /// Empty space, useful to push other nodes aside
+    pub fn spacer() -> Self {
+        Self::new(LayoutKind::Leaf, None)
+    }
+    /// A container placing its children from left to right
+    pub fn row() -> Self {
+        Self::new(LayoutKind::Row, None)
+    }
+    /// A container placing its children from top to bottom
+    pub fn column() -> Self {
+        Self::new(LayoutKind::Column, None)
+    }
+    /// A container placing all children in the same area, on top of each other
+    pub fn stack() -> Self {
+        Self::new(LayoutKind::Stack, None)
+    }
+    /// Makes the area of a container available after resolving
+    pub fn with_key(mut self, key: K) -> Self {
+        self.key = Some(key);
+        self
+    }
+    pub fn with_child(mut self, child: LayoutNode<K>) -> Self {
+        self.children.push(child);
+        self
+    }
+    pub fn with_children(mut self, children: impl IntoIterator<Item = LayoutNode<K>>) -> Self {
+        self.children.extend(children);
+        self
+    }
+    pub fn with_width(mut self, width: LayoutSize) -> Self {
+        self.width = width;
+        self
+    }
+    pub fn with_height(mut self, height: LayoutSize) -> Self {
+        self.height = height;
+        self
+    }
+    /// Space around the node, outside of its area
+    pub fn with_margin(mut self, margin: impl Into<Margin>) -> Self {
+        self.margin = margin.into();
+        self
+    }
+    /// Space inside a container, between its border and its children
+    pub fn with_padding(mut self, padding: impl Into<Margin>) -> Self {
+        self.padding = padding.into();
+        self
+    }
+    /// Gap between the children of a row or a column
+    pub fn with_spacing(mut self, spacing: f32) -> Self {
+        self.spacing = spacing;
+        self
+    }
+    /// How the node is placed when it is smaller than the space available to it
+    pub fn with_alignment(mut self, align: FitStrategy) -> Self {
+        self.align = align;
+        self
+    }
+    /// Keep the proportions of width / height, shrinking the node to fit into its available space.
+    pub fn with_aspect_ratio(mut self, ratio: f32) -> Self {
+        self.aspect_ratio = Some(ratio);
+        self
+    }
+}
+
+impl<K: Hash + Eq + Clone> LayoutNode<K> {
+    /// Computes the areas of all keyed nodes, when the root is placed in the given area.
+    pub fn resolve(&self, area: &Rectangle) -> HashMap<K, Rectangle> {
+        let mut out = HashMap::new();
+        self.resolve_into(area, &mut out);
+        out
+    }
+    /// Like `resolve`, but writes the results into an existing map.
+    pub fn resolve_into(&self, area: &Rectangle, out: &mut HashMap<K, Rectangle>) {
+        let slot = self.margin.shrink(area);
+        let size = Vector::new(
+            cross_size(self.width, slot.width()),
+            cross_size(self.height, slot.height()),
+        );
+        let rect = self.fit(slot, size);
+        self.place(rect, out);
+    }
+
+    /// Positions the node inside of its slot (after margins have been removed)
+    fn fit(&self, slot: Rectangle, size: Vector) -> Rectangle {
+        let mut size = size;
+        if let Some(ratio) = self.aspect_ratio {
+            if size.y > 0.0 && ratio > 0.0 {
+                if size.x / size.y > ratio {
+                    size.x = size.y * ratio;
+                } else {
+                    size.y = size.x / ratio;
+                }
+            }
+        }
+        align(size, &slot, self.align)
+    }
+
+    /// Stores the area of this node and lays out children inside of it
+    fn place(&self, rect: Rectangle, out: &mut HashMap<K, Rectangle>) {
+        if let Some(key) = &self.key {
+            out.insert(key.clone(), rect);
+        }
+        let inner = self.padding.shrink(&rect);
+        match self.kind {
+            LayoutKind::Leaf => {}
+            LayoutKind::Stack => {
+                for child in &self.children {
+                    child.resolve_into(&inner, out);
+                }
+            }
+            LayoutKind::Row => self.place_linear(inner, true, out),
+            LayoutKind::Column => self.place_linear(inner, false, out),
+        }
+    }
+
+    fn place_linear(&self, inner: Rectangle, horizontal: bool, out: &mut HashMap<K, Rectangle>) {
+        if self.children.is_empty() {
+            return;
+        }
+        let main = |v: Vector| if horizontal { v.x } else { v.y };
+        let main_size = |n: &LayoutNode<K>| if horizontal { n.width } else { n.height };
+        let cross_size_of = |n: &LayoutNode<K>| if horizontal { n.height } else { n.width };
+        let main_margin = |n: &LayoutNode<K>| {
+            if horizontal {
+                n.margin.horizontal()
+            } else {
+                n.margin.vertical()
+            }
+        };
+
+        let available = main(inner.size);
+        let gaps = self.spacing * (self.children.len() - 1) as f32;
+        let mut fixed_total = gaps;
+        let mut flex_total = 0.0;
+        for child in &self.children {
+            fixed_total += main_margin(child);
+            match main_size(child) {
+                LayoutSize::Fixed(s) => fixed_total += s,
+                LayoutSize::Percent(p) => fixed_total += p * available,
+                LayoutSize::Flex(f) => flex_total += f,
+            }
+        }
+        let flex_space = (available - fixed_total).max(0.0);
+
+        let mut cursor = if horizontal { inner.x() } else { inner.y() };
+        for child in &self.children {
+            let extent = match main_size(child) {
+                LayoutSize::Fixed(s) => s,
+                LayoutSize::Percent(p) => p * available,
+                LayoutSize::Flex(f) if flex_total > 0.0 => flex_space * f / flex_total,
+                LayoutSize::Flex(_) => 0.0,
+            };
+            let slot_main = extent + main_margin(child);
+            let slot = if horizontal {
+                Rectangle::new((cursor, inner.y()), (slot_main, inner.height()))
+            } else {
+                Rectangle::new((inner.x(), cursor), (inner.width(), slot_main))
+            };
+            let slot = child.margin.shrink(&slot);
+            let cross = if horizontal {
+                cross_size(cross_size_of(child), slot.height())
+            } else {
+                cross_size(cross_size_of(child), slot.width())
+            };
+            let size = if horizontal {
+                Vector::new(slot.width(), cross)
+            } else {
+                Vector::new(cross, slot.height())
+            };
+            let rect = child.fit(slot, size);
+            child.place(rect, out);
+            cursor += slot_main + self.spacing;
+        }
+    }
+}
+
+/// Size on an axis where the node does not compete with siblings
+fn cross_size(size: LayoutSize, available: f32) -> f32 {
+    match size {
+        LayoutSize::Fixed(s) => s,
+        LayoutSize::Percent(p) => p * available,
+        LayoutSize::Flex(_) => available,
+    }
+}
+
+/// Places a rectangle of the given size inside the slot
+fn align(size: Vector, slot: &Rectangle, align: FitStrategy) -> Rectangle {
+    let free = slot.size - size;
+    let offset = match align {
+        FitStrategy::TopLeft => Vector::ZERO,
+        FitStrategy::LeftCenter => free.y_comp() / 2.0,
+        FitStrategy::Center => free / 2.0,
+    };
+    Rectangle::new(slot.pos + offset, size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn area() -> Rectangle {
+        Rectangle::new((100, 100), (1000, 500))
+    }
+
+    #[test]
+    fn row_with_fixed_percent_and_flex() {
+        let layout = LayoutNode::row()
+            .with_child(LayoutNode::leaf(0).with_width(LayoutSize::Fixed(100.0)))
+            .with_child(LayoutNode::leaf(1).with_width(LayoutSize::Percent(0.5)))
+            .with_child(LayoutNode::leaf(2).with_width(LayoutSize::Flex(1.0)))
+            .with_child(LayoutNode::leaf(3).with_width(LayoutSize::Flex(3.0)));
+        let r = layout.resolve(&area());
+        assert_eq!(r[&0], Rectangle::new((100, 100), (100, 500)));
+        assert_eq!(r[&1], Rectangle::new((200, 100), (500, 500)));
+        assert_eq!(r[&2], Rectangle::new((700, 100), (100, 500)));
+        assert_eq!(r[&3], Rectangle::new((800, 100), (300, 500)));
+    }
+
+    #[test]
+    fn column_with_spacing_and_padding() {
+        let layout = LayoutNode::column()
+            .with_key("root")
+            .with_padding(10.0)
+            .with_spacing(20.0)
+            .with_child(LayoutNode::leaf("a"))
+            .with_child(LayoutNode::leaf("b"));
+        let r = layout.resolve(&area());
+        assert_eq!(r["root"], area());
+        assert_eq!(r["a"], Rectangle::new((110, 110), (980, 230)));
+        assert_eq!(r["b"], Rectangle::new((110, 360), (980, 230)));
+    }
+
+    #[test]
+    fn margins() {
+        let layout = LayoutNode::row()
+            .with_child(
+                LayoutNode::leaf("a")
+                    .with_width(LayoutSize::Fixed(100.0))
+                    .with_margin(Margin::new(1.0, 2.0, 3.0, 4.0)),
+            )
+            .with_child(LayoutNode::leaf("b"));
+        let r = layout.resolve(&Rectangle::new_sized((200, 100)));
+        assert_eq!(r["a"], Rectangle::new((4, 1), (100, 96)));
+        assert_eq!(r["b"], Rectangle::new((106, 0), (94, 100)));
+    }
+
+    #[test]
+    fn cross_axis_alignment() {
+        let layout = LayoutNode::row()
+            .with_child(
+                LayoutNode::leaf("top")
+                    .with_width(LayoutSize::Fixed(50.0))
+                    .with_height(LayoutSize::Fixed(20.0)),
+            )
+            .with_child(
+                LayoutNode::leaf("mid")
+                    .with_width(LayoutSize::Fixed(50.0))
+                    .with_height(LayoutSize::Percent(0.5))
+                    .with_alignment(FitStrategy::LeftCenter),
+            );
+        let r = layout.resolve(&Rectangle::new_sized((100, 100)));
+        assert_eq!(r["top"], Rectangle::new((0, 0), (50, 20)));
+        assert_eq!(r["mid"], Rectangle::new((50, 25), (50, 50)));
+    }
+
+    #[test]
+    fn stack_with_aspect_ratio() {
+        let layout = LayoutNode::stack()
+            .with_child(LayoutNode::leaf("bg"))
+            .with_child(
+                LayoutNode::leaf("square")
+                    .with_aspect_ratio(1.0)
+                    .with_alignment(FitStrategy::Center),
+            );
+        let r = layout.resolve(&Rectangle::new_sized((300, 100)));
+        assert_eq!(r["bg"], Rectangle::new_sized((300, 100)));
+        assert_eq!(r["square"], Rectangle::new((100, 0), (100, 100)));
+    }
+
+    #[test]
+    fn nested_and_overfull() {
+        let layout = LayoutNode::column()
+            .with_child(
+                LayoutNode::row()
+                    .with_height(LayoutSize::Fixed(40.0))
+                    .with_child(LayoutNode::spacer())
+                    .with_child(LayoutNode::leaf("btn").with_width(LayoutSize::Fixed(30.0))),
+            )
+            .with_child(LayoutNode::leaf("rest").with_height(LayoutSize::Fixed(100.0)));
+        let r = layout.resolve(&Rectangle::new_sized((200, 100)));
+        assert_eq!(r["btn"], Rectangle::new((170, 0), (30, 40)));
+        // Fixed sizes are respected, even when they overflow
+        assert_eq!(r["rest"], Rectangle::new((0, 40), (200, 100)));
+    }
+}
@@ -0,0 +1,393 @@
+use crate::quicksilver_compat::geom::Shape;
+use crate::{Rectangle, Vector};
+use std::collections::HashMap;
+
+/// Refers to an entry of a `SpatialHash`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SpatialKey(usize);
+
+/// A payload hit by a ray cast
+#[derive(Clone, Copy, Debug)]
+pub struct RayHit<'a, T> {
+    pub key: SpatialKey,
+    pub payload: &'a T,
+    /// Distance from the ray origin to the entry bounds, 0 if the origin is inside
+    pub distance: f32,
+}
+
+struct Entry<T> {
+    bounds: Rectangle,
+    payload: T,
+}
+
+type Cell = (i32, i32);
+
+/// Payloads indexed by rectangular bounds, for fast hit testing among many objects.
+///
+/// Space is divided into square cells and each entry is stored in all cells it touches.
+/// Cells should be about as large as typical entries, for example 64 for objects of 30 to 100 pixels.
+///
+/// Query results are ordered by key, which is the insertion order unless keys of removed entries have been reused.
+///
+/// ```
+/// # use paddle::*;
+/// let mut index = SpatialHash::new(64.0);
+/// let a = index.insert(Rectangle::new((0, 0), (10, 10)), "a");
+/// index.insert(Rectangle::new((100, 100), (10, 10)), "b");
+/// assert_eq!(index.query_point((5, 5)), vec![(a, &"a")]);
+/// ```
+pub struct SpatialHash<T> {
+    cell_size: f32,
+    cells: HashMap<Cell, Vec<usize>>,
+    entries: Vec<Option<Entry<T>>>,
+    free: Vec<usize>,
+    len: usize,
+}
+
+impl<T> SpatialHash<T> {
+    pub fn new(cell_size: f32) -> Self {
+        assert!(cell_size > 0.0, "Cell size must be positive");
+        Self {
+            cell_size,
+            cells: HashMap::new(),
+            entries: Vec::new(),
+            free: Vec::new(),
+            len: 0,
+        }
+    }
+    pub fn insert(&mut self, bounds: Rectangle, payload: T) -> SpatialKey {
+        let i = match self.free.pop() {
+            Some(i) => i,
+            None => {
+                self.entries.push(None);
+                self.entries.len() - 1
+            }
+        };
+        self.entries[i] = Some(Entry { bounds, payload });
+        self.add_to_cells(i, &bounds);
+        self.len += 1;
+        SpatialKey(i)
+    }
+    /// Keys of removed entries may be handed out again by `insert`
+    pub fn remove(&mut self, key: SpatialKey) -> Option<T> {
+        let entry = self.entries.get_mut(key.0)?.take()?;
+        self.remove_from_cells(key.0, &entry.bounds);
+        self.free.push(key.0);
+        self.len -= 1;
+        Some(entry.payload)
+    }
+    /// Move an entry to new bounds. Returns false if the key does not exist.
+    pub fn update(&mut self, key: SpatialKey, bounds: Rectangle) -> bool {
+        let old = match self.entries.get_mut(key.0) {
+            Some(Some(entry)) => std::mem::replace(&mut entry.bounds, bounds),
+            _ => return false,
+        };
+        if self.cell_range(&old) != self.cell_range(&bounds) {
+            self.remove_from_cells(key.0, &old);
+            self.add_to_cells(key.0, &bounds);
+        }
+        true
+    }
+    pub fn get(&self, key: SpatialKey) -> Option<&T> {
+        self.entry(key.0).map(|e| &e.payload)
+    }
+    pub fn get_mut(&mut self, key: SpatialKey) -> Option<&mut T> {
+        self.entries
+            .get_mut(key.0)?
+            .as_mut()
+            .map(|e| &mut e.payload)
+    }
+    pub fn bounds(&self, key: SpatialKey) -> Option<Rectangle> {
+        self.entry(key.0).map(|e| e.bounds)
+    }
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    pub fn clear(&mut self) {
+        self.cells.clear();
+        self.entries.clear();
+        self.free.clear();
+        self.len = 0;
+    }
+    pub fn iter(&self) -> impl Iterator<Item = (SpatialKey, &Rectangle, &T)> {
+        self.entries
+            .iter()
+            .enumerate()
+            .filter_map(|(i, e)| e.as_ref().map(|e| (SpatialKey(i), &e.bounds, &e.payload)))
+    }
+
+    /// All entries whose bounds contain the point
+    pub fn query_point(&self, point: impl Into<Vector>) -> Vec<(SpatialKey, &T)> {
+        let point = point.into();
+        let mut found: Vec<usize> = self
+            .cells
+            .get(&self.cell_of(point))
+            .into_iter()
+            .flatten()
+            .copied()
+            .filter(|&i| self.entry(i).unwrap().bounds.contains(point))
+            .collect();
+        found.sort_unstable();
+        self.resolve(found)
+    }
+    /// All entries whose bounds overlap the area
+    pub fn query_area(&self, area: &Rectangle) -> Vec<(SpatialKey, &T)> {
+        let ((x0, y0), (x1, y1)) = self.cell_range(area);
+        let mut found = Vec::new();
+        for x in x0..=x1 {
+            for y in y0..=y1 {
+                if let Some(cell) = self.cells.get(&(x, y)) {
+                    found.extend(
+                        cell.iter()
+                            .copied()
+                            .filter(|&i| self.entry(i).unwrap().bounds.overlaps_rectangle(area)),
+                    );
+                }
+            }
+        }
+        found.sort_unstable();
+        found.dedup();
+        self.resolve(found)
+    }
+    /// All entries hit by a ray, ordered by distance.
+    ///
+    /// The direction does not have to be normalized, distances are measured in the units of the index.
+    pub fn raycast(
+        &self,
+        origin: impl Into<Vector>,
+        direction: impl Into<Vector>,
+        max_distance: f32,
+    ) -> Vec<RayHit<'_, T>> {
+        let origin = origin.into();
+        let direction = direction.into();
+        if direction.len2() == 0.0 || self.is_empty() {
+            return Vec::new();
+        }
+        let direction = direction.normalize();
+        let mut candidates = Vec::new();
+        self.traverse(origin, direction, max_distance, |cell| {
+            candidates.extend_from_slice(cell)
+        });
+        candidates.sort_unstable();
+        candidates.dedup();
+        let mut hits: Vec<RayHit<'_, T>> = candidates
+            .into_iter()
+            .filter_map(|i| {
+                let entry = self.entry(i).unwrap();
+                ray_rectangle(origin, direction, &entry.bounds)
+                    .filter(|&d| d <= max_distance)
+                    .map(|distance| RayHit {
+                        key: SpatialKey(i),
+                        payload: &entry.payload,
+                        distance,
+                    })
+            })
+            .collect();
+        hits.sort_by(|a, b| a.distance.partial_cmp(&b.distance).unwrap());
+        hits
+    }
+    /// The closest entry hit by a ray
+    pub fn raycast_first(
+        &self,
+        origin: impl Into<Vector>,
+        direction: impl Into<Vector>,
+        max_distance: f32,
+    ) -> Option<RayHit<'_, T>> {
+        self.raycast(origin, direction, max_distance)
+            .into_iter()
+            .next()
+    }
+
+    fn entry(&self, i: usize) -> Option<&Entry<T>> {
+        self.entries.get(i)?.as_ref()
+    }
+    fn resolve(&self, indices: Vec<usize>) -> Vec<(SpatialKey, &T)> {
+        indices
+            .into_iter()
+            .map(|i| (SpatialKey(i), &self.entry(i).unwrap().payload))
+            .collect()
+    }
+    fn cell_of(&self, p: Vector) -> Cell {
+        (
+            (p.x / self.cell_size).floor() as i32,
+            (p.y / self.cell_size).floor() as i32,
+        )
+    }
+    fn cell_range(&self, bounds: &Rectangle) -> (Cell, Cell) {
+        (
+            self.cell_of(bounds.top_left()),
+            self.cell_of(bounds.top_left() + bounds.size()),
+        )
+    }
+    fn add_to_cells(&mut self, i: usize, bounds: &Rectangle) {
+        let ((x0, y0), (x1, y1)) = self.cell_range(bounds);
+        for x in x0..=x1 {
+            for y in y0..=y1 {
+                self.cells.entry((x, y)).or_default().push(i);
+            }
+        }
+    }
+    fn remove_from_cells(&mut self, i: usize, bounds: &Rectangle) {
+        let ((x0, y0), (x1, y1)) = self.cell_range(bounds);
+        for x in x0..=x1 {
+            for y in y0..=y1 {
+                if let Some(cell) = self.cells.get_mut(&(x, y)) {
+                    cell.retain(|&j| j != i);
+                    if cell.is_empty() {
+                        self.cells.remove(&(x, y));
+                    }
+                }
+            }
+        }
+    }
+    /// Visit the occupied cells along a ray, in order, without leaving the area covered by any cell
+    fn traverse(
+        &self,
+        origin: Vector,
+        direction: Vector,
+        max_distance: f32,
+        mut visit: impl FnMut(&[usize]),
+    ) {
+        let (min, max) = self.cells.keys().fold(
+            ((i32::MAX, i32::MAX), (i32::MIN, i32::MIN)),
+            |(min, max), &(x, y)| ((min.0.min(x), min.1.min(y)), (max.0.max(x), max.1.max(y))),
+        );
+        let (mut x, mut y) = self.cell_of(origin);
+        // Amanatides & Woo: distance along the ray to the next cell boundary and between boundaries, per axis
+        let axis = |p: f32, d: f32, cell: i32| -> (i32, f32, f32) {
+            if d > 0.0 {
+                let next = (cell + 1) as f32 * self.cell_size;
+                (1, (next - p) / d, self.cell_size / d)
+            } else if d < 0.0 {
+                let next = cell as f32 * self.cell_size;
+                (-1, (next - p) / d, -self.cell_size / d)
+            } else {
+                (0, f32::INFINITY, f32::INFINITY)
+            }
+        };
+        let (step_x, mut next_x, delta_x) = axis(origin.x, direction.x, x);
+        let (step_y, mut next_y, delta_y) = axis(origin.y, direction.y, y);
+        let mut t = 0.0;
+        while t <= max_distance {
+            if let Some(cell) = self.cells.get(&(x, y)) {
+                visit(cell);
+            }
+            if next_x < next_y {
+                t = next_x;
+                next_x += delta_x;
+                x += step_x;
+            } else {
+                t = next_y;
+                next_y += delta_y;
+                y += step_y;
+            }
+            let leaving = (step_x > 0 && x > max.0)
+                || (step_x < 0 && x < min.0)
+                || (step_y > 0 && y > max.1)
+                || (step_y < 0 && y < min.1);
+            if leaving {
+                break;
+            }
+        }
+    }
+}
+
+/// Slab test, returns the distance at which a normalized ray enters the rectangle
+fn ray_rectangle(origin: Vector, direction: Vector, rect: &Rectangle) -> Option<f32> {
+    let mut enter = 0.0f32;
+    let mut exit = f32::INFINITY;
+    let min = rect.top_left();
+    let max = min + rect.size();
+    for (o, d, lo, hi) in [
+        (origin.x, direction.x, min.x, max.x),
+        (origin.y, direction.y, min.y, max.y),
+    ] {
+        if d == 0.0 {
+            if o < lo || o > hi {
+                return None;
+            }
+        } else {
+            let t1 = (lo - o) / d;
+            let t2 = (hi - o) / d;
+            enter = enter.max(t1.min(t2));
+            exit = exit.min(t1.max(t2));
+        }
+    }
+    if enter <= exit {
+        Some(enter)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys<T>(found: Vec<(SpatialKey, &T)>) -> Vec<SpatialKey> {
+        found.into_iter().map(|(k, _)| k).collect()
+    }
+
+    #[test]
+    fn point_and_area_queries() {
+        let mut index = SpatialHash::new(10.0);
+        let a = index.insert(Rectangle::new((0, 0), (5, 5)), 'a');
+        let b = index.insert(Rectangle::new((-20, -20), (100, 100)), 'b');
+        let c = index.insert(Rectangle::new((50, 50), (5, 5)), 'c');
+        assert_eq!(keys(index.query_point((1, 1))), vec![a, b]);
+        assert_eq!(keys(index.query_point((52, 52))), vec![b, c]);
+        assert_eq!(keys(index.query_point((90, 90))), vec![]);
+        assert_eq!(
+            keys(index.query_area(&Rectangle::new((4, 4), (50, 50)))),
+            vec![a, b, c]
+        );
+        assert_eq!(
+            keys(index.query_area(&Rectangle::new((85, 0), (5, 5)))),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn update_and_remove() {
+        let mut index = SpatialHash::new(10.0);
+        let a = index.insert(Rectangle::new((0, 0), (5, 5)), 1);
+        let b = index.insert(Rectangle::new((0, 0), (5, 5)), 2);
+        assert!(index.update(a, Rectangle::new((100, 100), (5, 5))));
+        assert_eq!(keys(index.query_point((1, 1))), vec![b]);
+        assert_eq!(keys(index.query_point((101, 101))), vec![a]);
+        assert_eq!(index.remove(a), Some(1));
+        assert_eq!(index.remove(a), None);
+        assert!(!index.update(a, Rectangle::new((0, 0), (5, 5))));
+        assert!(index.query_point((101, 101)).is_empty());
+        assert_eq!(index.len(), 1);
+        let c = index.insert(Rectangle::new((-5, -5), (1, 1)), 3);
+        assert_eq!(c, a);
+        assert_eq!(index.get(c), Some(&3));
+    }
+
+    #[test]
+    fn raycasts() {
+        let mut index = SpatialHash::new(10.0);
+        let near = index.insert(Rectangle::new((20, -5), (5, 10)), "near");
+        let far = index.insert(Rectangle::new((200, -5), (5, 10)), "far");
+        index.insert(Rectangle::new((50, 50), (5, 10)), "off");
+        let hits: Vec<_> = index
+            .raycast((0, 0), (1, 0), 1000.0)
+            .into_iter()
+            .map(|h| (h.key, h.distance))
+            .collect();
+        assert_eq!(hits, vec![(near, 20.0), (far, 200.0)]);
+        assert!(index.raycast((0, 0), (1, 0), 10.0).is_empty());
+        assert!(index.raycast((0, 0), (-1, 0), f32::INFINITY).is_empty());
+        let first = index
+            .raycast_first((300, 0), (-1, 0), f32::INFINITY)
+            .unwrap();
+        assert_eq!(first.key, far);
+        assert_eq!(first.distance, 95.0);
+        let diagonal = index.raycast_first((0, 0), (1, 1), 100.0).unwrap();
+        assert_eq!(*diagonal.payload, "off");
+    }
+}
@@ -1,8 +1,16 @@
 /// Parent module for stuff related to graphics manipulation, such as textures and tessellation.
 /// (exclusive display + GPU)
+mod nine_slice;
+mod paint;
+mod particles;
+mod scene_graph;
 mod tessellation;
 mod texture;
 
+pub use nine_slice::*;
+pub use paint::*;
+pub use particles::*;
+pub use scene_graph::*;
 pub use tessellation::*;
 pub use texture::TextureConfig;
 pub use texture::*;
@@ -0,0 +1,221 @@
+use crate::graphics::{AbstractMesh, AbstractTriangle, Image, Tessellate};
+use crate::quicksilver_compat::Background;
+use crate::{Rectangle, Transform, Vector};
+
+/// Distances from the edges of an image, in texels, that mark its borders
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Insets {
+    pub top: f32,
+    pub right: f32,
+    pub bottom: f32,
+    pub left: f32,
+}
+
+impl Insets {
+    /// Insets in CSS order, like `Margin::new`
+    pub fn new(top: f32, right: f32, bottom: f32, left: f32) -> Self {
+        Insets {
+            top,
+            right,
+            bottom,
+            left,
+        }
+    }
+    /// The same inset on all four sides
+    pub fn uniform(inset: f32) -> Self {
+        Self::new(inset, inset, inset, inset)
+    }
+}
+
+/// An image split into a 3x3 grid by its border insets, for scalable frames and panels.
+///
+/// When drawn into a rectangle, the corners keep their size, the edges are stretched along one axis and the center along both.
+/// Borders shrink proportionally if the rectangle is too small to fit them.
+///
+/// The background passed in when drawing tints the image with its color, its image is ignored.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NineSlice {
+    image: Image,
+    insets: Insets,
+    scale: f32,
+}
+
+/// A `NineSlice` stretched over a specific area, ready to be tessellated
+#[derive(Clone, Copy, Debug)]
+pub struct NineSliceArea<'a> {
+    pub slice: &'a NineSlice,
+    pub area: Rectangle,
+}
+
+impl NineSlice {
+    pub fn new(image: Image, insets: Insets) -> Self {
+        NineSlice {
+            image,
+            insets,
+            scale: 1.0,
+        }
+    }
+    /// Draw the borders larger or smaller than their size in the image
+    pub fn with_scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
+    }
+    pub fn image(&self) -> &Image {
+        &self.image
+    }
+    pub fn insets(&self) -> Insets {
+        self.insets
+    }
+    /// Stretch the image over the given area
+    pub fn stretched(&self, area: Rectangle) -> NineSliceArea<'_> {
+        NineSliceArea { slice: self, area }
+    }
+}
+
+impl<'a> Tessellate for NineSliceArea<'a> {
+    fn tessellate<'b>(&self, mesh: &mut AbstractMesh, bkg: Background<'b>) {
+        let image = &self.slice.image;
+        let bkg = Background::Blended(image, bkg.color());
+        let image_trans = image.texture_transform();
+        let slices = slices(
+            self.slice.insets,
+            self.slice.scale,
+            image.natural_size(),
+            &self.area,
+        );
+        for (dest, uv) in slices.iter() {
+            if dest.width() <= 0.0 || dest.height() <= 0.0 {
+                continue;
+            }
+            let trans = Transform::translate(dest.pos) * Transform::scale(dest.size);
+            let tex_trans = image_trans * Transform::translate(uv.pos) * Transform::scale(uv.size);
+            let offset = mesh.add_positioned_vertices(
+                [Vector::ZERO, Vector::X, Vector::ONE, Vector::Y]
+                    .iter()
+                    .cloned(),
+                trans,
+                Some(tex_trans),
+                bkg,
+            );
+            mesh.triangles
+                .push(AbstractTriangle::new(offset, [0, 1, 2], bkg));
+            mesh.triangles
+                .push(AbstractTriangle::new(offset, [2, 3, 0], bkg));
+        }
+    }
+}
+
+/// Destination rectangle and normalized source region of each of the nine parts, row by row
+fn slices(
+    insets: Insets,
+    scale: f32,
+    image_size: Vector,
+    area: &Rectangle,
+) -> [(Rectangle, Rectangle); 9] {
+    let xs = split_axis(
+        insets.left,
+        insets.right,
+        scale,
+        image_size.x,
+        area.x(),
+        area.width(),
+    );
+    let ys = split_axis(
+        insets.top,
+        insets.bottom,
+        scale,
+        image_size.y,
+        area.y(),
+        area.height(),
+    );
+    let mut out = [(Rectangle::default(), Rectangle::default()); 9];
+    for row in 0..3 {
+        for col in 0..3 {
+            let ((x0, x1), (u0, u1)) = xs[col];
+            let ((y0, y1), (v0, v1)) = ys[row];
+            out[row * 3 + col] = (
+                Rectangle::new((x0, y0), (x1 - x0, y1 - y0)),
+                Rectangle::new((u0, v0), (u1 - u0, v1 - v0)),
+            );
+        }
+    }
+    out
+}
+
+/// Start and end of the three parts along one axis, in target coordinates and normalized texture coordinates
+#[allow(clippy::type_complexity)]
+fn split_axis(
+    before: f32,
+    after: f32,
+    scale: f32,
+    texels: f32,
+    start: f32,
+    length: f32,
+) -> [((f32, f32), (f32, f32)); 3] {
+    let mut near = before * scale;
+    let mut far = after * scale;
+    if near + far > length && near + far > 0.0 {
+        let shrink = length / (near + far);
+        near *= shrink;
+        far *= shrink;
+    }
+    let (u1, u2) = if texels > 0.0 {
+        (before / texels, 1.0 - after / texels)
+    } else {
+        (0.0, 1.0)
+    };
+    let end = start + length;
+    [
+        ((start, start + near), (0.0, u1)),
+        ((start + near, end - far), (u1, u2)),
+        ((end - far, end), (u2, 1.0)),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slice_layout() {
+        let insets = Insets::new(2.0, 4.0, 6.0, 4.0);
+        let parts = slices(
+            insets,
+            1.0,
+            Vector::new(16, 16),
+            &Rectangle::new((10, 10), (100, 50)),
+        );
+        let (top_left, top_left_uv) = parts[0];
+        assert_eq!(top_left, Rectangle::new((10, 10), (4, 2)));
+        assert_eq!(top_left_uv, Rectangle::new((0, 0), (0.25, 0.125)));
+        let (center, center_uv) = parts[4];
+        assert_eq!(center, Rectangle::new((14, 12), (92, 42)));
+        assert_eq!(center_uv, Rectangle::new((0.25, 0.125), (0.5, 0.5)));
+        let (bottom_right, bottom_right_uv) = parts[8];
+        assert_eq!(bottom_right, Rectangle::new((106, 54), (4, 6)));
+        assert_eq!(
+            bottom_right_uv,
+            Rectangle::new((0.75, 0.625), (0.25, 0.375))
+        );
+        let total: f32 = parts
+            .iter()
+            .map(|(dest, _)| dest.width() * dest.height())
+            .sum();
+        assert!((total - 5000.0).abs() < 1e-2);
+    }
+
+    #[test]
+    fn small_area_and_scale() {
+        let insets = Insets::uniform(4.0);
+        // Borders are scaled to 8 on each side but only 10 are available
+        let parts = slices(
+            insets,
+            2.0,
+            Vector::new(16, 16),
+            &Rectangle::new((0, 0), (10, 40)),
+        );
+        assert_eq!(parts[0].0.size(), Vector::new(5, 8));
+        assert_eq!(parts[4].0.size(), Vector::new(0, 24));
+        assert_eq!(parts[5].0, Rectangle::new((5, 8), (5, 24)));
+    }
+}
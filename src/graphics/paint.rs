@@ -0,0 +1,488 @@
+//! Backgrounds that vary over the area of a shape, like gradients and repeated images.
+//!
+//! Shapes are tessellated as usual first, the resulting triangles are then refined and painted with
+//! colors or texture coordinates computed per vertex. All shapes of paddle do this as part of `Tessellate`,
+//! so meshes drawn with `draw_mesh` keep their gradients and patterns.
+//! Meshes built by other means can be painted with `AbstractMesh::apply_background`.
+
+use crate::graphics::{AbstractMesh, AbstractTriangle, AbstractVertex, Image};
+use crate::quicksilver_compat::{Background, Color};
+use crate::Vector;
+
+/// Number of mesh edges a gradient is sampled with, along its full length
+const GRADIENT_RESOLUTION: f32 = 16.0;
+/// Upper bound for how many pieces a single triangle edge is split into for gradients
+const MAX_EDGE_SPLITS: usize = 32;
+
+/// A smooth transition between colors, defined in the same coordinates as the painted shape.
+///
+/// Positions before the first and after the last color stop use the color of that stop.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Gradient {
+    shape: GradientShape,
+    stops: Vec<ColorStop>,
+}
+
+/// Geometric layout of a `Gradient`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GradientShape {
+    /// Colors change along the line from `start` (offset 0) to `end` (offset 1)
+    Linear { start: Vector, end: Vector },
+    /// Colors change from the `center` (offset 0) to the circle with the given `radius` (offset 1)
+    Radial { center: Vector, radius: f32 },
+}
+
+/// A color at a specific offset of a `Gradient`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ColorStop {
+    /// Between 0 and 1
+    pub offset: f32,
+    pub color: Color,
+}
+
+impl Gradient {
+    /// Linear transition from `from` at `start` to `to` at `end`
+    pub fn linear(
+        start: impl Into<Vector>,
+        end: impl Into<Vector>,
+        from: Color,
+        to: Color,
+    ) -> Self {
+        Self::new(
+            GradientShape::Linear {
+                start: start.into(),
+                end: end.into(),
+            },
+            from,
+            to,
+        )
+    }
+    /// Circular transition from `inner` at the center to `outer` at the given radius
+    pub fn radial(center: impl Into<Vector>, radius: f32, inner: Color, outer: Color) -> Self {
+        Self::new(
+            GradientShape::Radial {
+                center: center.into(),
+                radius,
+            },
+            inner,
+            outer,
+        )
+    }
+    fn new(shape: GradientShape, first: Color, last: Color) -> Self {
+        Gradient {
+            shape,
+            stops: vec![
+                ColorStop {
+                    offset: 0.0,
+                    color: first,
+                },
+                ColorStop {
+                    offset: 1.0,
+                    color: last,
+                },
+            ],
+        }
+    }
+    /// Add another color stop between the existing ones.
+    ///
+    /// The offset is clamped to the range from 0 to 1.
+    pub fn with_stop(mut self, offset: f32, color: Color) -> Self {
+        let offset = offset.clamp(0.0, 1.0);
+        let index = self.stops.partition_point(|stop| stop.offset <= offset);
+        self.stops.insert(index, ColorStop { offset, color });
+        self
+    }
+    pub fn shape(&self) -> GradientShape {
+        self.shape
+    }
+    /// All color stops, ordered by their offset
+    pub fn stops(&self) -> &[ColorStop] {
+        &self.stops
+    }
+    /// The color of the gradient at the given position
+    pub fn color_at(&self, pos: impl Into<Vector>) -> Color {
+        color_at_offset(&self.stops, self.offset_at(pos.into()))
+    }
+    fn offset_at(&self, pos: Vector) -> f32 {
+        match self.shape {
+            GradientShape::Linear { start, end } => {
+                let axis = end - start;
+                if axis.len2() == 0.0 {
+                    return 0.0;
+                }
+                (pos - start).dot(axis) / axis.len2()
+            }
+            GradientShape::Radial { center, radius } => {
+                if radius <= 0.0 {
+                    return 1.0;
+                }
+                pos.distance(center) / radius
+            }
+        }
+    }
+    /// Longest mesh edge that still samples the gradient with enough detail
+    fn max_edge_length(&self) -> f32 {
+        let extent = match self.shape {
+            GradientShape::Linear { start, end } => start.distance(end),
+            GradientShape::Radial { radius, .. } => radius,
+        };
+        extent / GRADIENT_RESOLUTION
+    }
+}
+
+/// Interpolate between color stops ordered by offset, of which there must be at least one
+pub(crate) fn color_at_offset(stops: &[ColorStop], offset: f32) -> Color {
+    let next = stops.partition_point(|stop| stop.offset <= offset);
+    if next == 0 {
+        return stops[0].color;
+    }
+    if next == stops.len() {
+        return stops[next - 1].color;
+    }
+    let (a, b) = (stops[next - 1], stops[next]);
+    let t = (offset - a.offset) / (b.offset - a.offset);
+    lerp(a.color, b.color, t)
+}
+
+fn lerp(a: Color, b: Color, t: f32) -> Color {
+    Color {
+        r: a.r + (b.r - a.r) * t,
+        g: a.g + (b.g - a.g) * t,
+        b: a.b + (b.b - a.b) * t,
+        a: a.a + (b.a - a.a) * t,
+    }
+}
+
+/// An image repeated in a grid to fill a shape
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pattern {
+    pub image: Image,
+    /// Size of a single repetition of the image
+    pub tile_size: Vector,
+    /// Position of the top left corner of one of the tiles
+    pub offset: Vector,
+}
+
+impl Pattern {
+    /// Repeat the image in its natural size, starting at the origin
+    pub fn new(image: Image) -> Self {
+        let tile_size = image.natural_size();
+        Pattern {
+            image,
+            tile_size,
+            offset: Vector::ZERO,
+        }
+    }
+    pub fn with_tile_size(mut self, tile_size: impl Into<Vector>) -> Self {
+        self.tile_size = tile_size.into();
+        self
+    }
+    pub fn with_offset(mut self, offset: impl Into<Vector>) -> Self {
+        self.offset = offset.into();
+        self
+    }
+}
+
+impl AbstractMesh {
+    /// Paint all triangles in the mesh with a background that depends on the position, like a gradient or a pattern.
+    ///
+    /// Triangles are split up as necessary, which increases the size of the mesh.
+    /// Shapes implementing `Tessellate` already do this for their own triangles, this is for meshes built by other means.
+    /// Other backgrounds are fully applied when adding the vertices, for those this does nothing.
+    pub fn apply_background(&mut self, bkg: Background) {
+        self.apply_background_from(0, 0, bkg);
+    }
+    /// Like `apply_background` but only for triangles added after the mesh had the given number of vertices and triangles.
+    ///
+    /// Those triangles must only use vertices added after that point, too.
+    /// This is how implementations of `Tessellate` paint their own triangles without touching the rest of the mesh.
+    pub fn apply_background_from(
+        &mut self,
+        first_vertex: usize,
+        first_triangle: usize,
+        bkg: Background,
+    ) {
+        let first = (first_vertex, first_triangle);
+        match bkg {
+            Background::Gradient(gradient) => self.apply_gradient(first, gradient),
+            Background::Pattern(pattern) => self.apply_pattern(first, pattern),
+            Background::Col(_)
+            | Background::Img(_)
+            | Background::ImgView(_, _)
+            | Background::Blended(_, _) => {}
+        }
+    }
+
+    fn apply_gradient(&mut self, first: (usize, usize), gradient: &Gradient) {
+        let max_edge = gradient.max_edge_length();
+        self.rebuild(first, |corners, _color, vertices, triangles| {
+            for piece in subdivide(corners, max_edge) {
+                let first = vertices.len() as u32;
+                vertices.extend(piece.iter().map(|&pos| AbstractVertex {
+                    pos,
+                    tex_pos: None,
+                    col: gradient.color_at(pos),
+                }));
+                triangles.push([first, first + 1, first + 2]);
+            }
+        });
+    }
+
+    fn apply_pattern(&mut self, first: (usize, usize), pattern: &Pattern) {
+        let tex_trans = pattern.image.texture_transform();
+        let scale = pattern.tile_size.recip();
+        self.rebuild(first, |corners, color, vertices, triangles| {
+            for (tile, piece) in split_into_tiles(corners, pattern.tile_size, pattern.offset) {
+                let first = vertices.len() as u32;
+                vertices.extend(piece.iter().map(|&pos| AbstractVertex {
+                    pos,
+                    tex_pos: Some(tex_trans * (pos - tile).times(scale)),
+                    col: color,
+                }));
+                triangles
+                    .extend((1..piece.len() as u32 - 1).map(|i| [first, first + i, first + i + 1]));
+            }
+        });
+    }
+
+    /// Replace every triangle from `first_triangle` on with the triangles produced by `split`, keeping the image of the original
+    fn rebuild(
+        &mut self,
+        (first_vertex, first_triangle): (usize, usize),
+        mut split: impl FnMut([Vector; 3], Color, &mut Vec<AbstractVertex>, &mut Vec<[u32; 3]>),
+    ) {
+        let old_vertices = self.vertices.split_off(first_vertex);
+        let old_triangles = self.triangles.split_off(first_triangle);
+        let mut indices = Vec::new();
+        for triangle in old_triangles {
+            let [a, b, c] = triangle
+                .indices
+                .map(|i| old_vertices[i as usize - first_vertex]);
+            split(
+                [a.pos, b.pos, c.pos],
+                a.col,
+                &mut self.vertices,
+                &mut indices,
+            );
+            self.triangles
+                .extend(indices.drain(..).map(|indices| AbstractTriangle {
+                    indices,
+                    image: triangle.image.clone(),
+                }));
+        }
+    }
+}
+
+/// Split a triangle into a regular grid of smaller triangles, with edges no longer than `max_edge`
+fn subdivide([a, b, c]: [Vector; 3], max_edge: f32) -> Vec<[Vector; 3]> {
+    let longest = a.distance(b).max(b.distance(c)).max(c.distance(a));
+    let n = if max_edge > 0.0 {
+        ((longest / max_edge).ceil() as usize).clamp(1, MAX_EDGE_SPLITS)
+    } else {
+        1
+    };
+    // Point in row i (counted from a towards c) and column j (counted towards b)
+    let point =
+        |i: usize, j: usize| a + (b - a) * (j as f32 / n as f32) + (c - a) * (i as f32 / n as f32);
+    let mut pieces = Vec::with_capacity(n * n);
+    for i in 0..n {
+        for j in 0..n - i {
+            pieces.push([point(i, j), point(i, j + 1), point(i + 1, j)]);
+            if j + 1 < n - i {
+                pieces.push([point(i, j + 1), point(i + 1, j + 1), point(i + 1, j)]);
+            }
+        }
+    }
+    pieces
+}
+
+/// Cut a triangle along the tile grid.
+///
+/// Returns convex pieces, each together with the top left corner of the tile it lies in.
+fn split_into_tiles(
+    corners: [Vector; 3],
+    tile_size: Vector,
+    offset: Vector,
+) -> Vec<(Vector, Vec<Vector>)> {
+    if tile_size.x <= 0.0 || tile_size.y <= 0.0 {
+        return Vec::new();
+    }
+    let min = corners[0].min(corners[1]).min(corners[2]);
+    let max = corners[0].max(corners[1]).max(corners[2]);
+    let cell = |v: f32, origin: f32, size: f32| ((v - origin) / size).floor() as i64;
+    let (x0, x1) = (
+        cell(min.x, offset.x, tile_size.x),
+        cell(max.x, offset.x, tile_size.x),
+    );
+    let (y0, y1) = (
+        cell(min.y, offset.y, tile_size.y),
+        cell(max.y, offset.y, tile_size.y),
+    );
+    let mut pieces = Vec::new();
+    for y in y0..=y1 {
+        for x in x0..=x1 {
+            let tile = offset + tile_size.times((x as f32, y as f32));
+            let piece = clip_to_rectangle(&corners, tile, tile + tile_size);
+            // Tiles only touched at a corner or along an edge leave no area to fill
+            if !is_degenerate(&piece) {
+                pieces.push((tile, piece));
+            }
+        }
+    }
+    pieces
+}
+
+fn is_degenerate(polygon: &[Vector]) -> bool {
+    polygon.len() < 3
+        || (1..polygon.len() - 1)
+            .map(|i| (polygon[i] - polygon[0]).cross(polygon[i + 1] - polygon[0]))
+            .sum::<f32>()
+            == 0.0
+}
+
+/// Sutherland–Hodgman clipping of a convex polygon against an axis aligned rectangle
+fn clip_to_rectangle(polygon: &[Vector], min: Vector, max: Vector) -> Vec<Vector> {
+    let mut result = polygon.to_vec();
+    // Each boundary as (horizontal axis, position, direction towards the inside)
+    let boundaries = [
+        (true, min.x, 1.0),
+        (true, max.x, -1.0),
+        (false, min.y, 1.0),
+        (false, max.y, -1.0),
+    ];
+    for &(horizontal, bound, direction) in boundaries.iter() {
+        let coordinate = |p: Vector| if horizontal { p.x } else { p.y };
+        let inside = |p: Vector| (coordinate(p) - bound) * direction;
+        let input = std::mem::take(&mut result);
+        for (i, &current) in input.iter().enumerate() {
+            let previous = input[(i + input.len() - 1) % input.len()];
+            let (d_cur, d_prev) = (inside(current), inside(previous));
+            if (d_cur >= 0.0) != (d_prev >= 0.0) {
+                let t = d_prev / (d_prev - d_cur);
+                let mut crossing = previous + (current - previous) * t;
+                // Place it exactly on the boundary, to avoid gaps between neighbouring tiles
+                if horizontal {
+                    crossing.x = bound;
+                } else {
+                    crossing.y = bound;
+                }
+                result.push(crossing);
+            }
+            if d_cur >= 0.0 {
+                result.push(current);
+            }
+        }
+        if result.is_empty() {
+            break;
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn area([a, b, c]: [Vector; 3]) -> f32 {
+        (b - a).cross(c - a).abs() / 2.0
+    }
+
+    fn polygon_area(polygon: &[Vector]) -> f32 {
+        (0..polygon.len())
+            .map(|i| polygon[i].cross(polygon[(i + 1) % polygon.len()]))
+            .sum::<f32>()
+            .abs()
+            / 2.0
+    }
+
+    #[test]
+    fn gradient_colors() {
+        let linear = Gradient::linear((0, 0), (10, 0), Color::BLACK, Color::WHITE);
+        assert_eq!(linear.color_at((-5, 3)), Color::BLACK);
+        assert_eq!(linear.color_at((5, 3)), Color::new(0.5, 0.5, 0.5));
+        assert_eq!(linear.color_at((20, 0)), Color::WHITE);
+
+        let radial =
+            Gradient::radial((0, 0), 10.0, Color::RED, Color::BLUE).with_stop(0.5, Color::GREEN);
+        assert_eq!(radial.stops().len(), 3);
+        assert_eq!(radial.stops()[1].color, Color::GREEN);
+        assert_eq!(radial.color_at((0, 5)), Color::GREEN);
+        assert_eq!(
+            radial.color_at((0, 2.5)),
+            lerp(Color::RED, Color::GREEN, 0.5)
+        );
+        assert_eq!(radial.color_at((30, 0)), Color::BLUE);
+    }
+
+    #[test]
+    fn subdivision() {
+        let triangle = [Vector::new(0, 0), Vector::new(10, 0), Vector::new(0, 10)];
+        assert_eq!(subdivide(triangle, 20.0).len(), 1);
+        let pieces = subdivide(triangle, 5.0);
+        // The longest edge is about 14.1 long, split in three
+        assert_eq!(pieces.len(), 9);
+        let total: f32 = pieces.iter().map(|p| area(*p)).sum();
+        assert!((total - area(triangle)).abs() < 1e-3);
+        assert!(pieces.iter().all(|[a, b, c]| a
+            .distance(*b)
+            .max(b.distance(*c))
+            .max(c.distance(*a))
+            <= 5.0));
+    }
+
+    #[test]
+    fn tiles() {
+        let triangle = [Vector::new(0, 0), Vector::new(20, 0), Vector::new(0, 20)];
+        let pieces = split_into_tiles(triangle, Vector::new(10, 10), Vector::ZERO);
+        // The tile in the bottom right is only touched in a corner
+        assert_eq!(pieces.len(), 3);
+        let total: f32 = pieces.iter().map(|(_, p)| polygon_area(p)).sum();
+        assert!((total - area(triangle)).abs() < 1e-3);
+        for (tile, piece) in &pieces {
+            for v in piece {
+                let local = *v - *tile;
+                assert!(local.x >= 0.0 && local.x <= 10.0);
+                assert!(local.y >= 0.0 && local.y <= 10.0);
+            }
+        }
+
+        let shifted = split_into_tiles(triangle, Vector::new(10, 10), Vector::new(5, 5));
+        let total: f32 = shifted.iter().map(|(_, p)| polygon_area(p)).sum();
+        assert!((total - area(triangle)).abs() < 1e-3);
+        assert!(shifted.iter().any(|(tile, _)| *tile == Vector::new(-5, -5)));
+    }
+
+    #[test]
+    fn gradient_mesh() {
+        let mut mesh = AbstractMesh::new();
+        let gradient = Gradient::linear((0, 0), (16, 0), Color::BLACK, Color::WHITE);
+        crate::Tessellate::tessellate(
+            &crate::Rectangle::new((0, 0), (16, 16)),
+            &mut mesh,
+            Background::Gradient(&gradient),
+        );
+        assert!(mesh.triangles.len() > 2);
+        assert!(mesh.triangles.iter().all(|t| t.image.is_none()));
+        for v in &mesh.vertices {
+            assert_eq!(v.col, gradient.color_at(v.pos));
+            assert!(v.tex_pos.is_none());
+        }
+    }
+
+    #[test]
+    fn paint_only_new_triangles() {
+        let mut mesh = AbstractMesh::new();
+        let square = crate::Rectangle::new((0, 0), (16, 16));
+        crate::Tessellate::tessellate(&square, &mut mesh, Background::Col(Color::RED));
+        let gradient = Gradient::linear((0, 0), (16, 0), Color::BLACK, Color::WHITE);
+        crate::Tessellate::tessellate(&square, &mut mesh, Background::Gradient(&gradient));
+        assert!(mesh.triangles.len() > 4);
+        for t in &mesh.triangles[..2] {
+            assert!(t.indices.iter().all(|&i| i < 4));
+        }
+        assert!(mesh.vertices[..4].iter().all(|v| v.col == Color::RED));
+        assert!(mesh.vertices[4..]
+            .iter()
+            .all(|v| v.col == gradient.color_at(v.pos)));
+    }
+}
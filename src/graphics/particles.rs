@@ -0,0 +1,366 @@
+use crate::graphics::{
+    color_at_offset, AbstractMesh, AbstractTriangle, AbstractVertex, ColorStop, Image,
+};
+use crate::quicksilver_compat::Color;
+use crate::{DisplayArea, Vector};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// Four vertices per particle must fit into the 16 bit indices used on the GPU
+const MAX_PARTICLES: usize = (u16::MAX as usize + 1) / 4;
+
+/// A single live particle of a `ParticleEmitter`
+#[derive(Clone, Copy, Debug)]
+pub struct Particle {
+    pub pos: Vector,
+    pub velocity: Vector,
+    /// Seconds since the particle was spawned
+    pub age: f32,
+    /// Seconds until the particle disappears, counted from its spawn
+    pub lifetime: f32,
+}
+
+impl Particle {
+    /// How far the particle is through its life, from 0 to 1
+    pub fn progress(&self) -> f32 {
+        (self.age / self.lifetime).min(1.0)
+    }
+}
+
+/// Spawns, moves and draws many small quads, for effects like smoke, sparks or rain.
+///
+/// All live particles are written into one mesh, which is drawn at once.
+/// Call `update` once per frame with the elapsed time, then `draw`.
+///
+/// Random values are taken from a seeded generator, so the same emitter behaves the same way every time.
+///
+/// ```
+/// # use paddle::*;
+/// # use paddle::quicksilver_compat::Color;
+/// let mut sparks = ParticleEmitter::new((100, 100))
+///     .with_rate(50.0)
+///     .with_lifetime(0.5, 1.0)
+///     .with_direction(-90.0, 30.0)
+///     .with_speed(50.0, 80.0)
+///     .with_acceleration((0, 100))
+///     .with_colors(Color::YELLOW, Color::RED.with_alpha(0.0))
+///     .with_size(4.0, 1.0);
+/// sparks.update(0.1);
+/// assert_eq!(sparks.len(), 5);
+/// assert_eq!(sparks.mesh().triangles.len(), 10);
+/// ```
+pub struct ParticleEmitter {
+    position: Vector,
+    emitting: bool,
+    /// Particles per second
+    rate: f32,
+    lifetime: (f32, f32),
+    /// Size of the area around the position where particles appear
+    spawn_area: Vector,
+    /// Direction and maximum deviation, in degrees
+    direction: (f32, f32),
+    speed: (f32, f32),
+    acceleration: Vector,
+    colors: Vec<ColorStop>,
+    size: (f32, f32),
+    image: Option<Image>,
+    max_particles: usize,
+    particles: Vec<Particle>,
+    /// Fraction of a particle not spawned yet in the last update
+    pending: f32,
+    rng: StdRng,
+    mesh: AbstractMesh,
+}
+
+impl ParticleEmitter {
+    /// An emitter of white particles at a position, moving in all directions
+    pub fn new(position: impl Into<Vector>) -> Self {
+        ParticleEmitter {
+            position: position.into(),
+            emitting: true,
+            rate: 10.0,
+            lifetime: (1.0, 1.0),
+            spawn_area: Vector::ZERO,
+            direction: (0.0, 180.0),
+            speed: (10.0, 10.0),
+            acceleration: Vector::ZERO,
+            colors: vec![ColorStop {
+                offset: 0.0,
+                color: Color::WHITE,
+            }],
+            size: (4.0, 4.0),
+            image: None,
+            max_particles: MAX_PARTICLES,
+            particles: Vec::new(),
+            pending: 0.0,
+            rng: StdRng::seed_from_u64(0),
+            mesh: AbstractMesh::new(),
+        }
+    }
+    /// Particles spawned per second, while emitting
+    pub fn with_rate(mut self, per_second: f32) -> Self {
+        self.rate = per_second;
+        self
+    }
+    /// Range of seconds particles live
+    pub fn with_lifetime(mut self, min: f32, max: f32) -> Self {
+        self.lifetime = (min, max);
+        self
+    }
+    /// Spawn particles anywhere inside a rectangle of this size centered on the emitter
+    pub fn with_spawn_area(mut self, size: impl Into<Vector>) -> Self {
+        self.spawn_area = size.into();
+        self
+    }
+    /// Initial direction of particles in degrees, with a random deviation of up to `spread` degrees to either side
+    pub fn with_direction(mut self, angle: f32, spread: f32) -> Self {
+        self.direction = (angle, spread);
+        self
+    }
+    /// Range of initial speeds, in pixels per second
+    pub fn with_speed(mut self, min: f32, max: f32) -> Self {
+        self.speed = (min, max);
+        self
+    }
+    /// Constant acceleration like gravity or wind, in pixels per second squared
+    pub fn with_acceleration(mut self, acceleration: impl Into<Vector>) -> Self {
+        self.acceleration = acceleration.into();
+        self
+    }
+    /// Fade from one color at spawn to another at the end of the life
+    pub fn with_colors(mut self, start: Color, end: Color) -> Self {
+        self.colors = vec![
+            ColorStop {
+                offset: 0.0,
+                color: start,
+            },
+            ColorStop {
+                offset: 1.0,
+                color: end,
+            },
+        ];
+        self
+    }
+    /// Add a color at a point of the life between 0 and 1
+    pub fn with_color_stop(mut self, offset: f32, color: Color) -> Self {
+        let offset = offset.clamp(0.0, 1.0);
+        let index = self.colors.partition_point(|stop| stop.offset <= offset);
+        self.colors.insert(index, ColorStop { offset, color });
+        self
+    }
+    /// Grow or shrink linearly from one size at spawn to another at the end of the life
+    pub fn with_size(mut self, start: f32, end: f32) -> Self {
+        self.size = (start, end);
+        self
+    }
+    /// Draw particles as images, tinted by their color
+    pub fn with_image(mut self, image: Image) -> Self {
+        self.image = Some(image);
+        self
+    }
+    /// Limit the number of live particles. Spawning stops while the limit is reached.
+    pub fn with_max_particles(mut self, max: usize) -> Self {
+        self.max_particles = max.min(MAX_PARTICLES);
+        self
+    }
+    /// Seed the random generator, to make emitters behave differently
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = StdRng::seed_from_u64(seed);
+        self
+    }
+
+    pub fn position(&self) -> Vector {
+        self.position
+    }
+    /// Move the emitter, live particles stay where they are
+    pub fn set_position(&mut self, position: impl Into<Vector>) {
+        self.position = position.into();
+    }
+    /// Start or stop spawning particles over time. Live particles keep moving.
+    pub fn set_emitting(&mut self, emitting: bool) {
+        self.emitting = emitting;
+        if !emitting {
+            self.pending = 0.0;
+        }
+    }
+    pub fn is_emitting(&self) -> bool {
+        self.emitting
+    }
+    /// Spawn a number of particles at once, for example for explosions
+    pub fn burst(&mut self, count: usize) {
+        for _ in 0..count {
+            self.spawn();
+        }
+        self.rebuild_mesh();
+    }
+    /// Number of live particles
+    pub fn len(&self) -> usize {
+        self.particles.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.particles.is_empty()
+    }
+    /// Not emitting anymore and all particles are gone, so the emitter can be dropped
+    pub fn is_finished(&self) -> bool {
+        !self.emitting && self.particles.is_empty()
+    }
+    pub fn particles(&self) -> &[Particle] {
+        &self.particles
+    }
+    /// All live particles, as of the last update
+    pub fn mesh(&self) -> &AbstractMesh {
+        &self.mesh
+    }
+
+    /// Advance the simulation by `dt` seconds
+    pub fn update(&mut self, dt: f32) {
+        let acceleration = self.acceleration;
+        self.particles.retain_mut(|p| {
+            p.age += dt;
+            p.velocity += acceleration * dt;
+            p.pos += p.velocity * dt;
+            p.age < p.lifetime
+        });
+        if self.emitting {
+            self.pending += self.rate * dt;
+            while self.pending >= 1.0 {
+                self.pending -= 1.0;
+                self.spawn();
+            }
+        }
+        self.rebuild_mesh();
+    }
+    /// Draw all particles with a single mesh
+    pub fn draw(&self, display: &mut DisplayArea) {
+        display.draw_mesh(&self.mesh);
+    }
+
+    fn spawn(&mut self) {
+        if self.particles.len() >= self.max_particles {
+            return;
+        }
+        let offset = Vector::new(
+            self.rng.gen_range(-0.5, 0.5) * self.spawn_area.x,
+            self.rng.gen_range(-0.5, 0.5) * self.spawn_area.y,
+        );
+        let angle =
+            self.direction.0 + random_in(&mut self.rng, -self.direction.1, self.direction.1);
+        let speed = random_in(&mut self.rng, self.speed.0, self.speed.1);
+        let lifetime = random_in(&mut self.rng, self.lifetime.0, self.lifetime.1);
+        self.particles.push(Particle {
+            pos: self.position + offset,
+            velocity: Vector::from_angle(angle) * speed,
+            age: 0.0,
+            lifetime,
+        });
+    }
+    fn rebuild_mesh(&mut self) {
+        self.mesh.clear();
+        let tex_trans = self.image.as_ref().map(Image::texture_transform);
+        for particle in &self.particles {
+            let t = particle.progress();
+            let size = self.size.0 + (self.size.1 - self.size.0) * t;
+            let col = color_at_offset(&self.colors, t);
+            let top_left = particle.pos - Vector::ONE * (size / 2.0);
+            let offset = self.mesh.vertices.len() as u32;
+            for corner in [Vector::ZERO, Vector::X, Vector::ONE, Vector::Y] {
+                self.mesh.vertices.push(AbstractVertex {
+                    pos: top_left + corner * size,
+                    tex_pos: tex_trans.map(|trans| trans * corner),
+                    col,
+                });
+            }
+            for indices in [[0, 1, 2], [2, 3, 0]] {
+                self.mesh.triangles.push(AbstractTriangle {
+                    indices: indices.map(|i| i + offset),
+                    image: self.image.clone(),
+                });
+            }
+        }
+    }
+}
+
+/// Uniformly distributed value, also for empty ranges
+fn random_in(rng: &mut StdRng, min: f32, max: f32) -> f32 {
+    if max > min {
+        rng.gen_range(min, max)
+    } else {
+        min
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spawn_and_expire() {
+        let mut emitter = ParticleEmitter::new((0, 0))
+            .with_rate(4.0)
+            .with_lifetime(1.0, 1.0);
+        emitter.update(0.5);
+        assert_eq!(emitter.len(), 2);
+        emitter.update(0.125);
+        assert_eq!(emitter.len(), 2);
+        // Fractions of particles add up over updates
+        emitter.update(0.125);
+        assert_eq!(emitter.len(), 3);
+        assert_eq!(emitter.mesh().vertices.len(), 12);
+        for _ in 0..4 {
+            emitter.update(0.25);
+        }
+        // One new particle per update, the first three have expired
+        assert_eq!(emitter.len(), 4);
+        emitter.set_emitting(false);
+        emitter.update(0.5);
+        assert!(!emitter.is_empty());
+        emitter.update(0.5);
+        assert!(emitter.is_finished());
+        assert!(emitter.mesh().vertices.is_empty());
+    }
+
+    #[test]
+    fn motion() {
+        let mut emitter = ParticleEmitter::new((10, 10))
+            .with_direction(0.0, 0.0)
+            .with_speed(10.0, 10.0)
+            .with_acceleration((0, 20))
+            .with_lifetime(5.0, 5.0);
+        emitter.set_emitting(false);
+        emitter.burst(3);
+        assert_eq!(emitter.len(), 3);
+        emitter.update(1.0);
+        emitter.update(1.0);
+        for p in emitter.particles() {
+            // Semi-implicit Euler: velocity is updated before the position
+            assert_eq!(p.pos, Vector::new(30, 70));
+            assert_eq!(p.velocity, Vector::new(10, 40));
+        }
+
+        let mut limited = ParticleEmitter::new((0, 0)).with_max_particles(2);
+        limited.burst(5);
+        assert_eq!(limited.len(), 2);
+    }
+
+    #[test]
+    fn appearance_over_life() {
+        let mut emitter = ParticleEmitter::new((0, 0))
+            .with_speed(0.0, 0.0)
+            .with_lifetime(2.0, 2.0)
+            .with_colors(Color::WHITE, Color::BLACK)
+            .with_color_stop(0.5, Color::RED)
+            .with_size(10.0, 2.0);
+        emitter.set_emitting(false);
+        emitter.burst(1);
+        let vertices = &emitter.mesh().vertices;
+        assert_eq!(vertices[0].pos, Vector::new(-5, -5));
+        assert_eq!(vertices[2].pos, Vector::new(5, 5));
+        assert_eq!(vertices[0].col, Color::WHITE);
+        assert!(vertices[0].tex_pos.is_none());
+        emitter.update(1.0);
+        let vertices = &emitter.mesh().vertices;
+        assert_eq!(vertices[0].col, Color::RED);
+        assert_eq!(vertices[2].pos - vertices[0].pos, Vector::new(6, 6));
+        assert_eq!(emitter.mesh().triangles.len(), 2);
+    }
+}
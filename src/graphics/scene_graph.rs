@@ -0,0 +1,477 @@
+use crate::graphics::{AbstractMesh, Tessellate};
+use crate::quicksilver_compat::geom::{Shape, Triangle};
+use crate::quicksilver_compat::Background;
+use crate::{DisplayArea, ErrorMessage, PaddleResult, PointerEvent, Transform, Vector};
+use std::cell::Cell;
+
+/// Handle to a node of a `SceneGraph`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId {
+    index: usize,
+    /// Distinguishes nodes that were stored in the same slot one after another
+    generation: u32,
+}
+
+/// Content and placement of a node, before it is added to a `SceneGraph`.
+pub struct SceneNode {
+    transform: Transform,
+    mesh: Option<AbstractMesh>,
+    visible: bool,
+    z: i16,
+}
+
+/// A tree of nodes, each placed relative to its parent.
+///
+/// World transforms, which place a node in frame coordinates, are computed when they are first needed
+/// and cached until the node or one of its ancestors moves.
+/// Hidden nodes hide their entire subtree, for drawing as well as for hit tests.
+///
+/// Slots of removed nodes are reused by later nodes, their old handles stay invalid.
+/// Node handles must not be used after the node has been removed, the graph panics on removed nodes.
+///
+/// ```
+/// # use paddle::*;
+/// # use paddle::quicksilver_compat::Color;
+/// let mut scene = SceneGraph::new();
+/// let ship = scene
+///     .add(scene.root(), SceneNode::new().with_transform(Transform::translate((100, 100))))
+///     .unwrap();
+/// let turret = scene
+///     .add(
+///         ship,
+///         SceneNode::new()
+///             .with_shape(&Rectangle::new((-5, -5), (10, 10)), Color::RED)
+///             .with_transform(Transform::translate((20, 0))),
+///     )
+///     .unwrap();
+/// scene.set_transform(ship, Transform::translate((100, 100)) * Transform::rotate(90));
+/// assert_eq!(scene.world_transform(turret) * Vector::ZERO, Vector::new(100, 120));
+/// let click = PointerEvent(PointerEventType::PrimaryClick, Vector::new(102, 118));
+/// assert_eq!(scene.hit_test(scene.root(), &click), Some(turret));
+/// ```
+pub struct SceneGraph {
+    slots: Vec<Slot>,
+    /// Indices of empty slots
+    free: Vec<usize>,
+}
+
+struct Slot {
+    generation: u32,
+    entry: Option<Entry>,
+}
+
+struct Entry {
+    node: SceneNode,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+    world: Cell<Transform>,
+    dirty: Cell<bool>,
+}
+
+impl SceneNode {
+    /// An empty, visible node at the origin of its parent
+    pub fn new() -> Self {
+        SceneNode {
+            transform: Transform::IDENTITY,
+            mesh: None,
+            visible: true,
+            z: 0,
+        }
+    }
+    /// Placement relative to the parent
+    pub fn with_transform(mut self, transform: Transform) -> Self {
+        self.transform = transform;
+        self
+    }
+    /// Draw a shape, tessellated once in node coordinates
+    pub fn with_shape<'a>(
+        mut self,
+        shape: &impl Tessellate,
+        bkg: impl Into<Background<'a>>,
+    ) -> Self {
+        self.mesh = Some(tessellated(shape, bkg.into()));
+        self
+    }
+    /// Draw a mesh given in node coordinates
+    pub fn with_mesh(mut self, mesh: AbstractMesh) -> Self {
+        self.mesh = Some(mesh);
+        self
+    }
+    pub fn with_visibility(mut self, visible: bool) -> Self {
+        self.visible = visible;
+        self
+    }
+    pub fn with_z(mut self, z: i16) -> Self {
+        self.z = z;
+        self
+    }
+    pub fn transform(&self) -> Transform {
+        self.transform
+    }
+    pub fn mesh(&self) -> Option<&AbstractMesh> {
+        self.mesh.as_ref()
+    }
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+    pub fn z(&self) -> i16 {
+        self.z
+    }
+    /// If the point in node coordinates lies on the drawn mesh
+    fn contains(&self, point: Vector) -> bool {
+        let mesh = match &self.mesh {
+            Some(mesh) => mesh,
+            None => return false,
+        };
+        mesh.triangles.iter().any(|t| {
+            let [a, b, c] = t.indices;
+            let pos = |i: u32| mesh.vertices[i as usize].pos;
+            Triangle::new(pos(a), pos(b), pos(c)).contains(point)
+        })
+    }
+}
+
+impl Default for SceneNode {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SceneGraph {
+    /// A graph with only an empty root node
+    pub fn new() -> Self {
+        SceneGraph {
+            slots: vec![Slot {
+                generation: 0,
+                entry: Some(Entry::new(SceneNode::new(), None)),
+            }],
+            free: Vec::new(),
+        }
+    }
+    /// The node all others descend from, it cannot be removed
+    pub fn root(&self) -> NodeId {
+        NodeId {
+            index: 0,
+            generation: 0,
+        }
+    }
+    /// Add a node as the last child of `parent`, drawn above its earlier siblings at the same z
+    pub fn add(&mut self, parent: NodeId, node: SceneNode) -> PaddleResult<NodeId> {
+        self.existing(parent)?;
+        let entry = Some(Entry::new(node, Some(parent)));
+        let id = match self.free.pop() {
+            Some(index) => {
+                let slot = &mut self.slots[index];
+                slot.entry = entry;
+                NodeId {
+                    index,
+                    generation: slot.generation,
+                }
+            }
+            None => {
+                self.slots.push(Slot {
+                    generation: 0,
+                    entry,
+                });
+                NodeId {
+                    index: self.slots.len() - 1,
+                    generation: 0,
+                }
+            }
+        };
+        self.entry_mut(parent).children.push(id);
+        Ok(id)
+    }
+    /// Remove a node with its entire subtree
+    pub fn remove(&mut self, id: NodeId) -> PaddleResult<SceneNode> {
+        self.existing(id)?;
+        let parent = match self.entry(id).parent {
+            Some(parent) => parent,
+            None => {
+                return Err(ErrorMessage::technical(
+                    "The root of a scene cannot be removed".to_owned(),
+                ))
+            }
+        };
+        self.entry_mut(parent).children.retain(|child| *child != id);
+        for descendant in self.subtree(id).into_iter().skip(1) {
+            self.vacate(descendant);
+        }
+        Ok(self.vacate(id).node)
+    }
+    /// Move a node with its subtree below another parent, keeping its local transform
+    pub fn set_parent(&mut self, id: NodeId, parent: NodeId) -> PaddleResult<()> {
+        self.existing(id)?;
+        self.existing(parent)?;
+        if self.ancestors(parent).any(|ancestor| ancestor == id) {
+            return Err(ErrorMessage::technical(
+                "A scene node cannot become a descendant of itself".to_owned(),
+            ));
+        }
+        let old_parent = self.entry(id).parent.expect("root is its own ancestor");
+        self.entry_mut(old_parent)
+            .children
+            .retain(|child| *child != id);
+        self.entry_mut(parent).children.push(id);
+        self.entry_mut(id).parent = Some(parent);
+        self.mark_dirty(id);
+        Ok(())
+    }
+    /// Whether the handle refers to a node that has not been removed
+    pub fn contains(&self, id: NodeId) -> bool {
+        matches!(
+            self.slots.get(id.index),
+            Some(Slot { generation, entry: Some(_) }) if *generation == id.generation
+        )
+    }
+    pub fn node(&self, id: NodeId) -> &SceneNode {
+        &self.entry(id).node
+    }
+    pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+        self.entry(id).parent
+    }
+    /// Children in drawing order
+    pub fn children(&self, id: NodeId) -> &[NodeId] {
+        &self.entry(id).children
+    }
+    /// Change the placement relative to the parent, which also moves all descendants
+    pub fn set_transform(&mut self, id: NodeId, transform: Transform) {
+        self.entry_mut(id).node.transform = transform;
+        self.mark_dirty(id);
+    }
+    pub fn set_visible(&mut self, id: NodeId, visible: bool) {
+        self.entry_mut(id).node.visible = visible;
+    }
+    pub fn set_z(&mut self, id: NodeId, z: i16) {
+        self.entry_mut(id).node.z = z;
+    }
+    pub fn set_shape<'a>(
+        &mut self,
+        id: NodeId,
+        shape: &impl Tessellate,
+        bkg: impl Into<Background<'a>>,
+    ) {
+        self.entry_mut(id).node.mesh = Some(tessellated(shape, bkg.into()));
+    }
+    /// Replace the drawn mesh, or draw nothing for this node
+    pub fn set_mesh(&mut self, id: NodeId, mesh: Option<AbstractMesh>) {
+        self.entry_mut(id).node.mesh = mesh;
+    }
+    /// Transformation from node coordinates to frame coordinates
+    pub fn world_transform(&self, id: NodeId) -> Transform {
+        let entry = self.entry(id);
+        if entry.dirty.get() {
+            let world = match entry.parent {
+                Some(parent) => self.world_transform(parent) * entry.node.transform,
+                None => entry.node.transform,
+            };
+            entry.world.set(world);
+            entry.dirty.set(false);
+        }
+        entry.world.get()
+    }
+    /// Draw all visible nodes of a subtree, parents before their children
+    pub fn draw(&self, subtree: NodeId, display: &mut DisplayArea) {
+        for id in self.visible_subtree(subtree) {
+            let node = &self.entry(id).node;
+            if let Some(mesh) = &node.mesh {
+                display.draw_mesh_ex(mesh, self.world_transform(id), node.z);
+            }
+        }
+    }
+    /// The topmost visible node of a subtree under the pointer, if any
+    pub fn hit_test(&self, subtree: NodeId, event: &PointerEvent) -> Option<NodeId> {
+        self.hit_test_point(subtree, event.pos())
+    }
+    /// The topmost visible node of a subtree whose mesh contains a point in frame coordinates.
+    ///
+    /// Nodes with a higher z are on top, for equal z the one drawn last.
+    pub fn hit_test_point(&self, subtree: NodeId, point: impl Into<Vector>) -> Option<NodeId> {
+        let point = point.into();
+        self.visible_subtree(subtree)
+            .into_iter()
+            .enumerate()
+            .filter(|(_, id)| {
+                let local = self.world_transform(*id).inverse() * point;
+                self.entry(*id).node.contains(local)
+            })
+            .max_by_key(|(order, id)| (self.entry(*id).node.z, *order))
+            .map(|(_, id)| id)
+    }
+
+    fn existing(&self, id: NodeId) -> PaddleResult<()> {
+        if self.contains(id) {
+            Ok(())
+        } else {
+            Err(ErrorMessage::technical(format!(
+                "Scene node {} does not exist",
+                id.index
+            )))
+        }
+    }
+    fn entry(&self, id: NodeId) -> &Entry {
+        let slot = &self.slots[id.index];
+        assert_eq!(
+            slot.generation, id.generation,
+            "Scene node has been removed"
+        );
+        slot.entry.as_ref().expect("Scene node has been removed")
+    }
+    fn entry_mut(&mut self, id: NodeId) -> &mut Entry {
+        let slot = &mut self.slots[id.index];
+        assert_eq!(
+            slot.generation, id.generation,
+            "Scene node has been removed"
+        );
+        slot.entry.as_mut().expect("Scene node has been removed")
+    }
+    /// Empty the slot of a node and make it available to the next added node
+    fn vacate(&mut self, id: NodeId) -> Entry {
+        let slot = &mut self.slots[id.index];
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(id.index);
+        slot.entry.take().expect("Scene node has been removed")
+    }
+    fn ancestors(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        std::iter::successors(Some(id), move |id| self.entry(*id).parent)
+    }
+    /// All nodes of a subtree in drawing order, starting with its root
+    fn subtree(&self, id: NodeId) -> Vec<NodeId> {
+        self.collect(id, |_| true)
+    }
+    /// Like `subtree`, without hidden nodes and their descendants
+    fn visible_subtree(&self, id: NodeId) -> Vec<NodeId> {
+        self.collect(id, |entry| entry.node.visible)
+    }
+    fn collect(&self, id: NodeId, include: impl Fn(&Entry) -> bool) -> Vec<NodeId> {
+        let mut out = Vec::new();
+        let mut stack = vec![id];
+        while let Some(id) = stack.pop() {
+            let entry = self.entry(id);
+            if include(entry) {
+                out.push(id);
+                stack.extend(entry.children.iter().rev());
+            }
+        }
+        out
+    }
+    /// Invalidate cached world transforms of a node and its descendants
+    fn mark_dirty(&self, id: NodeId) {
+        for id in self.subtree(id) {
+            self.entry(id).dirty.set(true);
+        }
+    }
+}
+
+impl Default for SceneGraph {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Entry {
+    fn new(node: SceneNode, parent: Option<NodeId>) -> Self {
+        Entry {
+            node,
+            parent,
+            children: Vec::new(),
+            world: Cell::new(Transform::IDENTITY),
+            dirty: Cell::new(true),
+        }
+    }
+}
+
+fn tessellated(shape: &impl Tessellate, bkg: Background) -> AbstractMesh {
+    let mut mesh = AbstractMesh::new();
+    shape.tessellate(&mut mesh, bkg);
+    mesh
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphics::Gradient;
+    use crate::quicksilver_compat::Color;
+    use crate::Rectangle;
+
+    fn square(pos: (i32, i32), z: i16) -> SceneNode {
+        SceneNode::new()
+            .with_shape(&Rectangle::new((0, 0), (10, 10)), Color::BLUE)
+            .with_transform(Transform::translate(pos))
+            .with_z(z)
+    }
+
+    #[test]
+    fn lazy_world_transforms() {
+        let mut scene = SceneGraph::new();
+        let a = scene.add(scene.root(), square((10, 0), 0)).unwrap();
+        let b = scene.add(a, square((0, 10), 0)).unwrap();
+        let c = scene.add(b, square((5, 5), 0)).unwrap();
+        assert_eq!(scene.world_transform(c) * Vector::ZERO, Vector::new(15, 15));
+        assert!(!scene.entry(b).dirty.get());
+
+        scene.set_transform(a, Transform::translate((20, 0)));
+        assert!(scene.entry(c).dirty.get());
+        assert_eq!(scene.world_transform(c) * Vector::ZERO, Vector::new(25, 15));
+
+        scene.set_parent(c, a).unwrap();
+        assert_eq!(scene.world_transform(c) * Vector::ZERO, Vector::new(25, 5));
+        assert_eq!(scene.children(a), &[b, c]);
+        assert!(scene.set_parent(a, c).is_err());
+
+        scene.remove(b).unwrap();
+        assert!(!scene.contains(b));
+        assert_eq!(scene.children(a), &[c]);
+        assert!(scene.remove(scene.root()).is_err());
+        assert!(scene.add(b, SceneNode::new()).is_err());
+    }
+
+    #[test]
+    fn removed_slots_are_reused() {
+        let mut scene = SceneGraph::new();
+        let a = scene.add(scene.root(), SceneNode::new()).unwrap();
+        let b = scene.add(a, SceneNode::new()).unwrap();
+        scene.remove(a).unwrap();
+        let c = scene.add(scene.root(), SceneNode::new()).unwrap();
+        let d = scene.add(scene.root(), SceneNode::new()).unwrap();
+        assert_eq!(scene.slots.len(), 3);
+        assert!(scene.contains(c) && scene.contains(d));
+        assert!(!scene.contains(a));
+        assert!(!scene.contains(b));
+        assert!(scene.add(a, SceneNode::new()).is_err());
+        assert_eq!(scene.children(scene.root()), &[c, d]);
+    }
+
+    #[test]
+    fn gradient_node() {
+        let gradient = Gradient::linear((0, 0), (10, 0), Color::BLACK, Color::WHITE);
+        let node = SceneNode::new().with_shape(&Rectangle::new((0, 0), (10, 10)), &gradient);
+        let mesh = node.mesh.unwrap();
+        let first = mesh.vertices[0].col;
+        assert!(mesh.vertices.iter().any(|v| v.col != first));
+    }
+
+    #[test]
+    fn hit_test_order() {
+        let mut scene = SceneGraph::new();
+        let back = scene.add(scene.root(), square((0, 0), 0)).unwrap();
+        let child = scene.add(back, square((5, 5), 0)).unwrap();
+        let sibling = scene.add(scene.root(), square((5, 0), 0)).unwrap();
+        let lifted = scene.add(scene.root(), square((0, 5), 1)).unwrap();
+        scene.set_visible(lifted, false);
+
+        // Later siblings are drawn on top of earlier ones and their children
+        assert_eq!(scene.hit_test_point(scene.root(), (7, 7)), Some(sibling));
+        assert_eq!(scene.hit_test_point(scene.root(), (2, 2)), Some(back));
+        assert_eq!(scene.hit_test_point(back, (7, 7)), Some(child));
+        assert_eq!(scene.hit_test_point(scene.root(), (12, 12)), Some(child));
+        assert_eq!(scene.hit_test_point(scene.root(), (30, 30)), None);
+
+        // Higher z wins regardless of order
+        scene.set_visible(lifted, true);
+        assert_eq!(scene.hit_test_point(scene.root(), (7, 7)), Some(lifted));
+        // Hidden parents hide their children
+        scene.set_visible(back, false);
+        assert_eq!(scene.hit_test_point(scene.root(), (12, 12)), None);
+    }
+}
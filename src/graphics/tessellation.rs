@@ -1,19 +1,27 @@
 //! Tessellation is the act of transforming (complex) geometric objects to a mesh of triangles.
 use crate::{
     quicksilver_compat::{
-        geom::{Line, Triangle},
-        Background, Circle, Shape,
+        geom::{Line, Polygon, Triangle},
+        Background, Circle, Color, Shape,
     },
-    Rectangle, Transform, Vector,
+    HexCell, Rectangle, Transform, Vector,
 };
 
+use lyon::math::point;
+use lyon::tessellation::{basic_shapes::fill_polyline, FillOptions, FillTessellator};
+
 mod abstract_mesh;
 mod lyon_tessellator;
+mod path;
 
 pub use abstract_mesh::*;
 pub use lyon_tessellator::*;
+pub use path::*;
 
 /// Some geometry object that can be tessellated to an AbstractMesh of triangles
+///
+/// Implementations paint the triangles they add with the full background, including gradients and patterns.
+/// Use `AbstractMesh::apply_background_from` for the latter.
 pub trait Tessellate {
     fn tessellate<'a>(&self, mesh: &mut AbstractMesh, background: Background<'a>);
 }
@@ -26,6 +34,7 @@ impl Tessellate for Vector {
 
 impl Tessellate for Rectangle {
     fn tessellate<'a>(&self, mesh: &mut AbstractMesh, bkg: Background<'a>) {
+        let (first_vertex, first_triangle) = (mesh.vertices.len(), mesh.triangles.len());
         let trans = Transform::translate(self.top_left() + self.size() / 2)
             * Transform::translate(-self.size() / 2)
             * Transform::scale(self.size());
@@ -42,11 +51,13 @@ impl Tessellate for Rectangle {
             .push(AbstractTriangle::new(offset, [0, 1, 2], bkg));
         mesh.triangles
             .push(AbstractTriangle::new(offset, [2, 3, 0], bkg));
+        mesh.apply_background_from(first_vertex, first_triangle, bkg);
     }
 }
 
 impl Tessellate for Circle {
     fn tessellate<'a>(&self, mesh: &mut AbstractMesh, bkg: Background<'a>) {
+        let (first_vertex, first_triangle) = (mesh.vertices.len(), mesh.triangles.len());
         let trans =
             Transform::translate(self.center()) * Transform::scale(Vector::ONE * self.radius);
         let tex_trans = bkg.texture_transform();
@@ -57,11 +68,13 @@ impl Tessellate for Circle {
                 AbstractTriangle::new(offset, [0, index as u32, index as u32 + 1], bkg)
             }),
         );
+        mesh.apply_background_from(first_vertex, first_triangle, bkg);
     }
 }
 
 impl Tessellate for Triangle {
     fn tessellate<'a>(&self, mesh: &mut AbstractMesh, bkg: Background<'a>) {
+        let (first_vertex, first_triangle) = (mesh.vertices.len(), mesh.triangles.len());
         let trans = Transform::translate(self.center()) * Transform::translate(-self.center());
         let tex_trans = bkg.texture_transform();
         let offset = mesh.add_positioned_vertices(
@@ -72,21 +85,169 @@ impl Tessellate for Triangle {
         );
         mesh.triangles
             .push(AbstractTriangle::new(offset, [0, 1, 2], bkg));
+        mesh.apply_background_from(first_vertex, first_triangle, bkg);
+    }
+}
+
+/// A line drawn with a cap at both ends. Plain lines are drawn with `LineCap::Butt`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CappedLine {
+    pub line: Line,
+    pub cap: LineCap,
+}
+
+impl CappedLine {
+    pub fn new(line: Line, cap: LineCap) -> Self {
+        Self { line, cap }
     }
 }
 
 impl Tessellate for Line {
     fn tessellate<'a>(&self, mesh: &mut AbstractMesh, bkg: Background<'a>) {
-        // create rectangle in right size
-        let rect = Rectangle::new(
-            (self.a.x, self.a.y + self.t / 2.0),
-            (self.a.distance(self.b), self.t),
+        CappedLine::new(*self, LineCap::Butt).tessellate(mesh, bkg);
+    }
+}
+
+impl Tessellate for CappedLine {
+    fn tessellate<'a>(&self, mesh: &mut AbstractMesh, bkg: Background<'a>) {
+        let Line { a, b, t } = self.line;
+        let length = a.distance(b);
+        if t <= 0.0 || (length == 0.0 && self.cap == LineCap::Butt) {
+            return;
+        }
+        let half = t / 2.0;
+        let extension = if self.cap == LineCap::Square {
+            half
+        } else {
+            0.0
+        };
+        // Build the line along the x axis, starting at the origin, and rotate it in place afterwards
+        let mut vertices = vec![
+            Vector::new(-extension, -half),
+            Vector::new(length + extension, -half),
+            Vector::new(length + extension, half),
+            Vector::new(-extension, half),
+        ];
+        let mut triangles = vec![[0, 1, 2], [2, 3, 0]];
+        if self.cap == LineCap::Round {
+            for (center, direction) in [(0.0, -1.0), (length, 1.0)] {
+                let first = vertices.len() as u32;
+                vertices.push(Vector::new(center, 0.0));
+                vertices.extend((0..=ROUND_CAP_SEGMENTS).map(|i| {
+                    let angle = std::f32::consts::PI * i as f32 / ROUND_CAP_SEGMENTS as f32;
+                    Vector::new(center + direction * half * angle.sin(), half * angle.cos())
+                }));
+                triangles
+                    .extend((1..=ROUND_CAP_SEGMENTS).map(|i| [first, first + i, first + i + 1]));
+            }
+        }
+
+        // Textures span the line from end to end, like for a rotated rectangle
+        let span = (length + 2.0 * extension).max(t);
+        let angle = if length > 0.0 { (b - a).angle() } else { 0.0 };
+        let trans = Transform::translate(a)
+            * Transform::rotate(angle)
+            * Transform::translate((-extension, -half))
+            * Transform::scale((span, t));
+        let (first_vertex, first_triangle) = (mesh.vertices.len(), mesh.triangles.len());
+        let tex_trans = bkg.texture_transform();
+        let offset = mesh.add_positioned_vertices(
+            vertices
+                .into_iter()
+                .map(|v| Vector::new((v.x + extension) / span, (v.y + half) / t)),
+            trans,
+            tex_trans,
+            bkg,
         );
+        mesh.triangles.extend(
+            triangles
+                .into_iter()
+                .map(|indices| AbstractTriangle::new(offset, indices, bkg)),
+        );
+        mesh.apply_background_from(first_vertex, first_triangle, bkg);
+    }
+}
 
-        rect.tessellate(mesh, bkg)
+/// Number of triangles used for each round line cap
+const ROUND_CAP_SEGMENTS: u32 = 12;
+
+impl Tessellate for Polygon {
+    fn tessellate<'a>(&self, mesh: &mut AbstractMesh, bkg: Background<'a>) {
+        // Let lyon find the triangles, then add the vertices again with the actual background
+        let mut shape_mesh = AbstractMesh::new();
+        let mut renderer = ShapeRenderer::new(&mut shape_mesh, Color::WHITE);
+        let points = self.vertices().iter().map(|v| point(v.x, v.y));
+        let result = fill_polyline(
+            points,
+            &mut FillTessellator::new(),
+            &FillOptions::default(),
+            &mut renderer,
+        );
+        let (vertices, triangles): (Vec<Vector>, Vec<[u32; 3]>) = if result.is_ok() {
+            (
+                shape_mesh.vertices.iter().map(|v| v.pos).collect(),
+                shape_mesh.triangles.iter().map(|t| t.indices).collect(),
+            )
+        } else {
+            let triangles = self.triangulate();
+            (
+                triangles.iter().flat_map(|t| vec![t.a, t.b, t.c]).collect(),
+                (0..triangles.len() as u32)
+                    .map(|i| [3 * i, 3 * i + 1, 3 * i + 2])
+                    .collect(),
+            )
+        };
+
+        add_with_background(mesh, vertices, triangles, bkg);
+    }
+}
+
+impl Tessellate for HexCell {
+    fn tessellate<'a>(&self, mesh: &mut AbstractMesh, bkg: Background<'a>) {
+        // A fan around the center, textures are stretched over the bounding box like for polygons
+        let corners = self.corners();
+        let vertices = std::iter::once(self.center).chain(corners.iter().cloned());
+        let triangles = (0..6).map(|i| [0, i + 1, (i + 1) % 6 + 1]).collect();
+        add_with_background(mesh, vertices.collect(), triangles, bkg);
     }
 }
 
+/// Add triangles found by a tessellator to the mesh, painted with the given background.
+///
+/// Like for rectangles, textures are stretched over the bounding box of all vertices.
+fn add_with_background(
+    mesh: &mut AbstractMesh,
+    vertices: Vec<Vector>,
+    triangles: Vec<[u32; 3]>,
+    bkg: Background,
+) {
+    let (min, max) = match vertices.first() {
+        Some(first) => vertices
+            .iter()
+            .fold((*first, *first), |(min, max), v| (min.min(*v), max.max(*v))),
+        None => return,
+    };
+    let size = max - min;
+    if size.x == 0.0 || size.y == 0.0 {
+        return;
+    }
+    let trans = Transform::translate(min) * Transform::scale(size);
+    let tex_trans = bkg.texture_transform();
+    let (first_vertex, first_triangle) = (mesh.vertices.len(), mesh.triangles.len());
+    let offset = mesh.add_positioned_vertices(
+        vertices.into_iter().map(|v| (v - min).times(size.recip())),
+        trans,
+        tex_trans,
+        bkg,
+    );
+    mesh.triangles.extend(
+        triangles
+            .into_iter()
+            .map(|indices| AbstractTriangle::new(offset, indices, bkg)),
+    );
+    mesh.apply_background_from(first_vertex, first_triangle, bkg);
+}
+
 // Taken from quicksilver:
 // Until there's serious compile-time calculations in Rust,
 // it's best to just pre-write the points on a rasterized circle
@@ -370,3 +531,62 @@ const CIRCLE_POINTS: [Vector; 64] = [
     },
     Vector { x: 1.0, y: 0.0 },
 ];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bounds(line: impl Tessellate) -> (Vector, Vector, usize) {
+        let mut mesh = AbstractMesh::new();
+        line.tessellate(&mut mesh, Background::Col(Color::BLACK));
+        let min = mesh
+            .vertices
+            .iter()
+            .fold(Vector::ONE * 1000, |m, v| m.min(v.pos));
+        let max = mesh
+            .vertices
+            .iter()
+            .fold(Vector::ONE * -1000, |m, v| m.max(v.pos));
+        (min, max, mesh.triangles.len())
+    }
+
+    #[test]
+    fn line_orientation() {
+        let vertical = Line::new((0, 0), (0, 10)).with_thickness(2);
+        assert_eq!(
+            bounds(vertical),
+            (Vector::new(-1, 0), Vector::new(1, 10), 2)
+        );
+        let diagonal = Line::new((0, 0), (-10, -10)).with_thickness(2f32.sqrt() * 2.0);
+        assert_eq!(
+            bounds(diagonal),
+            (Vector::new(-11, -11), Vector::new(1, 1), 2)
+        );
+    }
+
+    #[test]
+    fn line_caps() {
+        let line = Line::new((0, 0), (0, 10)).with_thickness(2);
+        let square = CappedLine::new(line, LineCap::Square);
+        assert_eq!(bounds(square), (Vector::new(-1, -1), Vector::new(1, 11), 2));
+        let (min, max, triangles) = bounds(CappedLine::new(line, LineCap::Round));
+        assert_eq!((min, max), (Vector::new(-1, -1), Vector::new(1, 11)));
+        assert_eq!(triangles, 2 + 2 * ROUND_CAP_SEGMENTS as usize);
+        let dot = Line::new((5, 5), (5, 5)).with_thickness(2);
+        assert_eq!(bounds(dot).2, 0);
+        let square_dot = CappedLine::new(dot, LineCap::Square);
+        assert_eq!(bounds(square_dot).0, Vector::new(4, 4));
+    }
+
+    #[test]
+    fn hex_cell() {
+        let grid = crate::HexGrid::new(crate::HexOrientation::FlatTop, 10.0);
+        let mut mesh = AbstractMesh::new();
+        grid.cell(crate::Hex::new(1, 0))
+            .tessellate(&mut mesh, Background::Col(Color::BLACK));
+        assert_eq!(mesh.vertices.len(), 7);
+        assert_eq!(mesh.triangles.len(), 6);
+        assert_eq!(mesh.vertices[0].pos, Vector::new(15, 8.660254));
+        assert_eq!(mesh.vertices[1].pos, Vector::new(25, 8.660254));
+    }
+}
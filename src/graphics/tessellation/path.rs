@@ -0,0 +1,534 @@
+//! Vector paths made of lines and curves, drawn filled or as strokes.
+
+use super::{add_with_background, AbstractMesh, ShapeRenderer, Tessellate};
+use crate::quicksilver_compat::{Background, Color};
+use crate::{Scalar, Vector};
+use lyon::math::{point, vector, Angle, Point};
+use lyon::path::iterator::PathIterator;
+use lyon::path::{FlattenedEvent, Path as LyonPath};
+use lyon::tessellation::{
+    FillOptions, FillTessellator, LineCap as LyonCap, LineJoin as LyonJoin, StrokeOptions,
+    StrokeTessellator,
+};
+use serde::{Deserialize, Serialize};
+
+/// A vector path, built from lines and curves and drawn either filled or stroked.
+///
+/// ```
+/// # use paddle::*;
+/// // A line chart
+/// let chart = Path::polyline(&[(0, 100), (50, 20), (100, 60), (150, 10)])
+///     .stroked(Stroke::new(3.0).with_join(LineJoin::Round));
+/// // An outline with a rounded corner, drawn dashed
+/// let outline = Path::new()
+///     .move_to((0, 0))
+///     .line_to((80, 0))
+///     .arc((80, 20), 90.0)
+///     .line_to((100, 100))
+///     .line_to((0, 100))
+///     .close()
+///     .stroked(Stroke::new(1.0).with_dashes(vec![5.0, 3.0], 0.0));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Path {
+    commands: Vec<PathCommand>,
+    current: Vector,
+    start: Vector,
+    style: PathStyle,
+    tolerance: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum PathCommand {
+    MoveTo(Vector),
+    LineTo(Vector),
+    QuadraticTo(Vector, Vector),
+    CubicTo(Vector, Vector, Vector),
+    Arc {
+        center: Vector,
+        radius: f32,
+        sweep: f32,
+    },
+    Close,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum PathStyle {
+    Fill,
+    Stroke(Stroke),
+}
+
+/// How the outline of a path is drawn
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stroke {
+    pub width: f32,
+    pub join: LineJoin,
+    /// Cap at both ends of each open sub-path and each dash
+    pub cap: LineCap,
+    /// Longest miter, relative to the width, before `LineJoin::MiterClip` clips it. At least 1.
+    pub miter_limit: f32,
+    /// Alternating lengths of dashes and gaps, empty for a solid line
+    pub dashes: Vec<f32>,
+    /// Distance into the dash pattern at which each sub-path starts
+    pub dash_offset: f32,
+}
+
+/// Shape of the corners where two segments of a stroke meet
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LineJoin {
+    #[default]
+    Miter,
+    /// Like `Miter`, but cut off at the miter limit
+    MiterClip,
+    Round,
+    Bevel,
+}
+
+/// Shape of the ends of a stroke
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum LineCap {
+    /// Ends exactly at the end points
+    #[default]
+    Butt,
+    /// Extends half the stroke width beyond the end points
+    Square,
+    Round,
+}
+
+impl Default for Path {
+    fn default() -> Self {
+        Self {
+            commands: Vec::new(),
+            current: Vector::ZERO,
+            start: Vector::ZERO,
+            style: PathStyle::Fill,
+            tolerance: StrokeOptions::DEFAULT_TOLERANCE,
+        }
+    }
+}
+
+impl Path {
+    /// An empty path, which is filled unless `stroked` is called
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// An open path through all the points
+    pub fn polyline<V: Into<Vector> + Copy>(points: &[V]) -> Self {
+        let mut points = points.iter().map(|p| (*p).into());
+        let mut path = match points.next() {
+            Some(first) => Path::new().move_to(first),
+            None => return Path::new(),
+        };
+        for p in points {
+            path = path.line_to(p);
+        }
+        path
+    }
+    /// Start a new sub-path
+    pub fn move_to(mut self, to: impl Into<Vector>) -> Self {
+        let to = to.into();
+        self.commands.push(PathCommand::MoveTo(to));
+        self.current = to;
+        self.start = to;
+        self
+    }
+    pub fn line_to(mut self, to: impl Into<Vector>) -> Self {
+        let to = to.into();
+        self.commands.push(PathCommand::LineTo(to));
+        self.current = to;
+        self
+    }
+    /// Quadratic Bézier curve to `to`, pulled towards the control point
+    pub fn quadratic_to(mut self, ctrl: impl Into<Vector>, to: impl Into<Vector>) -> Self {
+        let to = to.into();
+        self.commands
+            .push(PathCommand::QuadraticTo(ctrl.into(), to));
+        self.current = to;
+        self
+    }
+    /// Cubic Bézier curve to `to`, pulled towards the two control points
+    pub fn cubic_to(
+        mut self,
+        ctrl1: impl Into<Vector>,
+        ctrl2: impl Into<Vector>,
+        to: impl Into<Vector>,
+    ) -> Self {
+        let to = to.into();
+        self.commands
+            .push(PathCommand::CubicTo(ctrl1.into(), ctrl2.into(), to));
+        self.current = to;
+        self
+    }
+    /// Circular arc around `center`, starting at the current position.
+    ///
+    /// The sweep angle is in degrees, positive angles go clockwise on the screen.
+    pub fn arc(mut self, center: impl Into<Vector>, sweep_angle: f32) -> Self {
+        let center = center.into();
+        let offset = self.current - center;
+        let radius = offset.len();
+        self.commands.push(PathCommand::Arc {
+            center,
+            radius,
+            sweep: sweep_angle,
+        });
+        self.current = center + Vector::from_angle(offset.angle() + sweep_angle) * radius;
+        self
+    }
+    /// Connect the current position back to the start of the sub-path
+    pub fn close(mut self) -> Self {
+        self.commands.push(PathCommand::Close);
+        self.current = self.start;
+        self
+    }
+    /// The position at which the next segment starts
+    pub fn current_position(&self) -> Vector {
+        self.current
+    }
+    /// Draw the area enclosed by the path. Open sub-paths are closed implicitly.
+    pub fn filled(mut self) -> Self {
+        self.style = PathStyle::Fill;
+        self
+    }
+    /// Draw the outline of the path
+    pub fn stroked(mut self, stroke: Stroke) -> Self {
+        self.style = PathStyle::Stroke(stroke);
+        self
+    }
+    /// Maximum distance between curves and the line segments approximating them
+    pub fn with_tolerance(mut self, tolerance: f32) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    fn to_lyon(&self) -> LyonPath {
+        let mut builder = LyonPath::builder();
+        for command in &self.commands {
+            match *command {
+                PathCommand::MoveTo(to) => builder.move_to(to_point(to)),
+                PathCommand::LineTo(to) => builder.line_to(to_point(to)),
+                PathCommand::QuadraticTo(ctrl, to) => {
+                    builder.quadratic_bezier_to(to_point(ctrl), to_point(to))
+                }
+                PathCommand::CubicTo(ctrl1, ctrl2, to) => {
+                    builder.cubic_bezier_to(to_point(ctrl1), to_point(ctrl2), to_point(to))
+                }
+                PathCommand::Arc {
+                    center,
+                    radius,
+                    sweep,
+                } => builder.arc(
+                    to_point(center),
+                    vector(radius, radius),
+                    Angle::degrees(sweep),
+                    Angle::zero(),
+                ),
+                PathCommand::Close => builder.close(),
+            }
+        }
+        builder.build()
+    }
+}
+
+impl Stroke {
+    pub fn new(width: f32) -> Self {
+        Self {
+            width,
+            join: LineJoin::default(),
+            cap: LineCap::default(),
+            miter_limit: StrokeOptions::DEFAULT_MITER_LIMIT,
+            dashes: Vec::new(),
+            dash_offset: 0.0,
+        }
+    }
+    pub fn with_join(mut self, join: LineJoin) -> Self {
+        self.join = join;
+        self
+    }
+    pub fn with_cap(mut self, cap: LineCap) -> Self {
+        self.cap = cap;
+        self
+    }
+    pub fn with_miter_limit(mut self, miter_limit: f32) -> Self {
+        self.miter_limit = miter_limit.max(StrokeOptions::MINIMUM_MITER_LIMIT);
+        self
+    }
+    /// Draw dashes and gaps of alternating lengths, like SVG's `stroke-dasharray`
+    pub fn with_dashes(mut self, pattern: Vec<f32>, offset: f32) -> Self {
+        self.dashes = pattern;
+        self.dash_offset = offset;
+        self
+    }
+    fn options(&self, tolerance: f32) -> StrokeOptions {
+        let cap = match self.cap {
+            LineCap::Butt => LyonCap::Butt,
+            LineCap::Square => LyonCap::Square,
+            LineCap::Round => LyonCap::Round,
+        };
+        let join = match self.join {
+            LineJoin::Miter => LyonJoin::Miter,
+            LineJoin::MiterClip => LyonJoin::MiterClip,
+            LineJoin::Round => LyonJoin::Round,
+            LineJoin::Bevel => LyonJoin::Bevel,
+        };
+        StrokeOptions::tolerance(tolerance)
+            .with_line_width(self.width)
+            .with_line_cap(cap)
+            .with_line_join(join)
+            .with_miter_limit(self.miter_limit)
+    }
+}
+
+impl Tessellate for Path {
+    fn tessellate<'a>(&self, mesh: &mut AbstractMesh, bkg: Background<'a>) {
+        let path = self.to_lyon();
+        // Tessellate to a plain mesh first, then add the vertices again with the actual background
+        let mut shape_mesh = AbstractMesh::new();
+        let mut renderer = ShapeRenderer::new(&mut shape_mesh, Color::WHITE);
+        let result = match &self.style {
+            PathStyle::Fill => FillTessellator::new().tessellate_path(
+                path.iter(),
+                &FillOptions::tolerance(self.tolerance),
+                &mut renderer,
+            ),
+            PathStyle::Stroke(stroke) => {
+                let options = stroke.options(self.tolerance);
+                let mut tessellator = StrokeTessellator::new();
+                if stroke.dashes.iter().any(|d| *d > 0.0) {
+                    let dashed = dash(&path, &stroke.dashes, stroke.dash_offset, self.tolerance);
+                    tessellator.tessellate_path(dashed.iter(), &options, &mut renderer)
+                } else {
+                    tessellator.tessellate_path(path.iter(), &options, &mut renderer)
+                }
+            }
+        };
+        if result.is_err() {
+            return;
+        }
+        add_with_background(
+            mesh,
+            shape_mesh.vertices.iter().map(|v| v.pos).collect(),
+            shape_mesh.triangles.iter().map(|t| t.indices).collect(),
+            bkg,
+        );
+    }
+}
+
+/// Connected line segments of a constant thickness, with joins between them and caps at both ends.
+///
+/// Use a `Path` for curves or dashes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Polyline {
+    pub points: Vec<Vector>,
+    /// The thickness of all segments
+    pub t: f32,
+    pub join: LineJoin,
+    pub cap: LineCap,
+    /// Whether the last point connects back to the first
+    pub closed: bool,
+}
+
+impl Polyline {
+    pub fn new<V: Into<Vector>>(points: impl IntoIterator<Item = V>) -> Self {
+        Self {
+            points: points.into_iter().map(Into::into).collect(),
+            t: 1.0,
+            join: LineJoin::default(),
+            cap: LineCap::default(),
+            closed: false,
+        }
+    }
+    #[must_use]
+    pub fn with_thickness(mut self, thickness: impl Scalar) -> Self {
+        self.t = thickness.float();
+        self
+    }
+    #[must_use]
+    pub fn with_join(mut self, join: LineJoin) -> Self {
+        self.join = join;
+        self
+    }
+    #[must_use]
+    pub fn with_cap(mut self, cap: LineCap) -> Self {
+        self.cap = cap;
+        self
+    }
+    /// Connect the last point back to the first, with a join instead of caps
+    #[must_use]
+    pub fn closed(mut self) -> Self {
+        self.closed = true;
+        self
+    }
+}
+
+impl Tessellate for Polyline {
+    fn tessellate<'a>(&self, mesh: &mut AbstractMesh, bkg: Background<'a>) {
+        let mut path = Path::polyline(&self.points);
+        if self.closed {
+            path = path.close();
+        }
+        path.stroked(Stroke::new(self.t).with_join(self.join).with_cap(self.cap))
+            .tessellate(mesh, bkg)
+    }
+}
+
+fn to_point(v: Vector) -> Point {
+    point(v.x, v.y)
+}
+
+/// Split the flattened path into one open sub-path per dash
+fn dash(path: &LyonPath, pattern: &[f32], offset: f32, tolerance: f32) -> LyonPath {
+    // Like in SVG, odd patterns are repeated to get an even number of entries
+    let repeat = if pattern.len() % 2 == 1 { 2 } else { 1 };
+    let pattern: Vec<f32> = pattern
+        .iter()
+        .cycle()
+        .take(pattern.len() * repeat)
+        .map(|d| d.max(0.0))
+        .collect();
+    let mut builder = LyonPath::builder();
+    let mut dashes = DashState::new(&pattern, offset);
+    for event in path.iter().flattened(tolerance) {
+        match event {
+            FlattenedEvent::MoveTo(p) => {
+                dashes = DashState::new(&pattern, offset);
+                if dashes.on {
+                    builder.move_to(p);
+                }
+            }
+            FlattenedEvent::Line(segment) | FlattenedEvent::Close(segment) => {
+                let length = segment.length();
+                if length == 0.0 {
+                    continue;
+                }
+                let direction = (segment.to - segment.from) / length;
+                let mut position = segment.from;
+                let mut remaining = length;
+                while remaining > dashes.left {
+                    position += direction * dashes.left;
+                    remaining -= dashes.left;
+                    if dashes.on {
+                        builder.line_to(position);
+                    }
+                    dashes.next();
+                    if dashes.on {
+                        builder.move_to(position);
+                    }
+                }
+                dashes.left -= remaining;
+                if dashes.on {
+                    builder.line_to(segment.to);
+                }
+            }
+        }
+    }
+    builder.build()
+}
+
+/// Position inside a dash pattern
+struct DashState<'a> {
+    pattern: &'a [f32],
+    index: usize,
+    /// Remaining length of the current entry
+    left: f32,
+    /// Whether the current entry is a dash or a gap
+    on: bool,
+}
+
+impl<'a> DashState<'a> {
+    fn new(pattern: &'a [f32], offset: f32) -> Self {
+        let mut state = Self {
+            pattern,
+            index: 0,
+            left: pattern[0],
+            on: true,
+        };
+        let mut skip = offset.rem_euclid(pattern.iter().sum());
+        while skip > state.left {
+            skip -= state.left;
+            state.next();
+        }
+        state.left -= skip;
+        state
+    }
+    fn next(&mut self) {
+        self.index = (self.index + 1) % self.pattern.len();
+        self.left = self.pattern[self.index];
+        self.on = !self.on;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lyon::path::PathEvent;
+
+    fn dash_lengths(path: &LyonPath) -> Vec<f32> {
+        let mut lengths = Vec::new();
+        for event in path.iter() {
+            match event {
+                PathEvent::MoveTo(_) => lengths.push(0.0),
+                PathEvent::Line(segment) => *lengths.last_mut().unwrap() += segment.length(),
+                _ => panic!("Dashes should only contain lines"),
+            }
+        }
+        lengths
+    }
+
+    #[test]
+    fn dashes() {
+        let line = Path::polyline(&[(0, 0), (10, 0), (10, 10)]).to_lyon();
+        let lengths = dash_lengths(&dash(&line, &[4.0, 2.0], 0.0, 0.1));
+        let expected = [4.0, 4.0, 4.0, 2.0];
+        assert_eq!(lengths.len(), expected.len());
+        for (l, e) in lengths.iter().zip(&expected) {
+            assert!((l - e).abs() < 1e-4, "{:?}", lengths);
+        }
+        // Starting inside the gap and odd patterns
+        let lengths = dash_lengths(&dash(&line, &[3.0], 4.0, 0.1));
+        assert_eq!(lengths.len(), 3);
+        assert!(lengths.iter().all(|l| (l - 3.0).abs() < 1e-4));
+    }
+
+    #[test]
+    fn arc_end_position() {
+        let path = Path::new().move_to((10, 0)).arc((0, 0), 90.0);
+        assert_eq!(path.current_position(), Vector::new(0, 10));
+        let closed = path.line_to((0, 0)).close();
+        assert_eq!(closed.current_position(), Vector::new(10, 0));
+    }
+
+    #[test]
+    fn polyline_joins() {
+        let bkg = Background::Col(Color::BLACK);
+        let corner = Polyline::new(vec![(0, 0), (10, 0), (10, 10)]).with_thickness(2);
+        let mut mitered = AbstractMesh::new();
+        corner.clone().tessellate(&mut mitered, bkg);
+        let mut beveled = AbstractMesh::new();
+        corner
+            .with_join(LineJoin::Bevel)
+            .tessellate(&mut beveled, bkg);
+        let top_right = |mesh: &AbstractMesh| {
+            mesh.vertices
+                .iter()
+                .any(|v| (v.pos - Vector::new(11, -1)).len() < 1e-3)
+        };
+        assert!(top_right(&mitered));
+        assert!(!top_right(&beveled));
+    }
+
+    #[test]
+    fn tessellates_fill_and_stroke() {
+        let bkg = Background::Col(Color::BLACK);
+        let square = Path::polyline(&[(0, 0), (10, 0), (10, 10), (0, 10)]);
+        let mut mesh = AbstractMesh::new();
+        square.clone().tessellate(&mut mesh, bkg);
+        assert_eq!(mesh.triangles.len(), 2);
+        let mut mesh = AbstractMesh::new();
+        square
+            .stroked(Stroke::new(2.0).with_dashes(vec![2.0, 2.0], 0.0))
+            .tessellate(&mut mesh, bkg);
+        assert!(mesh.triangles.len() >= 2 * 7);
+        let max_x = mesh.vertices.iter().map(|v| v.pos.x).fold(0.0, f32::max);
+        assert!((max_x - 11.0).abs() < 1e-4);
+    }
+}
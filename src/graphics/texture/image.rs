@@ -82,15 +82,10 @@ impl Image {
 
     /// Create a view into an existing image, using texel coordinates (number of pixels in source texture)
     pub fn subimage_texels(&self, rect: Rectangle) -> Image {
+        let texel_size = Vector::new(self.texture.texel_width, self.texture.texel_height);
         let img = Image {
             texture: self.texture.clone(),
-            region: Rectangle::new(
-                (
-                    self.region.pos.x + rect.pos.x / self.natural_width(),
-                    self.region.pos.y + rect.pos.y / self.natural_height(),
-                ),
-                (rect.width(), rect.height()),
-            ),
+            region: texel_region(&self.region, texel_size, &rect),
             transform: self.transform,
         };
         debug_assert!(img.region.x() <= 1.0);
@@ -152,3 +147,36 @@ impl Default for BindTexturePayload {
         Self::Done
     }
 }
+
+/// Region in texture coordinates of a rectangle given in texels, relative to the region of an image.
+///
+/// Both position and size are divided by the size of the texture in texels.
+fn texel_region(region: &Rectangle, texel_size: Vector, rect: &Rectangle) -> Rectangle {
+    Rectangle::new(
+        region.pos + rect.pos.times(texel_size.recip()),
+        rect.size.times(texel_size.recip()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn texel_region_is_normalized() {
+        let texels = Vector::new(256, 128);
+        let full = Rectangle::new_sized((1.0, 1.0));
+        let rect = Rectangle::new((64, 32), (32, 64));
+        assert_eq!(
+            texel_region(&full, texels, &rect),
+            Rectangle::new((0.25, 0.25), (0.125, 0.5))
+        );
+        // Relative to a view into the texture
+        let view = Rectangle::new((0.5, 0.0), (0.5, 1.0));
+        let rect = Rectangle::new((64, 0), (64, 64));
+        assert_eq!(
+            texel_region(&view, texels, &rect),
+            Rectangle::new((0.75, 0.0), (0.25, 0.5))
+        );
+    }
+}
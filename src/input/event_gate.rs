@@ -1,10 +1,11 @@
 use super::event::*;
 use super::{browser_pointer_events::*, keys::Key};
-use crate::{js::PaddleJsContext, FrameHandle, Vector};
+use crate::{js::PaddleJsContext, AudioUnlock, FocusManager, FrameHandle, Vector};
 use div::DivHandle;
 use nuts::{Activity, UncheckedActivityId};
 use strum::IntoEnumIterator;
 use wasm_bindgen::prelude::wasm_bindgen;
+use web_sys::AudioContext;
 
 use super::browser_pointer_events::{BrowserPointerEventType, TouchEventType};
 
@@ -14,6 +15,9 @@ pub(crate) struct RegisterEventListener {
     activity: UncheckedActivityId,
 }
 
+/// Resume the audio context on the first user input
+struct RegisterAudioUnlock(AudioContext);
+
 pub enum EventListenerType {
     Click,
     Keyboard,
@@ -28,6 +32,8 @@ pub enum EventListenerType {
 /// A single JS EventListener sits on the JS side of things and will call event_from_js(ID) with event IDs.
 pub(crate) struct EventGate {
     js: PaddleJsContext,
+    /// Keyboard events are listened to on the document, a single listener serves all frames
+    keyboard_registered: bool,
 }
 
 #[wasm_bindgen(module = "/src/js/paddle.js")]
@@ -51,22 +57,34 @@ pub fn pointer_event_gate(activity_id: usize, event: BrowserPointerEventType, x:
     aid.private_message(PointerEvent(event.into(), Vector::new(x, y)));
 }
 #[wasm_bindgen(module = "/src/js/paddle.js")]
-pub fn keyboard_event_gate(activity_id: usize, event: KeyEventType, key: Key) {
-    let aid = UncheckedActivityId::forge_from_usize(activity_id);
-    aid.private_message(KeyEvent(event, key));
+pub fn keyboard_event_gate(_activity_id: usize, event: KeyEventType, key: Key) {
+    // Key events are routed to the focused frame, not to the frame that registered the listener
+    FocusManager::key_event(KeyEvent(event, key));
+}
+#[wasm_bindgen(module = "/src/js/paddle.js")]
+pub fn audio_unlock_gate() {
+    nuts::publish(AudioUnlock);
 }
 
 impl EventGate {
     pub(crate) fn init() {
         let gate = EventGate {
             js: PaddleJsContext::new(),
+            keyboard_registered: false,
         };
         let aid = nuts::new_activity(gate);
         aid.private_channel(Self::register_event_listener);
+        aid.private_channel(Self::register_audio_unlock);
     }
     pub fn listen<A: Activity>(frame: &FrameHandle<A>, event_type: EventListenerType) {
         nuts::send_to::<Self, _>(RegisterEventListener::new(frame, event_type));
     }
+    pub(crate) fn unlock_audio(ctx: AudioContext) {
+        nuts::send_to::<Self, _>(RegisterAudioUnlock(ctx));
+    }
+    fn register_audio_unlock(&mut self, msg: RegisterAudioUnlock) {
+        self.js.register_audio_unlock(&msg.0);
+    }
     fn register_event_listener(&mut self, msg: RegisterEventListener) {
         match msg.event_type {
             EventListenerType::Click => {
@@ -98,6 +116,10 @@ impl EventGate {
                 }
             }
             EventListenerType::Keyboard => {
+                if self.keyboard_registered {
+                    return;
+                }
+                self.keyboard_registered = true;
                 let aid = msg.activity.as_usize();
                 for event in KeyEventType::iter() {
                     self.js.register_keyboard_event_listener(event, aid);
@@ -21,6 +21,10 @@ impl PointerTracker {
     pub fn pos(&self) -> Option<Vector> {
         self.pos
     }
+    /// True while the mouse button is held down or a finger touches the screen.
+    pub fn is_pressed(&self) -> bool {
+        self.mouse_down.is_some()
+    }
     /// Movement from point A to point B with touch or pressed mouse.
     ///
     /// Reading a drag with this method clears the content.
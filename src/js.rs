@@ -1,7 +1,7 @@
 //! Glue module between paddle.js and the Rust side of Paddle
 
 use wasm_bindgen::prelude::wasm_bindgen;
-use web_sys::HtmlElement;
+use web_sys::{AudioContext, HtmlElement};
 
 use crate::input::browser_pointer_events::*;
 use crate::*;
@@ -54,6 +54,10 @@ extern "C" {
         callback_id: usize,
     );
 
+    #[wasm_bindgen(method)]
+    #[wasm_bindgen(js_name = registerAudioUnlock)]
+    pub fn register_audio_unlock(this: &PaddleJsContext, audio_context: &AudioContext);
+
     #[wasm_bindgen(js_name = supportsPointerEvents)]
     pub fn supports_pointer_events() -> bool;
 }
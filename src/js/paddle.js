@@ -1,4 +1,4 @@
-import { click_event_gate, mouse_event_gate, keyboard_event_gate, pointer_event_gate, touch_event_gate } from "#RUST#";
+import { click_event_gate, mouse_event_gate, keyboard_event_gate, pointer_event_gate, touch_event_gate, audio_unlock_gate } from "#RUST#";
 import { mouseEventString, clickEventString, touchEventString, pointerEventString, keyboardEventString, keyEventEnum } from "./enums";
 
 export class PaddleJsContext {
@@ -30,6 +30,22 @@ export class PaddleJsContext {
         const event = keyboardEventString(eventType);
         document.addEventListener(event, (ev) => this.forwardKeyboardEvent(ev, eventType, callbackId));
     }
+    // Browsers only allow audio playback after a user interaction.
+    // The audio context is resumed inside the first input event handler and Rust is notified once it is running.
+    registerAudioUnlock(audioContext) {
+        const events = ["pointerdown", "touchend", "mousedown", "keydown"];
+        const unlock = () => {
+            audioContext.resume().then(() => {
+                events.forEach((ev) => document.removeEventListener(ev, unlock, true));
+                audio_unlock_gate();
+            });
+        };
+        if (audioContext.state === "running") {
+            audio_unlock_gate();
+        } else {
+            events.forEach((ev) => document.addEventListener(ev, unlock, true));
+        }
+    }
     forwardClickEvent(event, eventType, callbackId) {
         const rect = event.target.getBoundingClientRect();
         const x = event.clientX - rect.left;
@@ -62,6 +78,10 @@ export class PaddleJsContext {
     forwardKeyboardEvent(event, eventType, callbackId) {
         let key = keyEventEnum(event);
         if (typeof(key) === "number") {
+            // Tab moves the focus between frames, the browser must not move it out of the game
+            if (event.key === "Tab") {
+                event.preventDefault();
+            }
             keyboard_event_gate(callbackId, eventType, key);
         }
     }
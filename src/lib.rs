@@ -9,27 +9,45 @@ pub use nuts;
 #[macro_use]
 pub(crate) mod debug;
 
+pub(crate) mod animation;
+pub(crate) mod audio;
 pub(crate) mod context;
+pub(crate) mod ecs;
 pub(crate) mod error;
 pub(crate) mod frame;
 pub mod graphics;
 pub(crate) mod input;
 pub(crate) mod js;
 pub(crate) mod load;
+pub(crate) mod network;
+pub(crate) mod pathfinding;
+pub(crate) mod physics;
 pub mod quicksilver_compat;
+pub(crate) mod storage;
+pub(crate) mod tiled;
+pub(crate) mod ui;
 pub(crate) mod view_manager;
 pub mod web_integration;
 
 mod display;
 mod geometry;
+pub use animation::*;
+pub use audio::*;
 pub use context::*;
 pub use display::*;
+pub use ecs::*;
 pub use error::*;
 pub use frame::*;
 pub use geometry::*;
 pub use graphics::*;
 pub use input::*;
 pub use load::*;
+pub use network::*;
+pub use pathfinding::*;
+pub use physics::*;
+pub use storage::*;
+pub use tiled::*;
+pub use ui::*;
 pub use view_manager::*;
 
 // Code that currently belongs nowhere
@@ -50,6 +68,9 @@ pub fn init(config: PaddleConfig) -> PaddleResult<()> {
     }
     Context::init(config)?;
     EventGate::init();
+    FocusManager::init();
+    AudioSystem::init();
+    NetworkSystem::init();
     FrameManipulator::init();
     Ok(())
 }
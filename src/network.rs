@@ -0,0 +1,185 @@
+//! WebSocket connections to game servers.
+//!
+//! Connections are owned by a network system living in `Domain::Network`.
+//! Incoming messages are decoded with the `Codec` of their connection and shared with all frames as `NetworkMessage`,
+//! changes of the connection status as `ConnectionChanged`. Listen to both with `FrameHandle::listen`.
+//!
+//! Lost connections are reopened after a delay growing with each failed attempt, as configured by a `Backoff`.
+//! Messages sent in the meantime are buffered and sent once the connection is open again.
+//!
+//! ```no_run
+//! # use paddle::*;
+//! # use serde::{Deserialize, Serialize};
+//! #[derive(Serialize, Deserialize)]
+//! enum Chat {
+//!     Say(String),
+//! }
+//! # fn example<F: Frame + 'static>(handle: FrameHandle<F>) {
+//! let chat: Connection<Chat> = Network::connect("wss://example.com/chat", JsonCodec::text());
+//! chat.send(&Chat::Say("Hello".to_owned())).nuts_check();
+//! handle.listen(|_frame, _state, msg: &NetworkMessage<Chat>| {
+//!     let Chat::Say(text) = &msg.message;
+//!     println!("{}", text);
+//! });
+//! # }
+//! ```
+
+mod codec;
+mod connection;
+mod system;
+mod transport;
+mod web_socket;
+
+pub use codec::*;
+pub use connection::{Backoff, ConnectionStatus};
+pub(crate) use system::NetworkSystem;
+pub use transport::*;
+pub use web_socket::*;
+
+use crate::{share, PaddleResult};
+use connection::ConnectionState;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use system::{NetworkCommand, OpenConnection, TransportMessage};
+
+/// Facade to the network system. Commands are forwarded to the network system and executed asynchronously.
+pub struct Network;
+
+/// Identifies a connection in shared events
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ConnectionId(usize);
+
+/// A message as sent over the network
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RawMessage {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+/// Refers to a connection opened with `Network::connect`, to send typed messages over it.
+pub struct Connection<T> {
+    id: ConnectionId,
+    codec: Rc<dyn Codec<T>>,
+}
+
+/// Shared with all frames for every message received on a connection.
+///
+/// Listen to it with `FrameHandle::listen`, with the message type of the connection.
+#[derive(Clone, Debug)]
+pub struct NetworkMessage<T> {
+    pub connection: ConnectionId,
+    pub message: T,
+}
+
+/// Shared with all frames when the status of a connection changes.
+///
+/// Listen to it with `FrameHandle::listen`.
+#[derive(Clone, Copy, Debug)]
+pub struct ConnectionChanged {
+    pub connection: ConnectionId,
+    pub status: ConnectionStatus,
+}
+
+/// How a connection is opened
+pub struct ConnectOptions {
+    backoff: Backoff,
+    transport: Box<dyn Transport>,
+}
+
+impl ConnectOptions {
+    /// A WebSocket with the default backoff
+    pub fn new() -> Self {
+        ConnectOptions {
+            backoff: Backoff::default(),
+            transport: Box::new(WebSocketTransport::new()),
+        }
+    }
+    pub fn with_backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+    /// Use something else than a WebSocket, such as an `EchoTransport` for tests
+    pub fn with_transport(mut self, transport: impl Transport + 'static) -> Self {
+        self.transport = Box::new(transport);
+        self
+    }
+}
+
+impl Default for ConnectOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Network {
+    /// Open a WebSocket connection, which is reopened whenever it is lost
+    pub fn connect<T: 'static>(url: &str, codec: impl Codec<T> + 'static) -> Connection<T> {
+        Self::connect_ex(url, codec, ConnectOptions::new())
+    }
+    pub fn connect_ex<T: 'static>(
+        url: &str,
+        codec: impl Codec<T> + 'static,
+        options: ConnectOptions,
+    ) -> Connection<T> {
+        let id = ConnectionId::next();
+        let codec: Rc<dyn Codec<T>> = Rc::new(codec);
+        let decoder = codec.clone();
+        let deliver = Box::new(move |raw: RawMessage| -> PaddleResult<()> {
+            let message = decoder.decode(&raw)?;
+            share(NetworkMessage {
+                connection: id,
+                message,
+            });
+            Ok(())
+        });
+        let events =
+            Rc::new(move |event| nuts::send_to::<NetworkSystem, _>(TransportMessage(id, event)));
+        let state =
+            ConnectionState::new(url.to_owned(), options.transport, events, options.backoff);
+        send(NetworkCommand::Connect(
+            id,
+            Box::new(OpenConnection { state, deliver }),
+        ));
+        Connection { id, codec }
+    }
+}
+
+impl<T> Connection<T> {
+    pub fn id(&self) -> ConnectionId {
+        self.id
+    }
+    /// Encode and send a message, or buffer it until the connection is open
+    pub fn send(&self, message: &T) -> PaddleResult<()> {
+        let raw = self.codec.encode(message)?;
+        self.send_raw(raw);
+        Ok(())
+    }
+    /// Send a message without encoding it
+    pub fn send_raw(&self, message: RawMessage) {
+        send(NetworkCommand::Send(self.id, message));
+    }
+    /// Close the connection for good
+    pub fn close(&self) {
+        send(NetworkCommand::Close(self.id));
+    }
+}
+
+impl<T> Clone for Connection<T> {
+    fn clone(&self) -> Self {
+        Connection {
+            id: self.id,
+            codec: self.codec.clone(),
+        }
+    }
+}
+
+impl ConnectionId {
+    fn next() -> Self {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        ConnectionId(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+fn send(cmd: NetworkCommand) {
+    nuts::send_to::<NetworkSystem, _>(cmd);
+}
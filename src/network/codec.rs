@@ -0,0 +1,90 @@
+use super::RawMessage;
+use crate::PaddleResult;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Converts between typed game messages and what is sent over the network
+pub trait Codec<T> {
+    fn encode(&self, message: &T) -> PaddleResult<RawMessage>;
+    fn decode(&self, message: &RawMessage) -> PaddleResult<T>;
+}
+
+/// Sends any `serde` type as JSON, in text or binary messages.
+///
+/// Both text and binary messages are accepted when decoding.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct JsonCodec {
+    binary: bool,
+}
+
+/// Passes messages through unchanged
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RawCodec;
+
+impl JsonCodec {
+    /// Send JSON in text messages
+    pub fn text() -> Self {
+        JsonCodec { binary: false }
+    }
+    /// Send JSON as UTF-8 bytes in binary messages
+    pub fn binary() -> Self {
+        JsonCodec { binary: true }
+    }
+}
+
+impl<T: Serialize + DeserializeOwned> Codec<T> for JsonCodec {
+    fn encode(&self, message: &T) -> PaddleResult<RawMessage> {
+        if self.binary {
+            Ok(RawMessage::Binary(serde_json::to_vec(message)?))
+        } else {
+            Ok(RawMessage::Text(serde_json::to_string(message)?))
+        }
+    }
+    fn decode(&self, message: &RawMessage) -> PaddleResult<T> {
+        match message {
+            RawMessage::Text(text) => Ok(serde_json::from_str(text)?),
+            RawMessage::Binary(data) => Ok(serde_json::from_slice(data)?),
+        }
+    }
+}
+
+impl Codec<RawMessage> for RawCodec {
+    fn encode(&self, message: &RawMessage) -> PaddleResult<RawMessage> {
+        Ok(message.clone())
+    }
+    fn decode(&self, message: &RawMessage) -> PaddleResult<RawMessage> {
+        Ok(message.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Chat {
+        Join { name: String },
+        Say(String),
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let join = Chat::Join {
+            name: "Bo".to_owned(),
+        };
+        let text = JsonCodec::text().encode(&join).unwrap();
+        assert_eq!(
+            text,
+            RawMessage::Text(r#"{"Join":{"name":"Bo"}}"#.to_owned())
+        );
+        let binary = JsonCodec::binary().encode(&join).unwrap();
+        assert!(matches!(binary, RawMessage::Binary(_)));
+        let decoded: Chat = JsonCodec::text().decode(&binary).unwrap();
+        assert_eq!(decoded, join);
+
+        let invalid = RawMessage::Text(r#"{"Shout":"hi"}"#.to_owned());
+        let result: PaddleResult<Chat> = JsonCodec::text().decode(&invalid);
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,380 @@
+use super::{RawMessage, Transport, TransportEvent, TransportEvents};
+use crate::PaddleResult;
+use std::collections::VecDeque;
+
+/// Messages sent while disconnected are kept until the connection is open again, up to this many
+const MAX_BUFFERED: usize = 1024;
+
+/// How long to wait before reconnecting after a connection has been lost.
+///
+/// The delay starts at `initial_delay_ms` and grows by `factor` with every failed attempt, up to `max_delay_ms`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Backoff {
+    pub initial_delay_ms: f64,
+    pub max_delay_ms: f64,
+    pub factor: f64,
+    /// Give up after this many attempts in a row, or never if `None`
+    pub max_attempts: Option<u32>,
+}
+
+/// Shared with all frames when a connection opens, closes or is about to reconnect.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConnectionStatus {
+    Connecting,
+    Open,
+    /// Waiting before the next attempt, counted from 1
+    Reconnecting {
+        attempt: u32,
+        delay_ms: f64,
+    },
+    /// Closed by the game, or lost with no attempts left
+    Closed,
+}
+
+/// Result of handling an event of the transport
+#[derive(Debug, PartialEq)]
+pub(crate) enum ConnectionUpdate {
+    Status(ConnectionStatus),
+    Message(RawMessage),
+}
+
+/// One connection with its reconnection logic, independent of the browser and of time
+pub(crate) struct ConnectionState {
+    url: String,
+    transport: Box<dyn Transport>,
+    events: TransportEvents,
+    backoff: Backoff,
+    status: ConnectionStatus,
+    attempt: u32,
+    retry_at: f64,
+    outbox: VecDeque<RawMessage>,
+}
+
+impl Backoff {
+    /// Never reconnect
+    pub fn none() -> Self {
+        Backoff {
+            max_attempts: Some(0),
+            ..Default::default()
+        }
+    }
+    pub fn with_initial_delay(mut self, delay_ms: f64) -> Self {
+        self.initial_delay_ms = delay_ms;
+        self
+    }
+    pub fn with_max_delay(mut self, delay_ms: f64) -> Self {
+        self.max_delay_ms = delay_ms;
+        self
+    }
+    pub fn with_factor(mut self, factor: f64) -> Self {
+        self.factor = factor;
+        self
+    }
+    pub fn with_max_attempts(mut self, attempts: u32) -> Self {
+        self.max_attempts = Some(attempts);
+        self
+    }
+    /// Delay before an attempt, counted from 1, or `None` if no attempts are left
+    pub fn delay_ms(&self, attempt: u32) -> Option<f64> {
+        if matches!(self.max_attempts, Some(max) if attempt > max) {
+            return None;
+        }
+        let growth = self.factor.powi(attempt.saturating_sub(1) as i32);
+        Some((self.initial_delay_ms * growth).min(self.max_delay_ms))
+    }
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Backoff {
+            initial_delay_ms: 500.0,
+            max_delay_ms: 30_000.0,
+            factor: 2.0,
+            max_attempts: None,
+        }
+    }
+}
+
+impl ConnectionState {
+    pub fn new(
+        url: String,
+        transport: Box<dyn Transport>,
+        events: TransportEvents,
+        backoff: Backoff,
+    ) -> Self {
+        ConnectionState {
+            url,
+            transport,
+            events,
+            backoff,
+            status: ConnectionStatus::Closed,
+            attempt: 0,
+            retry_at: 0.0,
+            outbox: VecDeque::new(),
+        }
+    }
+    pub fn status(&self) -> ConnectionStatus {
+        self.status
+    }
+    /// Start connecting
+    pub fn open(&mut self) -> PaddleResult<ConnectionStatus> {
+        self.status = ConnectionStatus::Connecting;
+        if let Err(e) = self.transport.open(&self.url, self.events.clone()) {
+            self.status = ConnectionStatus::Closed;
+            return Err(e);
+        }
+        Ok(self.status)
+    }
+    /// Send now if the connection is open, otherwise once it is
+    pub fn send(&mut self, message: RawMessage) -> PaddleResult<()> {
+        match self.status {
+            ConnectionStatus::Open => self.transport.send(&message),
+            ConnectionStatus::Closed => Ok(()),
+            _ => {
+                if self.outbox.len() == MAX_BUFFERED {
+                    self.outbox.pop_front();
+                }
+                self.outbox.push_back(message);
+                Ok(())
+            }
+        }
+    }
+    pub fn close(&mut self) -> ConnectionStatus {
+        self.transport.close();
+        self.outbox.clear();
+        self.status = ConnectionStatus::Closed;
+        self.status
+    }
+    pub fn handle(
+        &mut self,
+        event: TransportEvent,
+        now: f64,
+    ) -> PaddleResult<Option<ConnectionUpdate>> {
+        match event {
+            TransportEvent::Opened if self.status == ConnectionStatus::Connecting => {
+                self.status = ConnectionStatus::Open;
+                self.attempt = 0;
+                while let Some(message) = self.outbox.pop_front() {
+                    self.transport.send(&message)?;
+                }
+                Ok(Some(ConnectionUpdate::Status(self.status)))
+            }
+            TransportEvent::Message(message) if self.status == ConnectionStatus::Open => {
+                Ok(Some(ConnectionUpdate::Message(message)))
+            }
+            TransportEvent::Closed
+                if matches!(
+                    self.status,
+                    ConnectionStatus::Open | ConnectionStatus::Connecting
+                ) =>
+            {
+                Ok(Some(ConnectionUpdate::Status(self.lost(now))))
+            }
+            // Late events of a connection that has been replaced or closed
+            _ => Ok(None),
+        }
+    }
+    /// Reconnect once the backoff delay has passed.
+    ///
+    /// Returns the new status, if it changed, and the error of an attempt that failed right away.
+    /// Such an attempt counts like a lost connection, so the next one follows after the next backoff delay.
+    pub fn tick(&mut self, now: f64) -> (Option<ConnectionStatus>, PaddleResult<()>) {
+        match self.status {
+            ConnectionStatus::Reconnecting { .. } if now >= self.retry_at => match self.open() {
+                Ok(status) => (Some(status), Ok(())),
+                Err(e) => (Some(self.lost(now)), Err(e)),
+            },
+            _ => (None, Ok(())),
+        }
+    }
+    /// Wait for the next attempt, or give up if the backoff allows no more
+    fn lost(&mut self, now: f64) -> ConnectionStatus {
+        self.attempt += 1;
+        self.status = match self.backoff.delay_ms(self.attempt) {
+            Some(delay_ms) => {
+                self.retry_at = now + delay_ms;
+                ConnectionStatus::Reconnecting {
+                    attempt: self.attempt,
+                    delay_ms,
+                }
+            }
+            None => {
+                self.outbox.clear();
+                ConnectionStatus::Closed
+            }
+        };
+        self.status
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::EchoTransport;
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /// A connection over an echo transport, with the events it reported
+    fn echo_connection(
+        backoff: Backoff,
+    ) -> (
+        ConnectionState,
+        EchoTransport,
+        Rc<RefCell<Vec<TransportEvent>>>,
+    ) {
+        let echo = EchoTransport::new();
+        let queue = Rc::new(RefCell::new(Vec::new()));
+        let sink = queue.clone();
+        let events: TransportEvents = Rc::new(move |event| sink.borrow_mut().push(event));
+        let connection = ConnectionState::new(
+            "ws://echo".to_owned(),
+            Box::new(echo.clone()),
+            events,
+            backoff,
+        );
+        (connection, echo, queue)
+    }
+
+    fn pump(
+        connection: &mut ConnectionState,
+        queue: &Rc<RefCell<Vec<TransportEvent>>>,
+        now: f64,
+    ) -> Vec<ConnectionUpdate> {
+        let events: Vec<_> = queue.borrow_mut().drain(..).collect();
+        events
+            .into_iter()
+            .filter_map(|event| connection.handle(event, now).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn backoff_delays() {
+        let backoff = Backoff::default()
+            .with_initial_delay(100.0)
+            .with_max_delay(1000.0)
+            .with_max_attempts(6);
+        let delays: Vec<_> = (1..=7).map(|a| backoff.delay_ms(a)).collect();
+        assert_eq!(
+            delays,
+            vec![
+                Some(100.0),
+                Some(200.0),
+                Some(400.0),
+                Some(800.0),
+                Some(1000.0),
+                Some(1000.0),
+                None
+            ]
+        );
+        assert_eq!(Backoff::none().delay_ms(1), None);
+    }
+
+    #[test]
+    fn echo_and_reconnect() {
+        let backoff = Backoff::default()
+            .with_initial_delay(100.0)
+            .with_max_attempts(2);
+        let (mut connection, echo, queue) = echo_connection(backoff);
+        let hello = RawMessage::Text("hello".to_owned());
+
+        // Messages sent before the connection is open are buffered
+        connection.open().unwrap();
+        connection.send(hello.clone()).unwrap();
+        assert_eq!(
+            pump(&mut connection, &queue, 0.0),
+            vec![ConnectionUpdate::Status(ConnectionStatus::Open)]
+        );
+        assert_eq!(
+            pump(&mut connection, &queue, 0.0),
+            vec![ConnectionUpdate::Message(hello)]
+        );
+
+        echo.refuse_connections(true);
+        echo.disconnect();
+        let reconnecting = ConnectionStatus::Reconnecting {
+            attempt: 1,
+            delay_ms: 100.0,
+        };
+        assert_eq!(
+            pump(&mut connection, &queue, 1000.0),
+            vec![ConnectionUpdate::Status(reconnecting)]
+        );
+        assert_eq!(connection.tick(1050.0).0, None);
+        assert_eq!(
+            connection.tick(1100.0).0,
+            Some(ConnectionStatus::Connecting)
+        );
+        let reconnecting = ConnectionStatus::Reconnecting {
+            attempt: 2,
+            delay_ms: 200.0,
+        };
+        assert_eq!(
+            pump(&mut connection, &queue, 1100.0),
+            vec![ConnectionUpdate::Status(reconnecting)]
+        );
+
+        echo.refuse_connections(false);
+        connection.tick(1300.0).1.unwrap();
+        assert_eq!(
+            pump(&mut connection, &queue, 1300.0),
+            vec![ConnectionUpdate::Status(ConnectionStatus::Open)]
+        );
+        assert_eq!(echo.connections_opened(), 2);
+
+        // Attempts are counted again from the start after a successful reconnect
+        echo.refuse_connections(true);
+        echo.disconnect();
+        pump(&mut connection, &queue, 2000.0);
+        connection.tick(2100.0).1.unwrap();
+        pump(&mut connection, &queue, 2100.0);
+        connection.tick(2300.0).1.unwrap();
+        assert_eq!(
+            pump(&mut connection, &queue, 2300.0),
+            vec![ConnectionUpdate::Status(ConnectionStatus::Closed)]
+        );
+    }
+
+    #[test]
+    fn failed_reconnect_counts_as_attempt() {
+        let backoff = Backoff::default()
+            .with_initial_delay(100.0)
+            .with_max_attempts(2);
+        let (mut connection, echo, queue) = echo_connection(backoff);
+        connection.open().unwrap();
+        pump(&mut connection, &queue, 0.0);
+
+        echo.fail_connections(true);
+        echo.disconnect();
+        pump(&mut connection, &queue, 1000.0);
+        let (status, result) = connection.tick(1100.0);
+        assert!(result.is_err());
+        let reconnecting = ConnectionStatus::Reconnecting {
+            attempt: 2,
+            delay_ms: 200.0,
+        };
+        assert_eq!(status, Some(reconnecting));
+        assert_eq!(connection.status(), reconnecting);
+
+        // The next attempt still happens, and succeeds
+        echo.fail_connections(false);
+        assert_eq!(connection.tick(1250.0).0, None);
+        assert_eq!(
+            connection.tick(1300.0).0,
+            Some(ConnectionStatus::Connecting)
+        );
+        assert_eq!(
+            pump(&mut connection, &queue, 1300.0),
+            vec![ConnectionUpdate::Status(ConnectionStatus::Open)]
+        );
+
+        // Without attempts left, the connection is closed
+        echo.fail_connections(true);
+        echo.disconnect();
+        pump(&mut connection, &queue, 2000.0);
+        connection.tick(2100.0);
+        let (status, result) = connection.tick(2300.0);
+        assert!(result.is_err());
+        assert_eq!(status, Some(ConnectionStatus::Closed));
+        assert_eq!(connection.tick(10_000.0).0, None);
+    }
+}
@@ -0,0 +1,92 @@
+use super::connection::{ConnectionState, ConnectionUpdate};
+use super::{ConnectionChanged, ConnectionId, ConnectionStatus, RawMessage, TransportEvent};
+use crate::{share, Domain, NutsCheck, PaddleResult, UpdateWorld};
+use std::collections::HashMap;
+
+/// Requests sent to the network system by `Network` and `Connection`
+pub(super) enum NetworkCommand {
+    Connect(ConnectionId, Box<OpenConnection>),
+    Send(ConnectionId, RawMessage),
+    Close(ConnectionId),
+}
+
+/// Sent by transports, tagged with the connection they belong to
+pub(super) struct TransportMessage(pub ConnectionId, pub TransportEvent);
+
+/// A connection with the function that decodes its messages and shares them with frames
+pub(super) struct OpenConnection {
+    pub state: ConnectionState,
+    pub deliver: Box<dyn Fn(RawMessage) -> PaddleResult<()>>,
+}
+
+/// Owns all connections. Lives in `Domain::Network`.
+///
+/// Reconnections are checked on every `UpdateWorld`.
+pub(crate) struct NetworkSystem {
+    connections: HashMap<ConnectionId, OpenConnection>,
+}
+
+impl NetworkSystem {
+    pub(crate) fn init() {
+        let system = NetworkSystem {
+            connections: HashMap::new(),
+        };
+        let aid = nuts::new_domained_activity(system, &Domain::Network);
+        aid.private_channel(|a, cmd| {
+            a.command(cmd).nuts_check();
+        });
+        aid.private_channel(|a, TransportMessage(id, event)| {
+            a.transport_event(id, event).nuts_check();
+        });
+        aid.subscribe(|a, _: &UpdateWorld| {
+            a.tick(js_sys::Date::now());
+        });
+    }
+
+    fn command(&mut self, cmd: NetworkCommand) -> PaddleResult<()> {
+        match cmd {
+            NetworkCommand::Connect(id, mut connection) => {
+                let result = connection.state.open();
+                notify(id, connection.state.status());
+                self.connections.insert(id, *connection);
+                result?;
+            }
+            NetworkCommand::Send(id, message) => {
+                if let Some(connection) = self.connections.get_mut(&id) {
+                    connection.state.send(message)?;
+                }
+            }
+            NetworkCommand::Close(id) => {
+                if let Some(mut connection) = self.connections.remove(&id) {
+                    notify(id, connection.state.close());
+                }
+            }
+        }
+        Ok(())
+    }
+    fn transport_event(&mut self, id: ConnectionId, event: TransportEvent) -> PaddleResult<()> {
+        let connection = match self.connections.get_mut(&id) {
+            Some(connection) => connection,
+            None => return Ok(()),
+        };
+        match connection.state.handle(event, js_sys::Date::now())? {
+            Some(ConnectionUpdate::Status(status)) => notify(id, status),
+            Some(ConnectionUpdate::Message(message)) => (connection.deliver)(message)?,
+            None => {}
+        }
+        Ok(())
+    }
+    fn tick(&mut self, now: f64) {
+        for (id, connection) in &mut self.connections {
+            let (status, result) = connection.state.tick(now);
+            if let Some(status) = status {
+                notify(*id, status);
+            }
+            result.nuts_check();
+        }
+    }
+}
+
+fn notify(connection: ConnectionId, status: ConnectionStatus) {
+    share(ConnectionChanged { connection, status });
+}
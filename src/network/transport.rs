@@ -0,0 +1,104 @@
+use super::RawMessage;
+use crate::{ErrorMessage, PaddleResult};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Reported by a transport while a connection is open
+#[derive(Clone, Debug, PartialEq)]
+pub enum TransportEvent {
+    Opened,
+    Message(RawMessage),
+    /// The connection was lost or could not be established. Not reported after `Transport::close`.
+    Closed,
+}
+
+/// Receives the events of a transport, possibly long after the call that caused them
+pub type TransportEvents = Rc<dyn Fn(TransportEvent)>;
+
+/// Moves messages between the game and a server, for example over a WebSocket.
+///
+/// Reconnecting is handled by the network system, which calls `open` again after a connection has been lost.
+pub trait Transport {
+    /// Start opening a connection, reporting its progress and all incoming messages through `events`
+    fn open(&mut self, url: &str, events: TransportEvents) -> PaddleResult<()>;
+    /// Send a message over the open connection
+    fn send(&mut self, message: &RawMessage) -> PaddleResult<()>;
+    /// Close the connection without reporting it
+    fn close(&mut self);
+}
+
+/// A stand-in for a WebSocket server, which sends every message straight back.
+///
+/// Useful to test networking code without a server, even outside of the browser.
+/// Clones control the same connection, which allows simulating network problems after handing it to the network system.
+#[derive(Clone, Default)]
+pub struct EchoTransport {
+    inner: Rc<RefCell<EchoState>>,
+}
+
+#[derive(Default)]
+struct EchoState {
+    events: Option<TransportEvents>,
+    refuse: bool,
+    fail: bool,
+    opened: usize,
+}
+
+impl EchoTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Drop the current connection, as if the server went away
+    pub fn disconnect(&self) {
+        let events = self.inner.borrow_mut().events.take();
+        if let Some(events) = events {
+            events(TransportEvent::Closed);
+        }
+    }
+    /// Let all following attempts to connect fail, or succeed again
+    pub fn refuse_connections(&self, refuse: bool) {
+        self.inner.borrow_mut().refuse = refuse;
+    }
+    /// Let all following attempts to connect return an error right away, like for an invalid URL, or succeed again
+    pub fn fail_connections(&self, fail: bool) {
+        self.inner.borrow_mut().fail = fail;
+    }
+    pub fn is_open(&self) -> bool {
+        self.inner.borrow().events.is_some()
+    }
+    /// How many times a connection has been opened successfully
+    pub fn connections_opened(&self) -> usize {
+        self.inner.borrow().opened
+    }
+}
+
+impl Transport for EchoTransport {
+    fn open(&mut self, _url: &str, events: TransportEvents) -> PaddleResult<()> {
+        let mut state = self.inner.borrow_mut();
+        if state.fail {
+            return Err(ErrorMessage::technical(
+                "Echo transport failed to connect".to_owned(),
+            ));
+        }
+        if state.refuse {
+            drop(state);
+            events(TransportEvent::Closed);
+        } else {
+            state.events = Some(events.clone());
+            state.opened += 1;
+            drop(state);
+            events(TransportEvent::Opened);
+        }
+        Ok(())
+    }
+    fn send(&mut self, message: &RawMessage) -> PaddleResult<()> {
+        let events = self.inner.borrow().events.clone();
+        let events = events
+            .ok_or_else(|| ErrorMessage::technical("Echo transport is not connected".to_owned()))?;
+        events(TransportEvent::Message(message.clone()));
+        Ok(())
+    }
+    fn close(&mut self) {
+        self.inner.borrow_mut().events = None;
+    }
+}
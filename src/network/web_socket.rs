@@ -0,0 +1,95 @@
+use super::{RawMessage, Transport, TransportEvent, TransportEvents};
+use crate::{ErrorMessage, JsError, PaddleResult};
+use js_sys::{ArrayBuffer, Uint8Array};
+use wasm_bindgen::{closure::Closure, JsCast};
+use web_sys::{BinaryType, MessageEvent, WebSocket};
+
+/// Connects through the WebSocket API of the browser
+#[derive(Default)]
+pub struct WebSocketTransport {
+    socket: Option<WebSocket>,
+    _on_open: Option<Closure<dyn FnMut()>>,
+    _on_message: Option<Closure<dyn FnMut(MessageEvent)>>,
+    _on_close: Option<Closure<dyn FnMut()>>,
+}
+
+impl WebSocketTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Transport for WebSocketTransport {
+    fn open(&mut self, url: &str, events: TransportEvents) -> PaddleResult<()> {
+        self.close();
+        let socket = WebSocket::new(url).map_err(JsError::from_js_value)?;
+        socket.set_binary_type(BinaryType::Arraybuffer);
+
+        let on_open = {
+            let events = events.clone();
+            Closure::wrap(Box::new(move || events(TransportEvent::Opened)) as Box<dyn FnMut()>)
+        };
+        let on_message = {
+            let events = events.clone();
+            Closure::wrap(Box::new(move |msg: MessageEvent| {
+                let data = msg.data();
+                let message = match data.as_string() {
+                    Some(text) => RawMessage::Text(text),
+                    None => match data.dyn_into::<ArrayBuffer>() {
+                        Ok(buffer) => RawMessage::Binary(Uint8Array::new(&buffer).to_vec()),
+                        Err(other) => {
+                            web_sys::console::error_2(
+                                &"Unexpected WebSocket message".into(),
+                                &other,
+                            );
+                            return;
+                        }
+                    },
+                };
+                events(TransportEvent::Message(message));
+            }) as Box<dyn FnMut(MessageEvent)>)
+        };
+        // An error is always followed by a close event
+        let on_close =
+            Closure::wrap(Box::new(move || events(TransportEvent::Closed)) as Box<dyn FnMut()>);
+
+        socket.set_onopen(Some(on_open.as_ref().unchecked_ref()));
+        socket.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+        socket.set_onclose(Some(on_close.as_ref().unchecked_ref()));
+        self.socket = Some(socket);
+        self._on_open = Some(on_open);
+        self._on_message = Some(on_message);
+        self._on_close = Some(on_close);
+        Ok(())
+    }
+    fn send(&mut self, message: &RawMessage) -> PaddleResult<()> {
+        let socket = self
+            .socket
+            .as_ref()
+            .ok_or_else(|| ErrorMessage::technical("WebSocket is not connected".to_owned()))?;
+        match message {
+            RawMessage::Text(text) => socket.send_with_str(text),
+            RawMessage::Binary(data) => socket.send_with_u8_array(data),
+        }
+        .map_err(JsError::from_js_value)?;
+        Ok(())
+    }
+    fn close(&mut self) {
+        if let Some(socket) = self.socket.take() {
+            socket.set_onopen(None);
+            socket.set_onmessage(None);
+            socket.set_onclose(None);
+            // Only fails for invalid close codes
+            let _ = socket.close();
+        }
+        self._on_open = None;
+        self._on_message = None;
+        self._on_close = None;
+    }
+}
+
+impl Drop for WebSocketTransport {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
@@ -0,0 +1,184 @@
+//! Pathfinding on the grid of a `Tilemap`.
+//!
+//! A `Pathfinder` describes how units move over a map: which tiles they can enter at which cost,
+//! whether they may move diagonally and which heuristic guides the search.
+//! With it, `find_path` runs A* between two points, while `flow_field` computes the way towards a single goal
+//! from every tile at once, which is cheaper when many units share the same goal.
+//!
+//! Paths are returned in world coordinates, as the centers of the tiles to pass through.
+//!
+//! ```
+//! # use paddle::*;
+//! # use paddle::quicksilver_compat::geom::{Tile, Tilemap};
+//! let mut map: Tilemap<f32> = Tilemap::new((100, 100), (10, 10));
+//! // Swamp, which takes five times as long to cross
+//! map.set_tile((1, 0), Tile::empty(Some(5.0)));
+//! let pathfinder = Pathfinder::new(&map).with_value_costs();
+//! let path = pathfinder.find_path((5, 5), (25, 5)).unwrap();
+//! assert_eq!(path.first(), Some(&Vector::new(5, 5)));
+//! assert_eq!(path.last(), Some(&Vector::new(25, 5)));
+//! // Going around the swamp is cheaper
+//! assert!(!path.contains(&Vector::new(15, 5)));
+//! ```
+
+mod astar;
+mod flow_field;
+
+pub use flow_field::FlowField;
+
+use crate::quicksilver_compat::geom::{Neighbourhood, Tile, TileCoord, Tilemap};
+use std::cmp::Ordering;
+
+/// Estimate of the remaining cost between two tiles, used to guide A*
+#[derive(Clone, Copy, Debug)]
+pub enum Heuristic {
+    /// Sum of the horizontal and vertical distance, best without diagonal movement
+    Manhattan,
+    /// Straight line distance
+    Euclidean,
+    /// Larger of the horizontal and vertical distance
+    Chebyshev,
+    /// Exact distance with diagonal moves costing the square root of two, best with diagonal movement
+    Octile,
+    /// No estimate at all, which turns A* into Dijkstra's algorithm
+    Zero,
+    Custom(fn(TileCoord, TileCoord) -> f32),
+}
+
+impl Heuristic {
+    fn estimate(self, from: TileCoord, to: TileCoord) -> f32 {
+        let dx = (from.0 as f32 - to.0 as f32).abs();
+        let dy = (from.1 as f32 - to.1 as f32).abs();
+        match self {
+            Heuristic::Manhattan => dx + dy,
+            Heuristic::Euclidean => (dx * dx + dy * dy).sqrt(),
+            Heuristic::Chebyshev => dx.max(dy),
+            Heuristic::Octile => dx.max(dy) + (std::f32::consts::SQRT_2 - 1.0) * dx.min(dy),
+            Heuristic::Zero => 0.0,
+            Heuristic::Custom(f) => f(from, to),
+        }
+    }
+}
+
+type CostFn<'a, T> = Box<dyn Fn(&Tile<T>) -> Option<f32> + 'a>;
+
+/// Movement rules for finding paths on a `Tilemap`.
+///
+/// By default, units move in four directions and can enter all empty tiles at a cost of 1.
+/// Heuristics assume that entering a tile costs at least 1, with cheaper tiles the paths found by A* may not be the shortest.
+pub struct Pathfinder<'a, T: Clone> {
+    map: &'a Tilemap<T>,
+    heuristic: Heuristic,
+    diagonals: bool,
+    cost: CostFn<'a, T>,
+}
+
+impl<'a, T: Clone> Pathfinder<'a, T> {
+    pub fn new(map: &'a Tilemap<T>) -> Self {
+        Pathfinder {
+            map,
+            heuristic: Heuristic::Manhattan,
+            diagonals: false,
+            cost: Box::new(|tile| if tile.empty { Some(1.0) } else { None }),
+        }
+    }
+    /// Allow moving diagonally, which costs the square root of two times the cost of the entered tile.
+    ///
+    /// Units cannot cut corners, both tiles next to a diagonal step must be passable.
+    /// Also switches the heuristic to `Heuristic::Octile`.
+    pub fn with_diagonals(mut self) -> Self {
+        self.diagonals = true;
+        self.heuristic = Heuristic::Octile;
+        self
+    }
+    pub fn with_heuristic(mut self, heuristic: Heuristic) -> Self {
+        self.heuristic = heuristic;
+        self
+    }
+    /// Decide the cost of entering a tile. `None` makes the tile impassable, as do negative, infinite and NaN costs.
+    pub fn with_cost(mut self, cost: impl Fn(&Tile<T>) -> Option<f32> + 'a) -> Self {
+        self.cost = Box::new(cost);
+        self
+    }
+    /// Use the value of empty tiles as cost of entering them, empty tiles without value cost 1.
+    /// Solid tiles and tiles with a negative value are impassable.
+    pub fn with_value_costs(self) -> Self
+    where
+        T: Copy + Into<f32>,
+    {
+        self.with_cost(|tile| {
+            let cost = tile.value.map_or(1.0, Into::into);
+            if tile.empty && cost >= 0.0 {
+                Some(cost)
+            } else {
+                None
+            }
+        })
+    }
+    pub fn map(&self) -> &'a Tilemap<T> {
+        self.map
+    }
+
+    fn neighbourhood(&self) -> Neighbourhood {
+        if self.diagonals {
+            Neighbourhood::Eight
+        } else {
+            Neighbourhood::Four
+        }
+    }
+    fn tile_cost(&self, coord: TileCoord) -> Option<f32> {
+        self.map
+            .tile(coord)
+            .and_then(|tile| (self.cost)(tile))
+            // Negative costs would let searches loop forever
+            .filter(|cost| cost.is_finite() && *cost >= 0.0)
+    }
+    /// Passable neighbours of a tile with the cost of moving there
+    fn steps(&self, from: TileCoord) -> impl Iterator<Item = (TileCoord, f32)> + '_ {
+        self.map
+            .neighbours(from, self.neighbourhood())
+            .filter_map(move |to| {
+                let cost = self.tile_cost(to)?;
+                if to.0 != from.0 && to.1 != from.1 {
+                    // No cutting corners
+                    self.tile_cost((to.0, from.1))?;
+                    self.tile_cost((from.0, to.1))?;
+                    Some((to, cost * std::f32::consts::SQRT_2))
+                } else {
+                    Some((to, cost))
+                }
+            })
+    }
+    fn index(&self, coord: TileCoord) -> usize {
+        self.map.tile_index(coord)
+    }
+}
+
+/// Entry of an open list, ordered so that a `BinaryHeap` pops the lowest `priority` first
+struct Open {
+    /// Estimated total cost for A*, cost so far for searches without heuristic
+    priority: f32,
+    cost: f32,
+    coord: TileCoord,
+}
+
+impl PartialEq for Open {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+impl Eq for Open {}
+impl PartialOrd for Open {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for Open {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .priority
+            .total_cmp(&self.priority)
+            // Among equal priorities, prefer nodes closer to the goal
+            .then(self.cost.total_cmp(&other.cost))
+    }
+}
@@ -0,0 +1,161 @@
+use super::{Open, Pathfinder};
+use crate::quicksilver_compat::geom::TileCoord;
+use crate::Vector;
+use std::collections::BinaryHeap;
+
+impl<'a, T: Clone> Pathfinder<'a, T> {
+    /// Find the cheapest path between the tiles at two points.
+    ///
+    /// The path consists of the centers of all tiles on the way, including the start and the goal tile.
+    /// Returns `None` if either point is outside the map or the goal cannot be reached.
+    pub fn find_path(&self, from: impl Into<Vector>, to: impl Into<Vector>) -> Option<Vec<Vector>> {
+        let from = self.map.tile_coord(from)?;
+        let to = self.map.tile_coord(to)?;
+        let path = self.find_tile_path(from, to)?;
+        Some(
+            path.into_iter()
+                .map(|coord| self.map.tile_center(coord))
+                .collect(),
+        )
+    }
+    /// Find the cheapest path between two tiles, including both ends
+    pub fn find_tile_path(&self, from: TileCoord, to: TileCoord) -> Option<Vec<TileCoord>> {
+        if !self.map.coord_valid(from) || self.tile_cost(to).is_none() {
+            return None;
+        }
+        let tiles = self.map.columns() as usize * self.map.rows() as usize;
+        let mut cost = vec![f32::INFINITY; tiles];
+        let mut came_from: Vec<Option<TileCoord>> = vec![None; tiles];
+        let mut open = BinaryHeap::new();
+        cost[self.index(from)] = 0.0;
+        open.push(Open {
+            priority: self.heuristic.estimate(from, to),
+            cost: 0.0,
+            coord: from,
+        });
+        while let Some(current) = open.pop() {
+            if current.coord == to {
+                return Some(self.reconstruct(&came_from, to));
+            }
+            if current.cost > cost[self.index(current.coord)] {
+                // Outdated entry, the tile has been reached more cheaply since
+                continue;
+            }
+            for (next, step) in self.steps(current.coord) {
+                let next_cost = current.cost + step;
+                let index = self.index(next);
+                if next_cost < cost[index] {
+                    cost[index] = next_cost;
+                    came_from[index] = Some(current.coord);
+                    open.push(Open {
+                        priority: next_cost + self.heuristic.estimate(next, to),
+                        cost: next_cost,
+                        coord: next,
+                    });
+                }
+            }
+        }
+        None
+    }
+    fn reconstruct(&self, came_from: &[Option<TileCoord>], to: TileCoord) -> Vec<TileCoord> {
+        let mut path = vec![to];
+        let mut current = to;
+        while let Some(previous) = came_from[self.index(current)] {
+            path.push(previous);
+            current = previous;
+        }
+        path.reverse();
+        path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pathfinding::Heuristic;
+    use crate::quicksilver_compat::geom::{Tile, Tilemap};
+
+    /// 5x5 map with a wall in the middle column, open at the bottom
+    fn walled() -> Tilemap<f32> {
+        let mut map = Tilemap::new((50, 50), (10, 10));
+        for y in 0..4 {
+            map.set_tile((2, y), Tile::solid(None));
+        }
+        map
+    }
+
+    #[test]
+    fn around_wall() {
+        let map = walled();
+        let path = Pathfinder::new(&map)
+            .find_tile_path((0, 0), (4, 0))
+            .unwrap();
+        assert_eq!(path.len(), 13);
+        assert!(path.contains(&(2, 4)));
+        for pair in path.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+            assert_eq!(
+                (a.0 as i32 - b.0 as i32).abs() + (a.1 as i32 - b.1 as i32).abs(),
+                1
+            );
+        }
+
+        let diagonal = Pathfinder::new(&map).with_diagonals();
+        let path = diagonal.find_tile_path((0, 0), (4, 0)).unwrap();
+        // Diagonal steps cannot cut the corners of the wall
+        assert_eq!(path.len(), 11);
+        assert_eq!(&path[4..7], &[(1, 4), (2, 4), (3, 4)]);
+
+        let dijkstra = Pathfinder::new(&map).with_heuristic(Heuristic::Zero);
+        assert_eq!(dijkstra.find_tile_path((0, 0), (4, 0)).unwrap().len(), 13);
+    }
+
+    #[test]
+    fn unreachable() {
+        let mut map = walled();
+        map.set_tile((2, 4), Tile::solid(None));
+        let pathfinder = Pathfinder::new(&map).with_diagonals();
+        assert!(pathfinder.find_tile_path((0, 0), (4, 0)).is_none());
+        assert!(pathfinder.find_tile_path((0, 0), (2, 0)).is_none());
+        assert!(pathfinder.find_path((5, 5), (60, 5)).is_none());
+        assert_eq!(
+            pathfinder.find_path((5, 5), (5, 5)),
+            Some(vec![Vector::new(5, 5)])
+        );
+    }
+
+    #[test]
+    fn invalid_costs_are_impassable() {
+        for value in [-1.0, f32::NAN, f32::INFINITY] {
+            let mut map: Tilemap<f32> = Tilemap::new((30, 10), (10, 10));
+            map.set_tile((1, 0), Tile::empty(Some(value)));
+            // Raw values, without the checks of `with_value_costs`
+            let pathfinder = Pathfinder::new(&map).with_cost(|tile| tile.value.or(Some(1.0)));
+            assert!(pathfinder.find_tile_path((0, 0), (2, 0)).is_none());
+            assert_eq!(pathfinder.flow_field((25, 5)).cost((5, 5)), None);
+        }
+    }
+
+    #[test]
+    fn tile_costs() {
+        let mut map: Tilemap<f32> = Tilemap::new((50, 30), (10, 10));
+        // A river across the middle row with a single cheap bridge
+        for x in 0..5 {
+            map.set_tile((x, 1), Tile::empty(Some(10.0)));
+        }
+        map.set_tile((4, 1), Tile::empty(Some(1.0)));
+        let pathfinder = Pathfinder::new(&map).with_value_costs();
+        let path = pathfinder.find_tile_path((0, 0), (0, 2)).unwrap();
+        assert!(path.contains(&(4, 1)));
+        let ignoring_costs = Pathfinder::new(&map);
+        assert_eq!(
+            ignoring_costs.find_tile_path((0, 0), (0, 2)).unwrap(),
+            vec![(0, 0), (0, 1), (0, 2)]
+        );
+        let impassable = Pathfinder::new(&map).with_cost(|tile| match tile.value {
+            Some(v) if v > 5.0 => None,
+            _ => Some(1.0),
+        });
+        assert_eq!(impassable.find_tile_path((0, 0), (0, 2)).unwrap().len(), 11);
+    }
+}
@@ -0,0 +1,180 @@
+use super::{Open, Pathfinder};
+use crate::quicksilver_compat::geom::{TileCoord, TileGrid};
+use crate::Vector;
+use std::collections::BinaryHeap;
+
+/// Directions towards a common goal, for every tile of a map.
+///
+/// Computing the field once is as expensive as a single search without heuristic,
+/// afterwards any number of units can look up their next step in constant time.
+/// The field must be recomputed when the map or the goal changes.
+#[derive(Clone, Debug)]
+pub struct FlowField {
+    grid: TileGrid,
+    /// `None` if the goal is impassable
+    goal: Option<TileCoord>,
+    /// Cost of the cheapest path to the goal
+    cost: Vec<f32>,
+    /// Next tile on the cheapest path to the goal
+    next: Vec<Option<TileCoord>>,
+}
+
+impl<'a, T: Clone> Pathfinder<'a, T> {
+    /// Compute the way to the tile at `goal` from all tiles of the map.
+    ///
+    /// The heuristic is not used.
+    pub fn flow_field(&self, goal: impl Into<Vector>) -> FlowField {
+        let map = self.map;
+        let tiles = map.columns() as usize * map.rows() as usize;
+        let mut field = FlowField {
+            grid: map.grid(),
+            goal: None,
+            cost: vec![f32::INFINITY; tiles],
+            next: vec![None; tiles],
+        };
+        let goal = match map.tile_coord(goal) {
+            Some(goal) if self.tile_cost(goal).is_some() => goal,
+            _ => return field,
+        };
+        field.goal = Some(goal);
+        field.cost[self.index(goal)] = 0.0;
+        let mut open = BinaryHeap::new();
+        open.push(Open {
+            priority: 0.0,
+            cost: 0.0,
+            coord: goal,
+        });
+        while let Some(current) = open.pop() {
+            if current.cost > field.cost[self.index(current.coord)] {
+                continue;
+            }
+            // Search backwards: a unit on `previous` steps onto `current`
+            for previous in map.neighbours(current.coord, self.neighbourhood()) {
+                if self.tile_cost(previous).is_none() {
+                    continue;
+                }
+                let step = self
+                    .steps(previous)
+                    .find(|(to, _)| *to == current.coord)
+                    .map(|(_, step)| step);
+                if let Some(step) = step {
+                    let cost = current.cost + step;
+                    let index = self.index(previous);
+                    if cost < field.cost[index] {
+                        field.cost[index] = cost;
+                        field.next[index] = Some(current.coord);
+                        open.push(Open {
+                            priority: cost,
+                            cost,
+                            coord: previous,
+                        });
+                    }
+                }
+            }
+        }
+        field
+    }
+}
+
+impl FlowField {
+    /// Center of the next tile to move to from a point, `None` if the goal cannot be reached or has been reached already
+    pub fn next_waypoint(&self, pos: impl Into<Vector>) -> Option<Vector> {
+        let next = self.next[self.index(self.coord(pos)?)]?;
+        Some(self.center(next))
+    }
+    /// Unit vector pointing from a point to the next waypoint.
+    ///
+    /// On the goal tile, it points to the center of the goal.
+    pub fn direction(&self, pos: impl Into<Vector>) -> Option<Vector> {
+        let pos = pos.into();
+        let coord = self.coord(pos)?;
+        let target = if Some(coord) == self.goal {
+            self.center(coord)
+        } else {
+            self.center(self.next[self.index(coord)]?)
+        };
+        let delta = target - pos;
+        if delta.len2() == 0.0 {
+            Some(Vector::ZERO)
+        } else {
+            Some(delta.normalize())
+        }
+    }
+    /// Cost of the cheapest path from a point to the goal, `None` if it cannot be reached
+    pub fn cost(&self, pos: impl Into<Vector>) -> Option<f32> {
+        let cost = self.cost[self.index(self.coord(pos)?)];
+        if cost.is_finite() {
+            Some(cost)
+        } else {
+            None
+        }
+    }
+    /// The full path from a point to the goal, as tile centers including both ends
+    pub fn path(&self, pos: impl Into<Vector>) -> Option<Vec<Vector>> {
+        let mut coord = self.coord(pos)?;
+        if self.cost[self.index(coord)].is_infinite() {
+            return None;
+        }
+        let mut path = vec![self.center(coord)];
+        while let Some(next) = self.next[self.index(coord)] {
+            path.push(self.center(next));
+            coord = next;
+        }
+        Some(path)
+    }
+
+    fn coord(&self, pos: impl Into<Vector>) -> Option<TileCoord> {
+        self.grid.tile_coord(pos)
+    }
+    fn center(&self, coord: TileCoord) -> Vector {
+        self.grid.tile_center(coord)
+    }
+    fn index(&self, coord: TileCoord) -> usize {
+        self.grid.tile_index(coord)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quicksilver_compat::geom::{Tile, Tilemap};
+
+    #[test]
+    fn flow_around_wall() {
+        let mut map: Tilemap<()> = Tilemap::new((50, 50), (10, 10));
+        for y in 0..4 {
+            map.set_tile((2, y), Tile::solid(None));
+        }
+        let field = Pathfinder::new(&map).flow_field((45, 5));
+        assert_eq!(field.cost((45, 5)), Some(0.0));
+        assert_eq!(field.cost((5, 5)), Some(12.0));
+        assert_eq!(field.cost((25, 5)), None);
+        assert_eq!(field.next_waypoint((5, 45)), Some(Vector::new(15, 45)));
+        assert_eq!(field.direction((5, 45)), Some(Vector::new(1, 0)));
+        assert_eq!(
+            field.direction((40, 0)),
+            Some(Vector::new(5, 5).normalize())
+        );
+        assert_eq!(field.next_waypoint((45, 5)), None);
+
+        let path = field.path((5, 5)).unwrap();
+        let astar = Pathfinder::new(&map).find_path((5, 5), (45, 5)).unwrap();
+        assert_eq!(path.len(), astar.len());
+        assert_eq!(path.last(), Some(&Vector::new(45, 5)));
+
+        let diagonal = Pathfinder::new(&map).with_diagonals().flow_field((45, 5));
+        assert!(diagonal.cost((5, 5)).unwrap() < 12.0);
+        // Cannot cut the corner of the wall
+        assert_eq!(diagonal.next_waypoint((15, 35)), Some(Vector::new(15, 45)));
+    }
+
+    #[test]
+    fn unreachable_goal() {
+        let mut map: Tilemap<()> = Tilemap::new((30, 30), (10, 10));
+        map.set_tile((1, 1), Tile::solid(None));
+        let field = Pathfinder::new(&map).flow_field((15, 15));
+        assert_eq!(field.cost((5, 5)), None);
+        assert_eq!(field.direction((5, 5)), None);
+        assert_eq!(field.path((5, 5)), None);
+    }
+}
@@ -0,0 +1,29 @@
+//! Collision detection and resolution for the shapes of `quicksilver_compat`.
+//!
+//! While `Shape::overlaps` only answers whether two shapes touch, a `Manifold` also tells how to separate them.
+//! `sweep` finds the first contact of a moving shape and `PhysicsWorld` simulates rigid bodies with gravity,
+//! restitution and friction. Everything in here is independent of the browser.
+//!
+//! ```
+//! # use paddle::*;
+//! # use paddle::quicksilver_compat::Circle;
+//! let mut world = PhysicsWorld::new().with_gravity((0, 100));
+//! world.add(RigidBody::fixed(Rectangle::new((0, 100), (200, 20))));
+//! let ball = world.add(RigidBody::dynamic(Circle::new((0, 0), 10), 1.0).with_position((100, 0)));
+//! for _ in 0..120 {
+//!     world.step(1.0 / 60.0);
+//! }
+//! assert!(world.get(ball).unwrap().position.y < 91.0);
+//! ```
+
+mod body;
+mod broadphase;
+mod collider;
+mod manifold;
+mod sweep;
+
+pub use body::*;
+pub use broadphase::sweep_and_prune;
+pub use collider::Collider;
+pub use manifold::Manifold;
+pub use sweep::{sweep, Hit};
@@ -0,0 +1,323 @@
+use super::broadphase::sweep_and_prune;
+use super::collider::Collider;
+use super::manifold::Manifold;
+use crate::Vector;
+
+/// Fraction of the penetration resolved per step
+const CORRECTION_PERCENT: f32 = 0.8;
+/// Penetration tolerated without positional correction, avoids jitter of resting bodies
+const CORRECTION_SLOP: f32 = 0.01;
+
+/// Refers to a body inside a `PhysicsWorld`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BodyId(usize);
+
+/// A body moved by forces and collisions. Bodies do not rotate.
+#[derive(Clone, Debug)]
+pub struct RigidBody {
+    /// Shape of the body, relative to its position
+    pub collider: Collider,
+    pub position: Vector,
+    /// Units per second
+    pub velocity: Vector,
+    /// Bounciness, 0 for none and 1 for perfectly elastic collisions
+    pub restitution: f32,
+    /// Coulomb friction coefficient
+    pub friction: f32,
+    inv_mass: f32,
+    force: Vector,
+}
+
+impl RigidBody {
+    /// A body moved by gravity, forces and collisions
+    pub fn dynamic(collider: impl Into<Collider>, mass: f32) -> Self {
+        assert!(mass > 0.0, "Dynamic bodies must have a positive mass");
+        Self::new(collider.into(), 1.0 / mass)
+    }
+    /// A body with infinite mass that never moves on its own, like walls and floors
+    pub fn fixed(collider: impl Into<Collider>) -> Self {
+        Self::new(collider.into(), 0.0)
+    }
+    fn new(collider: Collider, inv_mass: f32) -> Self {
+        Self {
+            collider,
+            position: Vector::ZERO,
+            velocity: Vector::ZERO,
+            restitution: 0.0,
+            friction: 0.5,
+            inv_mass,
+            force: Vector::ZERO,
+        }
+    }
+    pub fn with_position(mut self, position: impl Into<Vector>) -> Self {
+        self.position = position.into();
+        self
+    }
+    pub fn with_velocity(mut self, velocity: impl Into<Vector>) -> Self {
+        self.velocity = velocity.into();
+        self
+    }
+    pub fn with_restitution(mut self, restitution: f32) -> Self {
+        self.restitution = restitution;
+        self
+    }
+    pub fn with_friction(mut self, friction: f32) -> Self {
+        self.friction = friction;
+        self
+    }
+    pub fn is_fixed(&self) -> bool {
+        self.inv_mass == 0.0
+    }
+    /// Infinite for fixed bodies
+    pub fn mass(&self) -> f32 {
+        1.0 / self.inv_mass
+    }
+    /// The collider at the current position of the body
+    pub fn shape(&self) -> Collider {
+        self.collider.translate(self.position)
+    }
+    /// Force applied continuously until the next step
+    pub fn apply_force(&mut self, force: impl Into<Vector>) {
+        self.force += force.into();
+    }
+    /// Instantly change the momentum of the body
+    pub fn apply_impulse(&mut self, impulse: impl Into<Vector>) {
+        self.velocity += impulse.into() * self.inv_mass;
+    }
+}
+
+/// Two bodies that collided during the last step
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Contact {
+    pub a: BodyId,
+    pub b: BodyId,
+    /// Normal pointing from `a` towards `b`, before the bodies have been separated
+    pub manifold: Manifold,
+}
+
+/// A set of rigid bodies simulated together.
+///
+/// Call `step` with the time passed, typically from `Frame::update`.
+#[derive(Default)]
+pub struct PhysicsWorld {
+    bodies: Vec<Option<RigidBody>>,
+    free: Vec<usize>,
+    contacts: Vec<Contact>,
+    /// Acceleration applied to all dynamic bodies, in units per second squared
+    pub gravity: Vector,
+}
+
+impl PhysicsWorld {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn with_gravity(mut self, gravity: impl Into<Vector>) -> Self {
+        self.gravity = gravity.into();
+        self
+    }
+    pub fn add(&mut self, body: RigidBody) -> BodyId {
+        if let Some(i) = self.free.pop() {
+            self.bodies[i] = Some(body);
+            BodyId(i)
+        } else {
+            self.bodies.push(Some(body));
+            BodyId(self.bodies.len() - 1)
+        }
+    }
+    /// Ids of removed bodies may be handed out again by `add`
+    pub fn remove(&mut self, id: BodyId) -> Option<RigidBody> {
+        let body = self.bodies.get_mut(id.0)?.take()?;
+        self.free.push(id.0);
+        Some(body)
+    }
+    pub fn get(&self, id: BodyId) -> Option<&RigidBody> {
+        self.bodies.get(id.0)?.as_ref()
+    }
+    pub fn get_mut(&mut self, id: BodyId) -> Option<&mut RigidBody> {
+        self.bodies.get_mut(id.0)?.as_mut()
+    }
+    pub fn bodies(&self) -> impl Iterator<Item = (BodyId, &RigidBody)> {
+        self.bodies
+            .iter()
+            .enumerate()
+            .filter_map(|(i, b)| b.as_ref().map(|b| (BodyId(i), b)))
+    }
+    /// Collisions found during the last step
+    pub fn contacts(&self) -> &[Contact] {
+        &self.contacts
+    }
+
+    /// Advance the simulation by `dt` seconds
+    pub fn step(&mut self, dt: f32) {
+        let gravity = self.gravity;
+        for body in self.bodies.iter_mut().flatten() {
+            if !body.is_fixed() {
+                // Semi-implicit Euler
+                body.velocity += (gravity + body.force * body.inv_mass) * dt;
+                body.position += body.velocity * dt;
+            }
+            body.force = Vector::ZERO;
+        }
+
+        let ids: Vec<usize> = self
+            .bodies
+            .iter()
+            .enumerate()
+            .filter_map(|(i, b)| b.as_ref().map(|_| i))
+            .collect();
+        let shapes: Vec<Collider> = ids
+            .iter()
+            .map(|&i| self.bodies[i].as_ref().unwrap().shape())
+            .collect();
+        let boxes: Vec<_> = shapes.iter().map(Collider::bounding_box).collect();
+
+        self.contacts.clear();
+        for (i, j) in sweep_and_prune(&boxes) {
+            let (a, b) = (ids[i], ids[j]);
+            if self.bodies[a].as_ref().unwrap().is_fixed()
+                && self.bodies[b].as_ref().unwrap().is_fixed()
+            {
+                continue;
+            }
+            if let Some(manifold) = shapes[i].manifold(&shapes[j]) {
+                self.resolve(a, b, &manifold);
+                self.contacts.push(Contact {
+                    a: BodyId(a),
+                    b: BodyId(b),
+                    manifold,
+                });
+            }
+        }
+    }
+
+    fn resolve(&mut self, a: usize, b: usize, m: &Manifold) {
+        let (a, b) = pair_mut(&mut self.bodies, a, b);
+        let inv_mass_sum = a.inv_mass + b.inv_mass;
+        let n = m.normal;
+
+        let relative = b.velocity - a.velocity;
+        let approaching = relative.dot(n);
+        if approaching < 0.0 {
+            let restitution = a.restitution.max(b.restitution);
+            let j = -(1.0 + restitution) * approaching / inv_mass_sum;
+            a.velocity -= n * (j * a.inv_mass);
+            b.velocity += n * (j * b.inv_mass);
+
+            // Friction along the contact surface, limited by the normal impulse
+            let relative = b.velocity - a.velocity;
+            let tangent = relative - n * relative.dot(n);
+            if tangent.len2() > 0.0 {
+                let tangent = tangent.normalize();
+                let mu = (a.friction * b.friction).sqrt();
+                let jt = (-relative.dot(tangent) / inv_mass_sum).clamp(-j * mu, j * mu);
+                a.velocity -= tangent * (jt * a.inv_mass);
+                b.velocity += tangent * (jt * b.inv_mass);
+            }
+        }
+
+        let correction =
+            n * ((m.depth - CORRECTION_SLOP).max(0.0) / inv_mass_sum * CORRECTION_PERCENT);
+        a.position -= correction * a.inv_mass;
+        b.position += correction * b.inv_mass;
+    }
+}
+
+fn pair_mut(
+    bodies: &mut [Option<RigidBody>],
+    a: usize,
+    b: usize,
+) -> (&mut RigidBody, &mut RigidBody) {
+    debug_assert!(a < b);
+    let (left, right) = bodies.split_at_mut(b);
+    (left[a].as_mut().unwrap(), right[0].as_mut().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quicksilver_compat::Circle;
+    use crate::Rectangle;
+
+    fn world_with_floor() -> (PhysicsWorld, BodyId) {
+        let mut world = PhysicsWorld::new().with_gravity((0, 100));
+        let floor = world.add(RigidBody::fixed(Rectangle::new((-500, 100), (1000, 20))));
+        (world, floor)
+    }
+
+    #[test]
+    fn falls_and_rests_on_floor() {
+        let (mut world, floor) = world_with_floor();
+        let ball = world.add(RigidBody::dynamic(Circle::new((0, 0), 10), 1.0));
+        for _ in 0..300 {
+            world.step(1.0 / 60.0);
+        }
+        let body = world.get(ball).unwrap();
+        assert!((body.position.y - 90.0).abs() < 1.0, "{}", body.position);
+        assert!(body.velocity.y.abs() < 5.0);
+        assert_eq!(world.get(floor).unwrap().position, Vector::ZERO);
+        assert_eq!(world.contacts().len(), 1);
+    }
+
+    #[test]
+    fn elastic_bounce() {
+        let mut world = PhysicsWorld::new();
+        world.add(RigidBody::fixed(Rectangle::new((-500, 100), (1000, 20))));
+        let ball = world.add(
+            RigidBody::dynamic(Circle::new((0, 0), 10), 1.0)
+                .with_position((0, 85))
+                .with_velocity((0, 600))
+                .with_restitution(1.0),
+        );
+        world.step(1.0 / 60.0);
+        let body = world.get(ball).unwrap();
+        assert_eq!(body.velocity, Vector::new(0, -600));
+    }
+
+    #[test]
+    fn momentum_is_exchanged() {
+        let mut world = PhysicsWorld::new();
+        let a = world.add(
+            RigidBody::dynamic(Circle::new((0, 0), 10), 1.0)
+                .with_velocity((60, 0))
+                .with_restitution(1.0),
+        );
+        let b = world.add(RigidBody::dynamic(Circle::new((0, 0), 10), 1.0).with_position((20, 0)));
+        world.step(1.0 / 60.0);
+        assert_eq!(world.get(a).unwrap().velocity, Vector::ZERO);
+        assert_eq!(world.get(b).unwrap().velocity, Vector::new(60, 0));
+    }
+
+    #[test]
+    fn friction_slows_sliding() {
+        let (mut world, _) = world_with_floor();
+        let rough = world.add(
+            RigidBody::dynamic(Rectangle::new((0, 0), (10, 10)), 1.0)
+                .with_position((0, 90))
+                .with_velocity((50, 0))
+                .with_friction(1.0),
+        );
+        let icy = world.add(
+            RigidBody::dynamic(Rectangle::new((0, 0), (10, 10)), 1.0)
+                .with_position((200, 90))
+                .with_velocity((50, 0))
+                .with_friction(0.0),
+        );
+        for _ in 0..60 {
+            world.step(1.0 / 60.0);
+        }
+        assert!(world.get(rough).unwrap().velocity.x < 50.0);
+        assert_eq!(world.get(icy).unwrap().velocity.x, 50.0);
+    }
+
+    #[test]
+    fn ids_are_reused() {
+        let mut world = PhysicsWorld::new();
+        let a = world.add(RigidBody::fixed(Circle::new((0, 0), 1)));
+        assert!(world.remove(a).is_some());
+        assert!(world.remove(a).is_none());
+        assert!(world.get(a).is_none());
+        let b = world.add(RigidBody::fixed(Circle::new((0, 0), 1)));
+        assert_eq!(a, b);
+        assert_eq!(world.bodies().count(), 1);
+    }
+}
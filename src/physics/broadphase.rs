@@ -0,0 +1,43 @@
+use crate::quicksilver_compat::Shape;
+use crate::Rectangle;
+
+/// Sort and sweep over the x axis.
+///
+/// Returns the index pairs `(i, j)`, with `i < j`, of all bounding boxes that overlap.
+/// These are candidates only, the exact shapes may still be apart.
+pub fn sweep_and_prune(boxes: &[Rectangle]) -> Vec<(usize, usize)> {
+    let mut order: Vec<usize> = (0..boxes.len()).collect();
+    order.sort_by(|&a, &b| boxes[a].x().partial_cmp(&boxes[b].x()).unwrap());
+    let mut active: Vec<usize> = Vec::new();
+    let mut pairs = Vec::new();
+    for i in order {
+        let current = &boxes[i];
+        active.retain(|&j| boxes[j].x() + boxes[j].width() > current.x());
+        for &j in &active {
+            if current.overlaps_rectangle(&boxes[j]) {
+                pairs.push((i.min(j), i.max(j)));
+            }
+        }
+        active.push(i);
+    }
+    pairs.sort_unstable();
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_overlapping_pairs() {
+        let boxes = [
+            Rectangle::new((0, 0), (10, 10)),
+            Rectangle::new((50, 0), (10, 10)),
+            Rectangle::new((5, 5), (10, 10)),
+            Rectangle::new((5, 50), (10, 10)),
+            Rectangle::new((-20, 0), (100, 2)),
+        ];
+        assert_eq!(sweep_and_prune(&boxes), vec![(0, 2), (0, 4), (1, 4)]);
+        assert!(sweep_and_prune(&[]).is_empty());
+    }
+}
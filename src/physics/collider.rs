@@ -0,0 +1,85 @@
+use crate::quicksilver_compat::geom::{Circle, Line, Shape, Triangle};
+use crate::{Rectangle, Vector};
+
+/// One of the shapes supported by the physics module.
+///
+/// Rectangles are axis aligned, bodies do not rotate.
+/// Lines are treated as infinitely thin segments, their thickness is ignored.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Collider {
+    Circle(Circle),
+    Rectangle(Rectangle),
+    Triangle(Triangle),
+    Line(Line),
+}
+
+/// Geometry reduced to the two cases the collision algorithms distinguish
+pub(super) enum Geometry {
+    Circle(Vector, f32),
+    /// Convex polygon, or a segment if it has only two vertices
+    Polygon(Vec<Vector>),
+}
+
+impl Collider {
+    pub fn translate(&self, v: impl Into<Vector>) -> Collider {
+        match self {
+            Collider::Circle(s) => Collider::Circle(s.translate(v)),
+            Collider::Rectangle(s) => Collider::Rectangle(s.translate(v)),
+            Collider::Triangle(s) => Collider::Triangle(s.translate(v)),
+            Collider::Line(s) => Collider::Line(s.translate(v)),
+        }
+    }
+    pub fn bounding_box(&self) -> Rectangle {
+        match self {
+            Collider::Circle(s) => s.bounding_box(),
+            Collider::Rectangle(s) => s.bounding_box(),
+            Collider::Triangle(s) => s.bounding_box(),
+            Collider::Line(s) => s.bounding_box(),
+        }
+    }
+    pub fn center(&self) -> Vector {
+        match self {
+            Collider::Circle(s) => s.center(),
+            Collider::Rectangle(s) => s.center(),
+            Collider::Triangle(s) => s.center(),
+            Collider::Line(s) => s.center(),
+        }
+    }
+    pub(super) fn geometry(&self) -> Geometry {
+        match self {
+            Collider::Circle(c) => Geometry::Circle(c.pos, c.radius),
+            Collider::Rectangle(r) => {
+                let tl = r.top_left();
+                Geometry::Polygon(vec![
+                    tl,
+                    tl + Vector::new(r.width(), 0.0),
+                    tl + r.size(),
+                    tl + Vector::new(0.0, r.height()),
+                ])
+            }
+            Collider::Triangle(t) => Geometry::Polygon(vec![t.a, t.b, t.c]),
+            Collider::Line(l) => Geometry::Polygon(vec![l.a, l.b]),
+        }
+    }
+}
+
+impl From<Circle> for Collider {
+    fn from(s: Circle) -> Self {
+        Collider::Circle(s)
+    }
+}
+impl From<Rectangle> for Collider {
+    fn from(s: Rectangle) -> Self {
+        Collider::Rectangle(s)
+    }
+}
+impl From<Triangle> for Collider {
+    fn from(s: Triangle) -> Self {
+        Collider::Triangle(s)
+    }
+}
+impl From<Line> for Collider {
+    fn from(s: Line) -> Self {
+        Collider::Line(s)
+    }
+}
@@ -0,0 +1,266 @@
+use super::collider::{Collider, Geometry};
+use crate::Vector;
+
+/// Describes how two overlapping colliders touch.
+///
+/// Moving the second collider by `normal * depth` separates the two.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Manifold {
+    /// Unit vector pointing from the first collider towards the second
+    pub normal: Vector,
+    /// Penetration depth along the normal
+    pub depth: f32,
+    /// Approximate point of contact
+    pub contact: Vector,
+}
+
+impl Manifold {
+    /// The same contact seen from the other collider
+    pub fn flipped(&self) -> Manifold {
+        Manifold {
+            normal: -self.normal,
+            ..*self
+        }
+    }
+}
+
+impl Collider {
+    /// Contact information if the two colliders overlap. Touching colliders do not overlap.
+    pub fn manifold(&self, other: &Collider) -> Option<Manifold> {
+        match (self.geometry(), other.geometry()) {
+            (Geometry::Circle(ca, ra), Geometry::Circle(cb, rb)) => circle_circle(ca, ra, cb, rb),
+            (Geometry::Circle(c, r), Geometry::Polygon(p)) => circle_polygon(c, r, &p),
+            (Geometry::Polygon(p), Geometry::Circle(c, r)) => {
+                circle_polygon(c, r, &p).map(|m| m.flipped())
+            }
+            (Geometry::Polygon(a), Geometry::Polygon(b)) => polygon_polygon(&a, &b),
+        }
+    }
+}
+
+fn circle_circle(ca: Vector, ra: f32, cb: Vector, rb: f32) -> Option<Manifold> {
+    let d = cb - ca;
+    let dist2 = d.len2();
+    let r = ra + rb;
+    if dist2 >= r * r {
+        return None;
+    }
+    let dist = dist2.sqrt();
+    let normal = if dist > 0.0 { d / dist } else { Vector::X };
+    let depth = r - dist;
+    Some(Manifold {
+        normal,
+        depth,
+        contact: ca + normal * (ra - depth / 2.0),
+    })
+}
+
+/// The circle is the first collider
+fn circle_polygon(center: Vector, radius: f32, polygon: &[Vector]) -> Option<Manifold> {
+    let closest = closest_point_on_boundary(polygon, center);
+    let d = closest - center;
+    let dist = d.len();
+    let inside = contains_point(polygon, center);
+    if !inside && dist >= radius {
+        return None;
+    }
+    let normal = if dist > 0.0 {
+        if inside {
+            -d / dist
+        } else {
+            d / dist
+        }
+    } else {
+        // Center exactly on the boundary, use the direction towards the polygon center instead
+        let towards = centroid(polygon) - center;
+        if towards.len2() > 0.0 {
+            towards.normalize()
+        } else {
+            Vector::X
+        }
+    };
+    let depth = if inside { radius + dist } else { radius - dist };
+    Some(Manifold {
+        normal,
+        depth,
+        contact: closest,
+    })
+}
+
+/// Separating axis test for convex polygons and segments
+fn polygon_polygon(a: &[Vector], b: &[Vector]) -> Option<Manifold> {
+    let mut best: Option<(f32, Vector)> = None;
+    for axis in edge_normals(a).chain(edge_normals(b)) {
+        let (a_min, a_max) = project(a, axis);
+        let (b_min, b_max) = project(b, axis);
+        // Distance to push b out of a in positive and negative axis direction
+        let forward = a_max - b_min;
+        let backward = b_max - a_min;
+        if forward <= 0.0 || backward <= 0.0 {
+            return None;
+        }
+        let (depth, normal) = if forward < backward {
+            (forward, axis)
+        } else {
+            (backward, -axis)
+        };
+        if best.is_none_or(|(d, _)| depth < d) {
+            best = Some((depth, normal));
+        }
+    }
+    let (depth, normal) = best?;
+    Some(Manifold {
+        normal,
+        depth,
+        contact: support(b, -normal),
+    })
+}
+
+fn edge_normals(polygon: &[Vector]) -> impl Iterator<Item = Vector> + '_ {
+    // A segment has a single edge, closing it would only repeat the same normal
+    let n = if polygon.len() == 2 { 1 } else { polygon.len() };
+    (0..n).filter_map(move |i| {
+        let edge = polygon[(i + 1) % polygon.len()] - polygon[i];
+        if edge.len2() > 0.0 {
+            Some(Vector::new(-edge.y, edge.x).normalize())
+        } else {
+            None
+        }
+    })
+}
+
+fn project(polygon: &[Vector], axis: Vector) -> (f32, f32) {
+    polygon
+        .iter()
+        .map(|v| v.dot(axis))
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), p| {
+            (min.min(p), max.max(p))
+        })
+}
+
+/// Vertex furthest in the given direction
+fn support(polygon: &[Vector], direction: Vector) -> Vector {
+    *polygon
+        .iter()
+        .max_by(|a, b| a.dot(direction).partial_cmp(&b.dot(direction)).unwrap())
+        .unwrap()
+}
+
+fn centroid(polygon: &[Vector]) -> Vector {
+    polygon.iter().fold(Vector::ZERO, |sum, v| sum + *v) / polygon.len() as f32
+}
+
+/// Only convex polygons with at least three vertices contain points
+fn contains_point(polygon: &[Vector], p: Vector) -> bool {
+    if polygon.len() < 3 {
+        return false;
+    }
+    let mut sign = 0.0;
+    for i in 0..polygon.len() {
+        let a = polygon[i];
+        let b = polygon[(i + 1) % polygon.len()];
+        let cross = (b - a).cross(p - a);
+        if cross != 0.0 {
+            if sign != 0.0 && cross.signum() != sign {
+                return false;
+            }
+            sign = cross.signum();
+        }
+    }
+    true
+}
+
+fn closest_point_on_boundary(polygon: &[Vector], p: Vector) -> Vector {
+    let n = if polygon.len() == 2 { 1 } else { polygon.len() };
+    (0..n)
+        .map(|i| closest_point_on_segment(polygon[i], polygon[(i + 1) % polygon.len()], p))
+        .min_by(|a, b| (*a - p).len2().partial_cmp(&(*b - p).len2()).unwrap())
+        .unwrap()
+}
+
+fn closest_point_on_segment(a: Vector, b: Vector, p: Vector) -> Vector {
+    let ab = b - a;
+    let len2 = ab.len2();
+    if len2 == 0.0 {
+        return a;
+    }
+    let t = ((p - a).dot(ab) / len2).clamp(0.0, 1.0);
+    a + ab * t
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quicksilver_compat::geom::{Circle, Line, Triangle};
+    use crate::Rectangle;
+
+    fn close(a: Vector, b: Vector) -> bool {
+        (a - b).len() < 1e-4
+    }
+
+    #[test]
+    fn circles() {
+        let a = Collider::from(Circle::new((0, 0), 2));
+        let b = Collider::from(Circle::new((3, 0), 2));
+        let m = a.manifold(&b).unwrap();
+        assert!(close(m.normal, Vector::X));
+        assert_eq!(m.depth, 1.0);
+        assert!(close(m.contact, Vector::new(1.5, 0.0)));
+        let far = Collider::from(Circle::new((4, 0), 2));
+        assert!(a.manifold(&far).is_none());
+    }
+
+    #[test]
+    fn rectangles() {
+        let a = Collider::from(Rectangle::new((0, 0), (10, 10)));
+        let b = Collider::from(Rectangle::new((8, 1), (10, 5)));
+        let m = a.manifold(&b).unwrap();
+        assert!(close(m.normal, Vector::X));
+        assert!((m.depth - 2.0).abs() < 1e-4);
+        let reverse = b.manifold(&a).unwrap();
+        assert!(close(reverse.normal, -Vector::X));
+        let touching = Collider::from(Rectangle::new((10, 0), (5, 5)));
+        assert!(a.manifold(&touching).is_none());
+    }
+
+    #[test]
+    fn circle_and_rectangle() {
+        let rect = Collider::from(Rectangle::new((0, 0), (10, 10)));
+        let circle = Collider::from(Circle::new((5, 11), 2));
+        let m = rect.manifold(&circle).unwrap();
+        assert!(close(m.normal, Vector::Y));
+        assert!((m.depth - 1.0).abs() < 1e-4);
+        assert!(close(m.contact, Vector::new(5, 10)));
+        let m = circle.manifold(&rect).unwrap();
+        assert!(close(m.normal, -Vector::Y));
+        // Center inside the rectangle
+        let deep = Collider::from(Circle::new((5, 9), 2));
+        let m = rect.manifold(&deep).unwrap();
+        assert!(close(m.normal, Vector::Y));
+        assert!((m.depth - 3.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn triangle_and_line() {
+        let tri = Collider::from(Triangle::new((0, 0), (10, 0), (0, 10)));
+        let line = Collider::from(Line::new((-5, 2), (5, 2)));
+        let m = tri.manifold(&line).unwrap();
+        assert!(m.depth > 0.0);
+        let away = Collider::from(Line::new((6, 6), (10, 10)));
+        assert!(tri.manifold(&away).is_none());
+        let rect = Collider::from(Rectangle::new((4, 4), (4, 4)));
+        let m = tri.manifold(&rect).unwrap();
+        let diagonal = Vector::new(1, 1).normalize();
+        assert!(close(m.normal, diagonal));
+        assert!((m.depth - 2.0 / 2f32.sqrt()).abs() < 1e-4);
+    }
+
+    #[test]
+    fn line_and_circle() {
+        let line = Collider::from(Line::new((0, 0), (10, 0)));
+        let circle = Collider::from(Circle::new((5, -1), 2));
+        let m = line.manifold(&circle).unwrap();
+        assert!(close(m.normal, -Vector::Y));
+        assert!((m.depth - 1.0).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,229 @@
+use super::collider::Collider;
+use crate::{Rectangle, Vector};
+
+/// First contact of a moving collider with a static one
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Hit {
+    /// Fraction of the movement, between 0 and 1, after which the colliders touch
+    pub time: f32,
+    /// Surface normal of the static collider at the contact, pointing towards the moving collider
+    pub normal: Vector,
+}
+
+const BISECTION_STEPS: usize = 16;
+
+/// Move `a` by `velocity` and find the first moment at which it touches `b`.
+///
+/// Colliders overlapping from the start report a hit at time 0.
+/// Rectangles against rectangles and circles against circles are solved exactly,
+/// other combinations are approximated with substeps small enough to not tunnel through `b`.
+/// Their number grows with the distance moved inside the bounding box of `b`, relative to the size of both colliders.
+/// Movements with an infinite or NaN velocity never hit anything.
+pub fn sweep(a: &Collider, velocity: impl Into<Vector>, b: &Collider) -> Option<Hit> {
+    let velocity = velocity.into();
+    if let Some(m) = a.manifold(b) {
+        return Some(Hit {
+            time: 0.0,
+            normal: -m.normal,
+        });
+    }
+    if velocity.len2() == 0.0 || !velocity.x.is_finite() || !velocity.y.is_finite() {
+        return None;
+    }
+    match (a, b) {
+        (Collider::Rectangle(ra), Collider::Rectangle(rb)) => sweep_rectangles(ra, velocity, rb),
+        (Collider::Circle(ca), Collider::Circle(cb)) => {
+            sweep_circles(ca.pos, ca.radius, velocity, cb.pos, cb.radius)
+        }
+        _ => sweep_substeps(a, velocity, b),
+    }
+}
+
+/// Slab test of the moving rectangle's top-left corner against the Minkowski difference
+fn sweep_rectangles(a: &Rectangle, velocity: Vector, b: &Rectangle) -> Option<Hit> {
+    let min = b.top_left() - a.size();
+    let max = b.top_left() + b.size();
+    let start = a.top_left();
+    let mut entry = f32::NEG_INFINITY;
+    let mut exit = f32::INFINITY;
+    let mut normal = Vector::ZERO;
+    for (p, v, lo, hi, axis) in [
+        (start.x, velocity.x, min.x, max.x, Vector::X),
+        (start.y, velocity.y, min.y, max.y, Vector::Y),
+    ] {
+        if v == 0.0 {
+            if p <= lo || p >= hi {
+                return None;
+            }
+            continue;
+        }
+        let (t_near, t_far, n) = if v > 0.0 {
+            ((lo - p) / v, (hi - p) / v, -axis)
+        } else {
+            ((hi - p) / v, (lo - p) / v, axis)
+        };
+        if t_near > entry {
+            entry = t_near;
+            normal = n;
+        }
+        exit = exit.min(t_far);
+    }
+    if entry >= exit || !(0.0..=1.0).contains(&entry) {
+        return None;
+    }
+    Some(Hit {
+        time: entry,
+        normal,
+    })
+}
+
+fn sweep_circles(ca: Vector, ra: f32, velocity: Vector, cb: Vector, rb: f32) -> Option<Hit> {
+    // Solve |ca + v * t - cb| = ra + rb for the smallest t
+    let d = ca - cb;
+    let r = ra + rb;
+    let a = velocity.len2();
+    let b = 2.0 * d.dot(velocity);
+    let c = d.len2() - r * r;
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    let t = (-b - discriminant.sqrt()) / (2.0 * a);
+    if !(0.0..=1.0).contains(&t) {
+        return None;
+    }
+    Some(Hit {
+        time: t,
+        normal: (d + velocity * t).normalize(),
+    })
+}
+
+fn sweep_substeps(a: &Collider, velocity: Vector, b: &Collider) -> Option<Hit> {
+    let a_bounds = a.bounding_box();
+    let b_bounds = b.bounding_box();
+    // Only the part of the movement where the bounding boxes overlap can contain a hit
+    let (first, last) = bounds_overlap(&a_bounds, velocity, &b_bounds)?;
+    // Each step moves by at most half the combined thickness, so neither collider can be skipped
+    let thickness = |bounds: &Rectangle| bounds.width().min(bounds.height());
+    let extent = (thickness(&a_bounds) + thickness(&b_bounds)).max(1.0) / 2.0;
+    let distance = velocity.len() * (last - first);
+    let steps = ((distance / extent).ceil() as usize).max(1);
+    let overlaps = |t: f32| a.translate(velocity * t).manifold(b);
+    let mut before = first;
+    for i in 1..=steps {
+        let t = first + (last - first) * i as f32 / steps as f32;
+        if let Some(mut m) = overlaps(t) {
+            let mut after = t;
+            for _ in 0..BISECTION_STEPS {
+                let mid = (before + after) / 2.0;
+                match overlaps(mid) {
+                    Some(mid_m) => {
+                        after = mid;
+                        m = mid_m;
+                    }
+                    None => before = mid,
+                }
+            }
+            return Some(Hit {
+                time: before,
+                normal: -m.normal,
+            });
+        }
+        before = t;
+    }
+    None
+}
+
+/// Fractions of the movement of `a` between which its bounding box touches the one of `b`, if it ever does
+fn bounds_overlap(a: &Rectangle, velocity: Vector, b: &Rectangle) -> Option<(f32, f32)> {
+    let min = b.top_left() - a.size();
+    let max = b.top_left() + b.size();
+    let start = a.top_left();
+    let mut first: f32 = 0.0;
+    let mut last: f32 = 1.0;
+    for (p, v, lo, hi) in [
+        (start.x, velocity.x, min.x, max.x),
+        (start.y, velocity.y, min.y, max.y),
+    ] {
+        if v == 0.0 {
+            if p < lo || p > hi {
+                return None;
+            }
+            continue;
+        }
+        let (t_lo, t_hi) = ((lo - p) / v, (hi - p) / v);
+        first = first.max(t_lo.min(t_hi));
+        last = last.min(t_lo.max(t_hi));
+    }
+    if first <= last {
+        Some((first, last))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quicksilver_compat::geom::{Circle, Line};
+
+    #[test]
+    fn rectangles() {
+        let a = Collider::from(Rectangle::new((0, 0), (10, 10)));
+        let wall = Collider::from(Rectangle::new((20, -50), (5, 100)));
+        let hit = sweep(&a, (20, 0), &wall).unwrap();
+        assert_eq!(hit.time, 0.5);
+        assert_eq!(hit.normal, -Vector::X);
+        assert!(sweep(&a, (5, 0), &wall).is_none());
+        assert!(sweep(&a, (0, 20), &wall).is_none());
+        // Fast enough to tunnel with discrete checks
+        let hit = sweep(&a, (1000, 0), &wall).unwrap();
+        assert_eq!(hit.time, 0.01);
+    }
+
+    #[test]
+    fn circles() {
+        let a = Collider::from(Circle::new((0, 0), 1));
+        let b = Collider::from(Circle::new((10, 0), 1));
+        let hit = sweep(&a, (16, 0), &b).unwrap();
+        assert_eq!(hit.time, 0.5);
+        assert_eq!(hit.normal, -Vector::X);
+        assert!(sweep(&a, (0, 16), &b).is_none());
+        assert!(sweep(&a, (-16, 0), &b).is_none());
+    }
+
+    #[test]
+    fn mixed_shapes() {
+        let ball = Collider::from(Circle::new((0, 0), 1));
+        let floor = Collider::from(Line::new((-100, 10), (100, 10)));
+        let hit = sweep(&ball, (0, 18), &floor).unwrap();
+        assert!((hit.time - 0.5).abs() < 1e-3);
+        assert!((hit.normal - -Vector::Y).len() < 1e-3);
+        assert!(sweep(&ball, (0, -18), &floor).is_none());
+    }
+
+    #[test]
+    fn fast_circle_against_line() {
+        let ball = Collider::from(Circle::new((0, 0), 1));
+        let wall = Collider::from(Line::new((500, -100), (500, 100)));
+        let hit = sweep(&ball, (1000, 0), &wall).unwrap();
+        assert!((hit.time - 0.499).abs() < 1e-3);
+        assert!((hit.normal - -Vector::X).len() < 1e-3);
+
+        // Only the part of the way near the wall is checked
+        let hit = sweep(&ball, (1e6, 0.0), &wall).unwrap();
+        assert!((hit.time - 4.99e-4).abs() < 1e-6);
+        assert!(sweep(&ball, (1e6, 1e6), &wall).is_none());
+        assert!(sweep(&ball, (f32::INFINITY, 0.0), &wall).is_none());
+        assert!(sweep(&ball, (f32::NAN, 0.0), &wall).is_none());
+    }
+
+    #[test]
+    fn overlapping_at_start() {
+        let a = Collider::from(Rectangle::new((0, 0), (10, 10)));
+        let b = Collider::from(Rectangle::new((5, 0), (10, 10)));
+        let hit = sweep(&a, (1, 0), &b).unwrap();
+        assert_eq!(hit.time, 0.0);
+        assert_eq!(hit.normal, -Vector::X);
+    }
+}
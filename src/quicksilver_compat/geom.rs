@@ -14,8 +14,8 @@ mod tilemap;
 mod util;
 pub use self::{
     circle::Circle,
-    objects::{Line, Triangle},
+    objects::{Line, Polygon, Triangle},
     shape::Shape,
-    tilemap::{Tile, Tilemap},
+    tilemap::{Neighbourhood, Tile, TileCoord, TileGrid, Tilemap},
     util::{about_equal, lerp, lerp_angle},
 };
@@ -1,4 +1,5 @@
 mod line;
+mod polygon;
 mod triangle;
 
-pub use self::{line::Line, triangle::Triangle};
+pub use self::{line::Line, polygon::Polygon, triangle::Triangle};
@@ -0,0 +1,265 @@
+use super::{Line, Triangle};
+use crate::Vector;
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(try_from = "PolygonCorners")]
+///A simple polygon, convex or concave, defined by its corners in order
+///
+///The winding order does not matter, but edges must not cross each other.
+pub struct Polygon {
+    ///The corners of the polygon, the last one connects back to the first one. Always at least three.
+    vertices: Vec<Vector>,
+}
+
+///Deserialized corners, checked before they become a polygon
+#[derive(Deserialize)]
+struct PolygonCorners {
+    vertices: Vec<Vector>,
+}
+
+impl TryFrom<PolygonCorners> for Polygon {
+    type Error = String;
+    fn try_from(corners: PolygonCorners) -> Result<Self, Self::Error> {
+        if corners.vertices.len() < 3 {
+            return Err(format!(
+                "A polygon needs at least three corners, got {}",
+                corners.vertices.len()
+            ));
+        }
+        Ok(Polygon {
+            vertices: corners.vertices,
+        })
+    }
+}
+
+impl Polygon {
+    ///Create a polygon from its corners, in clockwise or counter-clockwise order
+    pub fn new<V: Into<Vector>>(vertices: impl IntoIterator<Item = V>) -> Polygon {
+        let vertices: Vec<Vector> = vertices.into_iter().map(Into::into).collect();
+        assert!(
+            vertices.len() >= 3,
+            "A polygon needs at least three corners"
+        );
+        Polygon { vertices }
+    }
+    ///The corners of the polygon, the last one connects back to the first one
+    pub fn vertices(&self) -> &[Vector] {
+        &self.vertices
+    }
+    ///Move each corner to a new position
+    pub(crate) fn map_vertices(&self, f: impl Fn(Vector) -> Vector) -> Polygon {
+        Polygon {
+            vertices: self.vertices.iter().map(|v| f(*v)).collect(),
+        }
+    }
+    ///Create a regular polygon, like a hexagon, with the first corner pointing to the right
+    pub fn regular(center: impl Into<Vector>, radius: f32, corners: usize) -> Polygon {
+        let center = center.into();
+        Polygon::new(
+            (0..corners)
+                .map(|i| center + Vector::from_angle(i as f32 * 360.0 / corners as f32) * radius),
+        )
+    }
+    ///Calculate the area of the polygon
+    pub fn area(&self) -> f32 {
+        self.signed_area().abs()
+    }
+    ///Positive for clockwise polygons on screen, where the y axis points down
+    fn signed_area(&self) -> f32 {
+        self.edge_points().map(|(a, b)| a.cross(b)).sum::<f32>() / 2.0
+    }
+    ///Whether all inner angles are at most 180 degrees
+    pub fn is_convex(&self) -> bool {
+        let n = self.vertices.len();
+        let mut sign = 0.0;
+        for i in 0..n {
+            let a = self.vertices[i];
+            let b = self.vertices[(i + 1) % n];
+            let c = self.vertices[(i + 2) % n];
+            let cross = (b - a).cross(c - b);
+            if cross != 0.0 {
+                if sign != 0.0 && cross.signum() != sign {
+                    return false;
+                }
+                sign = cross.signum();
+            }
+        }
+        true
+    }
+    ///Iterate the edges of the polygon
+    pub fn edges(&self) -> impl Iterator<Item = Line> + '_ {
+        self.edge_points().map(|(a, b)| Line::new(a, b))
+    }
+    fn edge_points(&self) -> impl Iterator<Item = (Vector, Vector)> + '_ {
+        let n = self.vertices.len();
+        (0..n).map(move |i| (self.vertices[i], self.vertices[(i + 1) % n]))
+    }
+    ///Split the polygon into triangles, using ear clipping
+    pub fn triangulate(&self) -> Vec<Triangle> {
+        let mut remaining = self.vertices.clone();
+        if self.signed_area() < 0.0 {
+            remaining.reverse();
+        }
+        let mut triangles = Vec::with_capacity(remaining.len() - 2);
+        while remaining.len() > 3 {
+            let n = remaining.len();
+            let ear = (0..n)
+                .find(|&i| is_ear(&remaining, i))
+                // Only degenerate polygons have no ears, cut anywhere to terminate
+                .unwrap_or(0);
+            let prev = remaining[(ear + n - 1) % n];
+            let next = remaining[(ear + 1) % n];
+            triangles.push(Triangle::new(prev, remaining[ear], next));
+            remaining.remove(ear);
+        }
+        triangles.push(Triangle::new(remaining[0], remaining[1], remaining[2]));
+        triangles
+    }
+    ///The corners of convex pieces that together form the polygon
+    pub(crate) fn convex_parts(&self) -> Vec<Vec<Vector>> {
+        if self.is_convex() {
+            vec![self.vertices.clone()]
+        } else {
+            self.triangulate()
+                .into_iter()
+                .map(|t| vec![t.a, t.b, t.c])
+                .collect()
+        }
+    }
+    ///If any area is bounded by both the polygon and the convex shape with the given corners
+    pub(crate) fn overlaps_convex(&self, corners: &[Vector]) -> bool {
+        self.convex_parts()
+            .iter()
+            .any(|part| convex_overlap(part, corners))
+    }
+}
+
+///A vertex is an ear if its inner angle is convex and no other vertex lies in the triangle it forms with its neighbours
+///
+///Expects clockwise vertices (on screen, y pointing down)
+fn is_ear(vertices: &[Vector], i: usize) -> bool {
+    let n = vertices.len();
+    let prev = vertices[(i + n - 1) % n];
+    let cur = vertices[i];
+    let next = vertices[(i + 1) % n];
+    if (cur - prev).cross(next - cur) <= 0.0 {
+        return false;
+    }
+    let triangle = [prev, cur, next];
+    vertices
+        .iter()
+        .enumerate()
+        .filter(|&(j, _)| j != i && j != (i + n - 1) % n && j != (i + 1) % n)
+        .all(|(_, &v)| !in_triangle(triangle, v))
+}
+
+fn in_triangle([a, b, c]: [Vector; 3], p: Vector) -> bool {
+    (b - a).cross(p - a) >= 0.0 && (c - b).cross(p - b) >= 0.0 && (a - c).cross(p - c) >= 0.0
+}
+
+///Separating axis test for two convex shapes, touching shapes do not overlap
+fn convex_overlap(a: &[Vector], b: &[Vector]) -> bool {
+    let axes = |corners: &[Vector]| -> Vec<Vector> {
+        (0..corners.len())
+            .map(|i| corners[(i + 1) % corners.len()] - corners[i])
+            .filter(|edge| edge.len2() > 0.0)
+            .map(|edge| Vector::new(-edge.y, edge.x))
+            .collect()
+    };
+    let project = |corners: &[Vector], axis: Vector| {
+        corners
+            .iter()
+            .map(|c| c.dot(axis))
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), p| {
+                (min.min(p), max.max(p))
+            })
+    };
+    axes(a).into_iter().chain(axes(b)).all(|axis| {
+        let (a_min, a_max) = project(a, axis);
+        let (b_min, b_max) = project(b, axis);
+        a_max > b_min && b_max > a_min
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quicksilver_compat::geom::{Circle, Shape, Tilemap};
+    use crate::Rectangle;
+
+    /// An L shape covering the top and left side of a 3x3 square
+    fn l_shape() -> Polygon {
+        Polygon::new(vec![(0, 0), (3, 0), (3, 1), (1, 1), (1, 3), (0, 3)])
+    }
+
+    #[test]
+    fn area_and_convexity() {
+        let square = Polygon::new(vec![(0, 0), (2, 0), (2, 2), (0, 2)]);
+        assert_eq!(square.area(), 4.0);
+        assert!(square.is_convex());
+        let l = l_shape();
+        assert_eq!(l.area(), 5.0);
+        assert!(!l.is_convex());
+        let mut reversed = l.clone();
+        reversed.vertices.reverse();
+        assert_eq!(reversed.area(), 5.0);
+    }
+
+    #[test]
+    fn triangulate() {
+        for polygon in [l_shape(), Polygon::regular((0, 0), 10.0, 7)] {
+            let triangles = polygon.triangulate();
+            assert_eq!(triangles.len(), polygon.vertices.len() - 2);
+            let total: f32 = triangles.iter().map(|t| t.area()).sum();
+            assert!((total - polygon.area()).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn deserialize() {
+        let l = l_shape();
+        let json = serde_json::to_string(&l).unwrap();
+        assert_eq!(serde_json::from_str::<Polygon>(&json).unwrap(), l);
+        assert!(serde_json::from_str::<Polygon>(r#"{"vertices":[]}"#).is_err());
+    }
+
+    #[test]
+    fn contains() {
+        let l = l_shape();
+        assert!(l.contains((0.5, 2.5)));
+        assert!(l.contains((2.5, 0.5)));
+        assert!(l.contains((3, 0.5)));
+        assert!(!l.contains((2, 2)));
+        assert!(!l.contains((-1, 0.5)));
+    }
+
+    #[test]
+    fn overlaps() {
+        let l = l_shape();
+        assert!(l.overlaps(&Rectangle::new((2, 0.5), (2, 2))));
+        assert!(!l.overlaps(&Rectangle::new((1.5, 1.5), (1, 1))));
+        assert!(l.overlaps(&Circle::new((3, 3), 2.5)));
+        assert!(!l.overlaps(&Circle::new((2.5, 2.5), 1)));
+        assert!(l.overlaps(&Line::new((2, 2), (2, -2))));
+        assert!(!l.overlaps(&Line::new((2, 2), (4, 2))));
+        assert!(l.overlaps(&Triangle::new((2, 0.5), (4, 0.5), (4, 4))));
+        assert!(l.overlaps(&Vector::new(0.5, 0.5)));
+        let square = Polygon::new(vec![(1.5, 1.5), (2.5, 1.5), (2.5, 2.5), (1.5, 2.5)]);
+        assert!(!l.overlaps(&square));
+        assert!(l.overlaps(&square.translate((-1, 0))));
+        // The other direction goes through the double dispatch of the other shapes
+        assert!(Rectangle::new((2, 0.5), (2, 2)).overlaps(&l));
+        assert!(!Circle::new((2.5, 2.5), 1).overlaps(&l));
+    }
+
+    #[test]
+    fn tilemap() {
+        let mut map: Tilemap<()> = Tilemap::new((10, 10), (1, 1));
+        map.set((2, 2), crate::quicksilver_compat::geom::Tile::solid(None));
+        let l = l_shape();
+        assert!(map.shape_empty(&l.translate((1, 1))));
+        assert!(!map.shape_empty(&l.translate((2, 1))));
+    }
+}
@@ -1,4 +1,4 @@
-use super::{about_equal, Circle, Line, Triangle};
+use super::{about_equal, Circle, Line, Polygon, Triangle};
 use crate::geometry::{Rectangle, Transform, Vector};
 /// The collision and positional attributes of shapes
 pub trait Shape {
@@ -20,6 +20,11 @@ pub trait Shape {
     fn overlaps_rectangle(&self, rectangle: &Rectangle) -> bool {
         self.overlaps(rectangle)
     }
+    /// If any area is bounded by both the shape and the polygon
+    #[must_use]
+    fn overlaps_polygon(&self, polygon: &Polygon) -> bool {
+        self.overlaps(polygon)
+    }
     /// If any area is bounded by both either shape
     #[must_use]
     fn overlaps(&self, other: &impl Shape) -> bool;
@@ -182,6 +187,9 @@ impl Shape for Triangle {
             || Line::new(self.b, self.c).overlaps_rectangle(rect)
             || Line::new(self.c, self.a).overlaps_rectangle(rect)
     }
+    fn overlaps_polygon(&self, polygon: &Polygon) -> bool {
+        polygon.overlaps_convex(&[self.a, self.b, self.c])
+    }
     fn overlaps(&self, other: &impl Shape) -> bool {
         self.contains(other.center())
             || other.intersects(&Line::new(self.a, self.b))
@@ -278,11 +286,79 @@ impl Shape for Line {
         Line {
             a: self.a + v,
             b: self.b + v,
-            t: self.t,
+            ..*self
         }
     }
 }
 
+impl Shape for Polygon {
+    fn contains(&self, v: impl Into<Vector>) -> bool {
+        let v = v.into();
+        if self.edges().any(|edge| edge.contains(v)) {
+            return true;
+        }
+        // Count edges crossed by a ray from the point to the right
+        self.edges()
+            .filter(|edge| (edge.a.y > v.y) != (edge.b.y > v.y))
+            .filter(|edge| {
+                let x = edge.a.x + (v.y - edge.a.y) / (edge.b.y - edge.a.y) * (edge.b.x - edge.a.x);
+                v.x < x
+            })
+            .count()
+            % 2
+            == 1
+    }
+    fn intersects(&self, line: &Line) -> bool {
+        self.contains(line.a) || self.edges().any(|edge| edge.intersects(line))
+    }
+    fn overlaps_circle(&self, circle: &Circle) -> bool {
+        self.contains(circle.center()) || self.edges().any(|edge| edge.overlaps_circle(circle))
+    }
+    fn overlaps_rectangle(&self, rect: &Rectangle) -> bool {
+        let top_left = rect.top_left();
+        self.overlaps_convex(&[
+            top_left,
+            top_left + Vector::new(rect.width(), 0.0),
+            top_left + rect.size(),
+            top_left + Vector::new(0.0, rect.height()),
+        ])
+    }
+    fn overlaps_polygon(&self, polygon: &Polygon) -> bool {
+        polygon
+            .convex_parts()
+            .iter()
+            .any(|part| self.overlaps_convex(part))
+    }
+    fn overlaps(&self, shape: &impl Shape) -> bool {
+        shape.overlaps_polygon(self)
+    }
+
+    fn center(&self) -> Vector {
+        // Centroid of the area, as sum over the triangles formed by each edge and the origin
+        let (weighted, doubled_area) =
+            self.edges().fold((Vector::ZERO, 0.0), |(sum, area), edge| {
+                let cross = edge.a.cross(edge.b);
+                (sum + (edge.a + edge.b) * cross, area + cross)
+            });
+        if doubled_area == 0.0 {
+            let vertices = self.vertices();
+            vertices.iter().fold(Vector::ZERO, |sum, v| sum + *v) / vertices.len() as f32
+        } else {
+            weighted / (3.0 * doubled_area)
+        }
+    }
+    fn bounding_box(&self) -> Rectangle {
+        let vertices = self.vertices();
+        let min = vertices.iter().fold(vertices[0], |min, v| min.min(*v));
+        let max = vertices.iter().fold(vertices[0], |max, v| max.max(*v));
+        Rectangle::new(min, max - min)
+    }
+    fn translate(&self, v: impl Into<Vector>) -> Self {
+        let v = v.into();
+        self.map_vertices(|p| p + v)
+    }
+}
+
 impl Shape for Vector {
     fn contains(&self, v: impl Into<Vector>) -> bool {
         *self == v.into()
@@ -1,8 +1,39 @@
 use super::Shape;
 use crate::{Rectangle, Vector};
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 use std::ops::Fn;
 
+///Column and row of a tile, counted from the top left
+pub type TileCoord = (u32, u32);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+///Which tiles count as adjacent to a tile
+pub enum Neighbourhood {
+    ///The tiles sharing an edge
+    Four,
+    ///The tiles sharing an edge or a corner
+    Eight,
+}
+
+impl Neighbourhood {
+    fn offsets(self) -> &'static [(i64, i64)] {
+        match self {
+            Neighbourhood::Four => &[(0, -1), (1, 0), (0, 1), (-1, 0)],
+            Neighbourhood::Eight => &[
+                (0, -1),
+                (1, -1),
+                (1, 0),
+                (1, 1),
+                (0, 1),
+                (-1, 1),
+                (-1, 0),
+                (-1, -1),
+            ],
+        }
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 ///An individual tile
 pub struct Tile<T: Clone> {
@@ -30,8 +61,77 @@ impl<T: Clone> Tile<T> {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+///The layout of the tiles of a map, without the tiles themselves
+///
+///Useful to keep the geometry of a map around without borrowing it.
+pub struct TileGrid {
+    map_size: Vector,
+    tile_size: Vector,
+}
+
+impl TileGrid {
+    ///Create the layout of a map with the given size, divided into tiles of the given size
+    pub fn new(map_size: impl Into<Vector>, tile_size: impl Into<Vector>) -> TileGrid {
+        TileGrid {
+            map_size: map_size.into(),
+            tile_size: tile_size.into(),
+        }
+    }
+
+    ///Get the size of a tile
+    pub fn tile_size(&self) -> Vector {
+        self.tile_size
+    }
+
+    ///Get the number of tiles in a row
+    pub fn columns(&self) -> u32 {
+        (self.map_size.x / self.tile_size.x) as u32
+    }
+
+    ///Get the number of tiles in a column
+    pub fn rows(&self) -> u32 {
+        (self.map_size.y / self.tile_size.y) as u32
+    }
+
+    ///Check if a tile coordinate is within the map bounds
+    pub fn coord_valid(&self, coord: TileCoord) -> bool {
+        coord.0 < self.columns() && coord.1 < self.rows()
+    }
+
+    ///Get the coordinate of the tile found at a given point, if it is valid
+    pub fn tile_coord(&self, point: impl Into<Vector>) -> Option<TileCoord> {
+        let point = point.into();
+        if point.x < 0.0 || point.y < 0.0 {
+            return None;
+        }
+        let coord = (
+            (point.x / self.tile_size.x) as u32,
+            (point.y / self.tile_size.y) as u32,
+        );
+        if self.coord_valid(coord) {
+            Some(coord)
+        } else {
+            None
+        }
+    }
+
+    ///Get the center of a tile
+    pub fn tile_center(&self, coord: TileCoord) -> Vector {
+        (Vector::new(coord.0, coord.1) + Vector::ONE * 0.5).times(self.tile_size)
+    }
+
+    ///Get the position of a tile in a list of all tiles, stored row by row
+    pub fn tile_index(&self, coord: TileCoord) -> usize {
+        coord.1 as usize * self.columns() as usize + coord.0 as usize
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 ///A grid of Tile values
+///
+///Tiles can be addressed by a point in world space or by their column and row.
+///They are stored row by row.
 pub struct Tilemap<T: Clone> {
     data: Vec<Tile<T>>,
     map_size: Vector,
@@ -42,16 +142,16 @@ impl<T: Clone> Tilemap<T> {
     ///Create a map full of empty, non-solid tiles of a given size
     pub fn new(map_size: impl Into<Vector>, tile_size: impl Into<Vector>) -> Tilemap<T> {
         let (map_size, tile_size) = (map_size.into(), tile_size.into());
-        let data =
-            vec![Tile::empty(None); (map_size.x / tile_size.x * map_size.y / tile_size.y) as usize];
-        Tilemap {
-            data,
+        let mut map = Tilemap {
+            data: Vec::new(),
             map_size,
             tile_size,
-        }
+        };
+        map.data = vec![Tile::empty(None); map.columns() as usize * map.rows() as usize];
+        map
     }
 
-    ///Create a map with pre-filled data
+    ///Create a map with pre-filled data, given row by row
     pub fn with_data(
         data: Vec<Tile<T>>,
         map_size: impl Into<Vector>,
@@ -99,50 +199,216 @@ impl<T: Clone> Tilemap<T> {
         self.tile_size
     }
 
+    ///Get the layout of the tiles
+    pub fn grid(&self) -> TileGrid {
+        TileGrid::new(self.map_size, self.tile_size)
+    }
+
+    ///Get the number of tiles in a row
+    pub fn columns(&self) -> u32 {
+        self.grid().columns()
+    }
+
+    ///Get the number of tiles in a column
+    pub fn rows(&self) -> u32 {
+        self.grid().rows()
+    }
+
+    ///Check if a tile coordinate is within the map bounds
+    pub fn coord_valid(&self, coord: TileCoord) -> bool {
+        self.grid().coord_valid(coord)
+    }
+
+    ///Get the coordinate of the tile found at a given point, if it is valid
+    pub fn tile_coord(&self, point: impl Into<Vector>) -> Option<TileCoord> {
+        self.grid().tile_coord(point)
+    }
+
+    ///Get the region a tile takes up
+    pub fn tile_area(&self, coord: TileCoord) -> Rectangle {
+        Rectangle::new(
+            Vector::new(coord.0, coord.1).times(self.tile_size),
+            self.tile_size,
+        )
+    }
+
     ///Check if a point is within the map bounds
     pub fn valid(&self, index: impl Into<Vector>) -> bool {
         self.region().contains(index.into())
     }
 
+    ///Get the center of a tile
+    pub fn tile_center(&self, coord: TileCoord) -> Vector {
+        self.grid().tile_center(coord)
+    }
+
     ///Checks if a shape is valid in its entirety
     pub fn shape_valid(&self, shape: impl Shape) -> bool {
         let bbox = shape.bounding_box();
         self.valid(bbox.top_left()) && self.valid(bbox.top_left() + bbox.size())
     }
 
-    fn array_index(&self, index: Vector) -> usize {
-        ((index.x / self.tile_width()).trunc() * (self.height() / self.tile_height()).trunc()
-            + (index.y / self.tile_height()).trunc()) as usize
+    ///Get the position of a tile in the list of all tiles, stored row by row
+    pub fn tile_index(&self, coord: TileCoord) -> usize {
+        self.grid().tile_index(coord)
     }
 
     ///Get the tile found at a given point, if it is valid
     pub fn get(&self, index: impl Into<Vector>) -> Option<&Tile<T>> {
-        let index = index.into();
-        if self.valid(index) {
-            Some(&self.data[self.array_index(index)])
+        self.tile(self.tile_coord(index)?)
+    }
+
+    ///Get a mutable reference to a tile at a given point, if it is valid
+    pub fn get_mut(&mut self, index: impl Into<Vector>) -> Option<&mut Tile<T>> {
+        let coord = self.tile_coord(index)?;
+        self.tile_mut(coord)
+    }
+
+    ///Set the value at a given point
+    pub fn set(&mut self, index: impl Into<Vector>, value: Tile<T>) {
+        if let Some(tile) = self.get_mut(index) {
+            *tile = value;
+        }
+    }
+
+    ///Get the tile at a given column and row, if it is valid
+    pub fn tile(&self, coord: TileCoord) -> Option<&Tile<T>> {
+        if self.coord_valid(coord) {
+            self.data.get(self.tile_index(coord))
         } else {
             None
         }
     }
 
-    ///Get a mutable reference to a tile at a given point, if it is valid
-    pub fn get_mut(&mut self, index: impl Into<Vector>) -> Option<&mut Tile<T>> {
-        let index = index.into();
-        if self.valid(index) {
-            let index = self.array_index(index);
-            Some(&mut self.data[index])
+    ///Get a mutable reference to the tile at a given column and row, if it is valid
+    pub fn tile_mut(&mut self, coord: TileCoord) -> Option<&mut Tile<T>> {
+        if self.coord_valid(coord) {
+            let index = self.tile_index(coord);
+            self.data.get_mut(index)
         } else {
             None
         }
     }
 
-    ///Set the value at a given point
-    pub fn set(&mut self, index: impl Into<Vector>, value: Tile<T>) {
-        let index = index.into();
-        match self.get_mut(index) {
-            Some(tile) => *tile = value,
-            None => (),
+    ///Set the value at a given column and row
+    pub fn set_tile(&mut self, coord: TileCoord, value: Tile<T>) {
+        if let Some(tile) = self.tile_mut(coord) {
+            *tile = value;
+        }
+    }
+
+    ///Find if the tile at a given column and row is empty
+    pub fn tile_empty(&self, coord: TileCoord) -> bool {
+        match self.tile(coord) {
+            Some(tile) => tile.empty,
+            None => false,
+        }
+    }
+
+    ///Iterate over all tiles with their coordinates, row by row
+    pub fn tiles(&self) -> impl Iterator<Item = (TileCoord, &Tile<T>)> {
+        let columns = self.columns().max(1);
+        self.data
+            .iter()
+            .enumerate()
+            .map(move |(i, tile)| ((i as u32 % columns, i as u32 / columns), tile))
+    }
+
+    ///Iterate mutably over all tiles with their coordinates, row by row
+    pub fn tiles_mut(&mut self) -> impl Iterator<Item = (TileCoord, &mut Tile<T>)> {
+        let columns = self.columns().max(1);
+        self.data
+            .iter_mut()
+            .enumerate()
+            .map(move |(i, tile)| ((i as u32 % columns, i as u32 / columns), tile))
+    }
+
+    ///Iterate over the coordinates of all tiles overlapping a region, row by row
+    pub fn coords_in(&self, region: &Rectangle) -> impl Iterator<Item = TileCoord> {
+        let start = region.pos.times(self.tile_size.recip());
+        let end = (region.pos + region.size).times(self.tile_size.recip());
+        let clamp = |v: f32, limit: u32| (v.max(0.0) as u32).min(limit);
+        let (x_start, y_start) = (
+            clamp(start.x.floor(), self.columns()),
+            clamp(start.y.floor(), self.rows()),
+        );
+        let (x_end, y_end) = (
+            clamp(end.x.ceil(), self.columns()),
+            clamp(end.y.ceil(), self.rows()),
+        );
+        (y_start..y_end).flat_map(move |y| (x_start..x_end).map(move |x| (x, y)))
+    }
+
+    ///Iterate over all tiles overlapping a region, with their coordinates
+    pub fn tiles_in<'a>(
+        &'a self,
+        region: &Rectangle,
+    ) -> impl Iterator<Item = (TileCoord, &'a Tile<T>)> + 'a {
+        self.coords_in(region)
+            .filter_map(move |coord| self.tile(coord).map(|tile| (coord, tile)))
+    }
+
+    ///Iterate over the valid neighbours of a tile
+    pub fn neighbours(
+        &self,
+        coord: TileCoord,
+        neighbourhood: Neighbourhood,
+    ) -> impl Iterator<Item = TileCoord> {
+        let (columns, rows) = (self.columns() as i64, self.rows() as i64);
+        neighbourhood.offsets().iter().filter_map(move |(dx, dy)| {
+            let (x, y) = (coord.0 as i64 + dx, coord.1 as i64 + dy);
+            if x >= 0 && y >= 0 && x < columns && y < rows {
+                Some((x as u32, y as u32))
+            } else {
+                None
+            }
+        })
+    }
+
+    ///Find all tiles connected to a start tile through tiles matching a predicate
+    ///
+    ///The tiles are returned in breadth-first order, starting with the start tile.
+    ///If the start tile is invalid or does not match, nothing is returned.
+    ///Use `|tile| tile.empty` as predicate to find the area reachable by movement.
+    pub fn flood_fill(
+        &self,
+        start: TileCoord,
+        neighbourhood: Neighbourhood,
+        predicate: impl Fn(&Tile<T>) -> bool,
+    ) -> Vec<TileCoord> {
+        let matches = |coord| self.tile(coord).is_some_and(&predicate);
+        let mut result = Vec::new();
+        if !matches(start) {
+            return result;
+        }
+        let mut visited = vec![false; self.data.len()];
+        let mut queue = VecDeque::new();
+        visited[self.tile_index(start)] = true;
+        queue.push_back(start);
+        while let Some(coord) = queue.pop_front() {
+            result.push(coord);
+            for next in self.neighbours(coord, neighbourhood) {
+                let index = self.tile_index(next);
+                if !visited[index] && matches(next) {
+                    visited[index] = true;
+                    queue.push_back(next);
+                }
+            }
         }
+        result
+    }
+
+    ///Check if no solid tile lies on the straight line between two tiles
+    ///
+    ///The line is rasterized with Bresenham's algorithm.
+    ///The start and end tiles themselves are not checked, so a solid target can still be seen.
+    pub fn line_of_sight(&self, from: TileCoord, to: TileCoord) -> bool {
+        if !self.coord_valid(from) || !self.coord_valid(to) {
+            return false;
+        }
+        bresenham(from, to)
+            .filter(|coord| *coord != from && *coord != to)
+            .all(|coord| self.tile_empty(coord))
     }
 
     ///Find if a point's tile is empty
@@ -252,6 +518,36 @@ impl<T: Clone> Tilemap<T> {
     }
 }
 
+///The tiles on a line between two tiles, including both ends
+fn bresenham(from: TileCoord, to: TileCoord) -> impl Iterator<Item = TileCoord> {
+    let (mut x, mut y) = (from.0 as i64, from.1 as i64);
+    let (x_end, y_end) = (to.0 as i64, to.1 as i64);
+    let (dx, dy) = ((x_end - x).abs(), -(y_end - y).abs());
+    let (step_x, step_y) = ((x_end - x).signum(), (y_end - y).signum());
+    let mut error = dx + dy;
+    let mut done = false;
+    std::iter::from_fn(move || {
+        if done {
+            return None;
+        }
+        let current = (x as u32, y as u32);
+        if x == x_end && y == y_end {
+            done = true;
+        } else {
+            let e2 = 2 * error;
+            if e2 >= dy {
+                error += dy;
+                x += step_x;
+            }
+            if e2 <= dx {
+                error += dx;
+                y += step_y;
+            }
+        }
+        Some(current)
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -361,4 +657,86 @@ mod tests {
             assert_eq!(speed_new, expected_speed);
         }
     }
+
+    ///A 5x3 map with a wall in the middle column, open at the bottom
+    fn walled() -> Tilemap<i32> {
+        let mut map = Tilemap::new((50, 30), (10, 10));
+        map.set_tile((2, 0), Tile::solid(None));
+        map.set_tile((2, 1), Tile::solid(None));
+        map
+    }
+
+    #[test]
+    fn row_major_addressing() {
+        let data = (0..6).map(|i| Tile::empty(Some(i))).collect();
+        let map = Tilemap::with_data(data, (30, 20), (10, 10));
+        assert_eq!((map.columns(), map.rows()), (3, 2));
+        assert_eq!(map.tile((2, 0)).unwrap().value, Some(2));
+        assert_eq!(map.tile((0, 1)).unwrap().value, Some(3));
+        assert_eq!(map.get((25, 15)).unwrap().value, Some(5));
+        assert!(map.tile((3, 0)).is_none());
+        assert!(map.tile((0, 2)).is_none());
+        assert_eq!(map.tile_coord((29.9, 0)), Some((2, 0)));
+        assert_eq!(map.tile_coord((-0.5, 0)), None);
+        assert_eq!(map.tile_area((1, 1)), Rectangle::new((10, 10), (10, 10)));
+        let coords: Vec<_> = map.tiles().map(|(coord, _)| coord).collect();
+        assert_eq!(coords[..4], [(0, 0), (1, 0), (2, 0), (0, 1)]);
+        let values: Vec<_> = map
+            .tiles_in(&Rectangle::new((15, 5), (10, 10)))
+            .map(|(_, tile)| tile.value.unwrap())
+            .collect();
+        assert_eq!(values, vec![1, 2, 4, 5]);
+
+        let short = (0..4).map(|i| Tile::empty(Some(i))).collect();
+        let map = Tilemap::with_data(short, (30, 20), (10, 10));
+        let coords: Vec<_> = map
+            .tiles_in(&Rectangle::new((0, 0), (30, 20)))
+            .map(|(coord, _)| coord)
+            .collect();
+        assert_eq!(coords, vec![(0, 0), (1, 0), (2, 0), (0, 1)]);
+    }
+
+    #[test]
+    fn neighbours() {
+        let map = walled();
+        let count = |coord, n| map.neighbours(coord, n).count();
+        assert_eq!(count((0, 0), Neighbourhood::Four), 2);
+        assert_eq!(count((0, 0), Neighbourhood::Eight), 3);
+        assert_eq!(count((1, 1), Neighbourhood::Four), 4);
+        assert_eq!(count((1, 1), Neighbourhood::Eight), 8);
+        assert_eq!(count((4, 1), Neighbourhood::Eight), 5);
+    }
+
+    #[test]
+    fn flood_fill() {
+        let mut map = walled();
+        let left = map.flood_fill((0, 0), Neighbourhood::Four, |tile| tile.empty);
+        assert_eq!(left[0], (0, 0));
+        assert_eq!(left.len(), 13);
+        map.set_tile((2, 2), Tile::solid(None));
+        let left = map.flood_fill((0, 0), Neighbourhood::Eight, |tile| tile.empty);
+        assert_eq!(left.len(), 6);
+        assert!(map
+            .flood_fill((2, 0), Neighbourhood::Four, |tile| tile.empty)
+            .is_empty());
+        let wall = map.flood_fill((2, 0), Neighbourhood::Four, |tile| !tile.empty);
+        assert_eq!(wall.len(), 3);
+    }
+
+    #[test]
+    fn line_of_sight() {
+        let line: Vec<_> = bresenham((0, 0), (5, 2)).collect();
+        assert_eq!(line, vec![(0, 0), (1, 0), (2, 1), (3, 1), (4, 2), (5, 2)]);
+        let line: Vec<_> = bresenham((3, 3), (3, 1)).collect();
+        assert_eq!(line, vec![(3, 3), (3, 2), (3, 1)]);
+        assert_eq!(bresenham((1, 1), (1, 1)).count(), 1);
+
+        let map = walled();
+        assert!(!map.line_of_sight((0, 0), (4, 0)));
+        assert!(map.line_of_sight((0, 2), (4, 2)));
+        assert!(!map.line_of_sight((0, 0), (4, 2)));
+        // Solid end points do not block the view
+        assert!(map.line_of_sight((0, 1), (2, 1)));
+        assert!(!map.line_of_sight((0, 0), (5, 0)));
+    }
 }
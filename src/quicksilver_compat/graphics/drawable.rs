@@ -1,8 +1,9 @@
 //! TODO: What's left in here needs to be cleaned up.
 //! This extends to the general draw interface, where the separation between tesselation and rendering is not well represented now.
 
+use crate::graphics::{Gradient, Image, Pattern};
 use crate::quicksilver_compat::graphics::Color;
-use crate::{graphics::Image, Transform};
+use crate::Transform;
 
 /// The background to use for a given drawable
 ///
@@ -20,13 +21,18 @@ pub enum Background<'a> {
     ImgView(&'a Image, Transform),
     /// A color and image blended multiplicatively
     Blended(&'a Image, Color),
+    /// A color gradient, evaluated at each vertex while tessellating
+    Gradient(&'a Gradient),
+    /// An image repeated in a grid, with texture coordinates computed while tessellating
+    Pattern(&'a Pattern),
 }
 
 impl<'a> Background<'a> {
     /// Return either the stored Image or None
     pub fn image(&self) -> Option<&Image> {
         match self {
-            Background::Col(_) => None,
+            Background::Col(_) | Background::Gradient(_) => None,
+            Background::Pattern(pattern) => Some(&pattern.image),
             Background::Img(img) | Background::Blended(img, _) | Background::ImgView(img, _) => {
                 Some(img)
             }
@@ -37,16 +43,20 @@ impl<'a> Background<'a> {
     pub fn color(&self) -> Color {
         match self {
             Background::Col(color) | Background::Blended(_, color) => *color,
-            Background::Img(_) | Background::ImgView(_, _) => Color::WHITE,
+            Background::Img(_)
+            | Background::ImgView(_, _)
+            | Background::Gradient(_)
+            | Background::Pattern(_) => Color::WHITE,
         }
     }
 
     /// Transformation to be applied to the texture (in normalized texture coordinates)
     pub fn texture_transform(&self) -> Option<Transform> {
         match self {
-            Background::Col(_) | Background::Blended(_, _) => None,
+            Background::Col(_) | Background::Blended(_, _) | Background::Gradient(_) => None,
             Background::Img(img) => Some(img.texture_transform()),
             Background::ImgView(img, transform) => Some(img.texture_transform() * *transform),
+            Background::Pattern(pattern) => Some(pattern.image.texture_transform()),
         }
     }
 }
@@ -68,3 +78,15 @@ impl<'a> From<(&'a Image, Color)> for Background<'a> {
         Background::Blended(img, col)
     }
 }
+
+impl<'a> From<&'a Gradient> for Background<'a> {
+    fn from(gradient: &'a Gradient) -> Self {
+        Background::Gradient(gradient)
+    }
+}
+
+impl<'a> From<&'a Pattern> for Background<'a> {
+    fn from(pattern: &'a Pattern) -> Self {
+        Background::Pattern(pattern)
+    }
+}
@@ -0,0 +1,40 @@
+//! Persistent storage for save games, settings and other data that must survive a page reload.
+//!
+//! A `StorageBackend` stores raw bytes by key. Available are `LocalStorage` for small amounts of data,
+//! `IndexedDbStorage` for large or binary data, and `MemoryStorage`, which forgets everything when dropped
+//! but works outside of the browser, for example in tests.
+//!
+//! A `Store` serializes any `serde` type to JSON and tags it with a schema version.
+//! When data saved with an older version is loaded, the registered migrations update it step by step.
+//!
+//! ```
+//! # use paddle::*;
+//! # use std::rc::Rc;
+//! # use serde::{Deserialize, Serialize};
+//! #[derive(Serialize, Deserialize)]
+//! struct Progress {
+//!     level: u32,
+//!     coins: u32,
+//! }
+//! # async fn example() -> PaddleResult<()> {
+//! let backend = Rc::new(LocalStorage::new("my-game/")?);
+//! // Version 1 only stored the level, coins were added in version 2
+//! let saves: Store<Progress> = Store::new(backend, "saves", 2).with_migration(1, |mut data| {
+//!     data["coins"] = 0.into();
+//!     Ok(data)
+//! });
+//! saves.save("slot-1", &Progress { level: 3, coins: 50 }).await?;
+//! let progress = saves.load("slot-1").await?;
+//! # Ok(())
+//! # }
+//! ```
+
+mod backend;
+mod indexed_db;
+mod local_storage;
+mod store;
+
+pub use backend::*;
+pub use indexed_db::*;
+pub use local_storage::*;
+pub use store::*;
@@ -0,0 +1,68 @@
+use crate::PaddleResult;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+
+/// Result of an asynchronous storage operation
+pub type StorageFuture<T> = Pin<Box<dyn Future<Output = PaddleResult<T>>>>;
+
+/// Stores raw bytes by key.
+///
+/// All operations are asynchronous, even for backends that complete them immediately.
+pub trait StorageBackend {
+    /// The data stored for the key, or `None` if there is none
+    fn read(&self, key: &str) -> StorageFuture<Option<Vec<u8>>>;
+    /// Store data for a key, replacing any previous data
+    fn write(&self, key: &str, data: Vec<u8>) -> StorageFuture<()>;
+    /// Delete the data for a key, if there is any
+    fn remove(&self, key: &str) -> StorageFuture<()>;
+    /// All keys with stored data
+    fn keys(&self) -> StorageFuture<Vec<String>>;
+}
+
+/// Keeps data in memory only, for tests and for running without a browser.
+///
+/// Clones share the same data.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryStorage {
+    data: Rc<RefCell<BTreeMap<String, Vec<u8>>>>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn len(&self) -> usize {
+        self.data.borrow().len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.data.borrow().is_empty()
+    }
+    pub fn clear(&self) {
+        self.data.borrow_mut().clear();
+    }
+}
+
+impl StorageBackend for MemoryStorage {
+    fn read(&self, key: &str) -> StorageFuture<Option<Vec<u8>>> {
+        ready(Ok(self.data.borrow().get(key).cloned()))
+    }
+    fn write(&self, key: &str, data: Vec<u8>) -> StorageFuture<()> {
+        self.data.borrow_mut().insert(key.to_owned(), data);
+        ready(Ok(()))
+    }
+    fn remove(&self, key: &str) -> StorageFuture<()> {
+        self.data.borrow_mut().remove(key);
+        ready(Ok(()))
+    }
+    fn keys(&self) -> StorageFuture<Vec<String>> {
+        ready(Ok(self.data.borrow().keys().cloned().collect()))
+    }
+}
+
+/// A future of a result that is already known
+pub(super) fn ready<T: 'static>(result: PaddleResult<T>) -> StorageFuture<T> {
+    Box::pin(std::future::ready(result))
+}
@@ -0,0 +1,118 @@
+use super::{StorageBackend, StorageFuture};
+use crate::{ErrorMessage, JsError, PaddleResult};
+use js_sys::{Array, Promise, Uint8Array};
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{IdbDatabase, IdbObjectStore, IdbRequest, IdbTransactionMode};
+
+/// All data is kept in a single object store of the database
+const OBJECT_STORE: &str = "paddle";
+
+/// Stores data in an IndexedDB database of the browser.
+///
+/// IndexedDB holds binary data directly and allows much more space than `LocalStorage`,
+/// usually a share of the free disk space.
+#[derive(Clone, Debug)]
+pub struct IndexedDbStorage {
+    db: IdbDatabase,
+}
+
+impl IndexedDbStorage {
+    /// Open a database, creating it if it does not exist yet
+    pub async fn open(name: &str) -> PaddleResult<Self> {
+        let factory = web_sys::window()
+            .unwrap()
+            .indexed_db()
+            .map_err(JsError::from_js_value)?
+            .ok_or_else(|| ErrorMessage::technical("IndexedDB is not available".to_owned()))?;
+        let request = factory
+            .open_with_u32(name, 1)
+            .map_err(JsError::from_js_value)?;
+
+        // A new database has no object store yet, it can only be created inside this event
+        let upgrading_request = request.clone();
+        let on_upgrade = Closure::wrap(Box::new(move || {
+            if let Ok(db) = upgrading_request.result() {
+                let db: IdbDatabase = db.unchecked_into();
+                if let Err(e) = db.create_object_store(OBJECT_STORE) {
+                    web_sys::console::error_1(&e);
+                }
+            }
+        }) as Box<dyn FnMut()>);
+        request.set_onupgradeneeded(Some(on_upgrade.as_ref().unchecked_ref()));
+        let result = finish(&request).await;
+        request.set_onupgradeneeded(None);
+
+        let db = result?.dyn_into().map_err(JsError::from_js_value)?;
+        Ok(IndexedDbStorage { db })
+    }
+    fn object_store(db: &IdbDatabase, mode: IdbTransactionMode) -> PaddleResult<IdbObjectStore> {
+        let store = db
+            .transaction_with_str_and_mode(OBJECT_STORE, mode)
+            .and_then(|transaction| transaction.object_store(OBJECT_STORE))
+            .map_err(JsError::from_js_value)?;
+        Ok(store)
+    }
+}
+
+impl StorageBackend for IndexedDbStorage {
+    fn read(&self, key: &str) -> StorageFuture<Option<Vec<u8>>> {
+        let db = self.db.clone();
+        let key = JsValue::from_str(key);
+        Box::pin(async move {
+            let store = Self::object_store(&db, IdbTransactionMode::Readonly)?;
+            let request = store.get(&key).map_err(JsError::from_js_value)?;
+            let value = finish(&request).await?;
+            if value.is_undefined() {
+                return Ok(None);
+            }
+            Ok(Some(Uint8Array::new(&value).to_vec()))
+        })
+    }
+    fn write(&self, key: &str, data: Vec<u8>) -> StorageFuture<()> {
+        let db = self.db.clone();
+        let key = JsValue::from_str(key);
+        Box::pin(async move {
+            let store = Self::object_store(&db, IdbTransactionMode::Readwrite)?;
+            let value = Uint8Array::from(data.as_slice());
+            let request = store
+                .put_with_key(&value, &key)
+                .map_err(JsError::from_js_value)?;
+            finish(&request).await?;
+            Ok(())
+        })
+    }
+    fn remove(&self, key: &str) -> StorageFuture<()> {
+        let db = self.db.clone();
+        let key = JsValue::from_str(key);
+        Box::pin(async move {
+            let store = Self::object_store(&db, IdbTransactionMode::Readwrite)?;
+            let request = store.delete(&key).map_err(JsError::from_js_value)?;
+            finish(&request).await?;
+            Ok(())
+        })
+    }
+    fn keys(&self) -> StorageFuture<Vec<String>> {
+        let db = self.db.clone();
+        Box::pin(async move {
+            let store = Self::object_store(&db, IdbTransactionMode::Readonly)?;
+            let request = store.get_all_keys().map_err(JsError::from_js_value)?;
+            let keys: Array = finish(&request).await?.unchecked_into();
+            Ok(keys.iter().filter_map(|key| key.as_string()).collect())
+        })
+    }
+}
+
+/// Wait for a request to succeed and return its result
+async fn finish(request: &IdbRequest) -> PaddleResult<JsValue> {
+    let promise = Promise::new(&mut |resolve, reject| {
+        request.set_onsuccess(Some(&resolve));
+        request.set_onerror(Some(&reject));
+    });
+    let outcome = JsFuture::from(promise).await;
+    request.set_onsuccess(None);
+    request.set_onerror(None);
+    outcome.map_err(JsError::from_js_value)?;
+    Ok(request.result().map_err(JsError::from_js_value)?)
+}
@@ -0,0 +1,73 @@
+use super::backend::ready;
+use super::{StorageBackend, StorageFuture};
+use crate::{ErrorMessage, JsError, PaddleResult};
+use base64::Engine;
+
+/// Stores data in the `localStorage` of the browser.
+///
+/// Browsers usually limit it to around 5 MB per origin, and since it only holds strings,
+/// data is base64 encoded, which takes a third more space. Prefer `IndexedDbStorage` for large data.
+#[derive(Clone, Debug)]
+pub struct LocalStorage {
+    storage: web_sys::Storage,
+    prefix: String,
+}
+
+impl LocalStorage {
+    /// Access the local storage, with all keys starting with `prefix` to avoid clashes with other scripts
+    pub fn new(prefix: impl Into<String>) -> PaddleResult<Self> {
+        let storage = web_sys::window()
+            .unwrap()
+            .local_storage()
+            .map_err(JsError::from_js_value)?
+            .ok_or_else(|| ErrorMessage::technical("localStorage is not available".to_owned()))?;
+        Ok(LocalStorage {
+            storage,
+            prefix: prefix.into(),
+        })
+    }
+    fn read_now(&self, key: &str) -> PaddleResult<Option<Vec<u8>>> {
+        let text = self
+            .storage
+            .get_item(&self.item(key))
+            .map_err(JsError::from_js_value)?;
+        match text {
+            Some(text) => Ok(Some(
+                base64::engine::general_purpose::STANDARD.decode(text)?,
+            )),
+            None => Ok(None),
+        }
+    }
+    fn keys_now(&self) -> PaddleResult<Vec<String>> {
+        let len = self.storage.length().map_err(JsError::from_js_value)?;
+        let mut keys = Vec::new();
+        for i in 0..len {
+            let item = self.storage.key(i).map_err(JsError::from_js_value)?;
+            if let Some(key) = item.as_deref().and_then(|i| i.strip_prefix(&self.prefix)) {
+                keys.push(key.to_owned());
+            }
+        }
+        Ok(keys)
+    }
+    fn item(&self, key: &str) -> String {
+        format!("{}{}", self.prefix, key)
+    }
+}
+
+impl StorageBackend for LocalStorage {
+    fn read(&self, key: &str) -> StorageFuture<Option<Vec<u8>>> {
+        ready(self.read_now(key))
+    }
+    fn write(&self, key: &str, data: Vec<u8>) -> StorageFuture<()> {
+        let text = base64::engine::general_purpose::STANDARD.encode(data);
+        let result = self.storage.set_item(&self.item(key), &text);
+        ready(result.map_err(|e| JsError::from_js_value(e).into()))
+    }
+    fn remove(&self, key: &str) -> StorageFuture<()> {
+        let result = self.storage.remove_item(&self.item(key));
+        ready(result.map_err(|e| JsError::from_js_value(e).into()))
+    }
+    fn keys(&self) -> StorageFuture<Vec<String>> {
+        ready(self.keys_now())
+    }
+}
@@ -0,0 +1,198 @@
+use super::StorageBackend;
+use crate::{ErrorMessage, PaddleResult};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::marker::PhantomData;
+use std::rc::Rc;
+
+/// Upgrades data from one schema version to the next
+pub type Migration = fn(Value) -> PaddleResult<Value>;
+
+/// Saves and loads values of one type, under keys in a namespace of a backend.
+///
+/// Values are stored as JSON together with the schema version of the store.
+/// Increase the version whenever the type changes incompatibly, and register a migration from the old version.
+pub struct Store<T> {
+    backend: Rc<dyn StorageBackend>,
+    namespace: String,
+    version: u32,
+    migrations: Vec<(u32, Migration)>,
+    _data: PhantomData<fn() -> T>,
+}
+
+#[derive(Serialize)]
+struct Envelope<'a, T> {
+    version: u32,
+    data: &'a T,
+}
+
+#[derive(Deserialize)]
+struct StoredEnvelope {
+    version: u32,
+    data: Value,
+}
+
+impl<T: Serialize + DeserializeOwned> Store<T> {
+    /// A store saving values with the given schema version. Stores on the same backend need distinct namespaces.
+    pub fn new(
+        backend: Rc<dyn StorageBackend>,
+        namespace: impl Into<String>,
+        version: u32,
+    ) -> Self {
+        Store {
+            backend,
+            namespace: namespace.into(),
+            version,
+            migrations: Vec::new(),
+            _data: PhantomData,
+        }
+    }
+    /// Register a migration from `from_version` to the version after it
+    pub fn with_migration(mut self, from_version: u32, migration: Migration) -> Self {
+        self.migrations.push((from_version, migration));
+        self
+    }
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+    pub async fn save(&self, key: &str, value: &T) -> PaddleResult<()> {
+        let envelope = Envelope {
+            version: self.version,
+            data: value,
+        };
+        let bytes = serde_json::to_vec(&envelope)?;
+        self.backend.write(&self.full_key(key), bytes).await
+    }
+    /// Load the value for a key, migrated to the current version, or `None` if nothing has been saved
+    pub async fn load(&self, key: &str) -> PaddleResult<Option<T>> {
+        let bytes = match self.backend.read(&self.full_key(key)).await? {
+            Some(bytes) => bytes,
+            None => return Ok(None),
+        };
+        let envelope: StoredEnvelope = serde_json::from_slice(&bytes)?;
+        let data = self.migrate(key, envelope.version, envelope.data)?;
+        Ok(Some(serde_json::from_value(data)?))
+    }
+    pub async fn remove(&self, key: &str) -> PaddleResult<()> {
+        self.backend.remove(&self.full_key(key)).await
+    }
+    /// Keys of all values saved in this store
+    pub async fn keys(&self) -> PaddleResult<Vec<String>> {
+        let prefix = self.full_key("");
+        let keys = self.backend.keys().await?;
+        Ok(keys
+            .into_iter()
+            .filter_map(|key| key.strip_prefix(&prefix).map(str::to_owned))
+            .collect())
+    }
+
+    fn migrate(&self, key: &str, mut version: u32, mut data: Value) -> PaddleResult<Value> {
+        if version > self.version {
+            return Err(ErrorMessage::technical(format!(
+                "'{}' was saved with version {} of '{}', which is newer than the supported version {}",
+                key, version, self.namespace, self.version
+            )));
+        }
+        while version < self.version {
+            let migration = self
+                .migrations
+                .iter()
+                .find(|(from, _)| *from == version)
+                .ok_or_else(|| {
+                    ErrorMessage::technical(format!(
+                        "No migration from version {} of '{}'",
+                        version, self.namespace
+                    ))
+                })?;
+            data = (migration.1)(data)?;
+            version += 1;
+        }
+        Ok(data)
+    }
+    fn full_key(&self, key: &str) -> String {
+        format!("{}/{}", self.namespace, key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::MemoryStorage;
+    use super::*;
+    use std::future::Future;
+    use std::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Progress {
+        level: u32,
+        coins: u32,
+        name: String,
+    }
+
+    /// Polls a future that completes immediately, like all operations on `MemoryStorage`
+    fn now<F: Future>(future: F) -> F::Output {
+        fn raw() -> RawWaker {
+            fn clone(_: *const ()) -> RawWaker {
+                raw()
+            }
+            fn noop(_: *const ()) {}
+            static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+            RawWaker::new(std::ptr::null(), &VTABLE)
+        }
+        let waker = unsafe { Waker::from_raw(raw()) };
+        let mut future = Box::pin(future);
+        match future.as_mut().poll(&mut Context::from_waker(&waker)) {
+            Poll::Ready(output) => output,
+            Poll::Pending => panic!("Storage future is pending"),
+        }
+    }
+
+    #[test]
+    fn save_and_load() {
+        let memory = MemoryStorage::new();
+        let saves: Store<Progress> = Store::new(Rc::new(memory.clone()), "saves", 1);
+        let settings: Store<u32> = Store::new(Rc::new(memory.clone()), "settings", 1);
+        let progress = Progress {
+            level: 2,
+            coins: 7,
+            name: "Ann".to_owned(),
+        };
+        now(saves.save("a", &progress)).unwrap();
+        now(settings.save("volume", &80)).unwrap();
+        assert_eq!(memory.len(), 2);
+        assert_eq!(now(saves.load("a")).unwrap(), Some(progress));
+        assert_eq!(now(saves.load("b")).unwrap(), None);
+        assert_eq!(now(saves.keys()).unwrap(), vec!["a".to_owned()]);
+
+        now(saves.remove("a")).unwrap();
+        assert_eq!(now(saves.load("a")).unwrap(), None);
+        assert_eq!(now(settings.load("volume")).unwrap(), Some(80));
+    }
+
+    #[test]
+    fn migrations() {
+        let memory = MemoryStorage::new();
+        let old = br#"{ "version": 1, "data": { "level": 4 } }"#;
+        now(memory.write("saves/old", old.to_vec())).unwrap();
+
+        let saves: Store<Progress> = Store::new(Rc::new(memory.clone()), "saves", 3)
+            .with_migration(2, |mut data| {
+                data["name"] = "Player".into();
+                Ok(data)
+            })
+            .with_migration(1, |mut data| {
+                data["coins"] = (data["level"].as_u64().unwrap_or(0) * 10).into();
+                Ok(data)
+            });
+        let progress = now(saves.load("old")).unwrap().unwrap();
+        assert_eq!(progress.coins, 40);
+        assert_eq!(progress.name, "Player");
+
+        let newer = br#"{ "version": 4, "data": null }"#;
+        now(memory.write("saves/newer", newer.to_vec())).unwrap();
+        assert!(now(saves.load("newer")).is_err());
+
+        let unmigrated: Store<Progress> = Store::new(Rc::new(memory), "saves", 3);
+        assert!(now(unmigrated.load("old")).is_err());
+    }
+}
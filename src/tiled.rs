@@ -0,0 +1,24 @@
+//! Loading and drawing maps made with the [Tiled](https://www.mapeditor.org/) map editor.
+//!
+//! Maps can be stored as TMX or JSON, tilesets can be embedded or in separate TSX or JSON files.
+//! Tile layers are stored as `Tilemap`s, so collision checks like `Tilemap::shape_empty` work on them directly.
+//! Tiles are solid if they have a custom boolean property `solid` set to true.
+//!
+//! ```
+//! # use paddle::*;
+//! # fn draw(map: &TiledMap, renderer: &mut TiledRenderer, display: &mut DisplayArea) {
+//! // Show the top left quarter of the map
+//! let view = Rectangle::new_sized(map.pixel_size() / 2.0);
+//! renderer.draw(map, display, view);
+//! # }
+//! ```
+
+mod data;
+mod json;
+mod loader;
+mod map;
+mod render;
+mod tmx;
+
+pub use map::*;
+pub use render::*;
@@ -0,0 +1,92 @@
+//! Decoding of tile layer data, shared by both file formats
+
+use crate::{ErrorMessage, PaddleResult};
+use base64::Engine;
+
+/// Comma separated gids, line breaks are allowed anywhere
+pub(crate) fn decode_csv(text: &str) -> PaddleResult<Vec<u32>> {
+    text.split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| {
+            s.parse()
+                .map_err(|_| ErrorMessage::technical(format!("Invalid tile id '{}'", s)))
+        })
+        .collect()
+}
+
+/// Little-endian u32 gids, base64 encoded and optionally compressed
+pub(crate) fn decode_base64(text: &str, compression: Option<&str>) -> PaddleResult<Vec<u32>> {
+    let compact: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    let bytes = base64::engine::general_purpose::STANDARD.decode(compact)?;
+    let bytes = match compression.unwrap_or("") {
+        "" => bytes,
+        "zlib" => miniz_oxide::inflate::decompress_to_vec_zlib(&bytes).map_err(|e| {
+            ErrorMessage::technical(format!("Failed to decompress tile data: {:?}", e))
+        })?,
+        other => {
+            return Err(ErrorMessage::technical(format!(
+                "Unsupported tile data compression '{}', use zlib or none",
+                other
+            )))
+        }
+    };
+    if bytes.len() % 4 != 0 {
+        return Err(ErrorMessage::technical(
+            "Tile data length is not a multiple of 4 bytes".to_owned(),
+        ));
+    }
+    Ok(bytes
+        .chunks_exact(4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect())
+}
+
+/// Resolve a path relative to the file at `base`
+pub(crate) fn resolve_path(base: &str, path: &str) -> String {
+    if path.starts_with('/') || path.contains("://") {
+        return path.to_owned();
+    }
+    match base.rfind('/') {
+        Some(i) => format!("{}{}", &base[..=i], path),
+        None => path.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv() {
+        assert_eq!(decode_csv("1,2,\n3,0\n").unwrap(), vec![1, 2, 3, 0]);
+        assert!(decode_csv("1,x").is_err());
+    }
+
+    #[test]
+    fn base64() {
+        // [1, 2, 2147483651]
+        let plain = "AQAAAAIAAAADAACA";
+        assert_eq!(decode_base64(plain, None).unwrap(), vec![1, 2, 0x8000_0003]);
+        let zlib = "eJxjZGBgYAJiZgaGBgAAtACH";
+        assert_eq!(
+            decode_base64(zlib, Some("zlib")).unwrap(),
+            vec![1, 2, 0x8000_0003]
+        );
+        assert!(decode_base64(plain, Some("zstd")).is_err());
+    }
+
+    #[test]
+    fn paths() {
+        assert_eq!(
+            resolve_path("maps/level.tmx", "tiles.png"),
+            "maps/tiles.png"
+        );
+        assert_eq!(
+            resolve_path("maps/level.tmx", "../img/tiles.png"),
+            "maps/../img/tiles.png"
+        );
+        assert_eq!(resolve_path("level.tmx", "tiles.png"), "tiles.png");
+        assert_eq!(resolve_path("maps/level.tmx", "/tiles.png"), "/tiles.png");
+    }
+}
@@ -0,0 +1,431 @@
+//! Tiled maps and tilesets exported as JSON
+
+use super::data::{decode_base64, decode_csv};
+use super::map::*;
+use crate::{ErrorMessage, PaddleResult, Vector};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+
+#[derive(Deserialize)]
+struct RawMap {
+    width: u32,
+    height: u32,
+    tilewidth: f32,
+    tileheight: f32,
+    #[serde(default = "orthogonal")]
+    orientation: String,
+    #[serde(default)]
+    infinite: bool,
+    #[serde(default)]
+    layers: Vec<RawLayer>,
+    #[serde(default)]
+    tilesets: Vec<RawTileset>,
+    #[serde(default)]
+    properties: Vec<RawProperty>,
+    backgroundcolor: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RawLayer {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default = "yes")]
+    visible: bool,
+    #[serde(default = "one")]
+    opacity: f32,
+    #[serde(default)]
+    offsetx: f32,
+    #[serde(default)]
+    offsety: f32,
+    #[serde(default)]
+    properties: Vec<RawProperty>,
+    data: Option<Value>,
+    encoding: Option<String>,
+    compression: Option<String>,
+    #[serde(default)]
+    objects: Vec<RawObject>,
+    #[serde(default)]
+    layers: Vec<RawLayer>,
+}
+
+#[derive(Deserialize)]
+struct RawTileset {
+    firstgid: Option<u32>,
+    source: Option<String>,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    tilewidth: f32,
+    #[serde(default)]
+    tileheight: f32,
+    #[serde(default)]
+    columns: u32,
+    #[serde(default)]
+    tilecount: u32,
+    #[serde(default)]
+    spacing: f32,
+    #[serde(default)]
+    margin: f32,
+    image: Option<String>,
+    #[serde(default)]
+    imagewidth: f32,
+    #[serde(default)]
+    imageheight: f32,
+    #[serde(default)]
+    tiles: Vec<RawTile>,
+}
+
+#[derive(Deserialize)]
+struct RawTile {
+    id: u32,
+    #[serde(default)]
+    properties: Vec<RawProperty>,
+}
+
+#[derive(Deserialize)]
+struct RawProperty {
+    name: String,
+    #[serde(rename = "type", default = "string")]
+    kind: String,
+    value: Value,
+}
+
+#[derive(Deserialize)]
+struct RawObject {
+    #[serde(default)]
+    id: u32,
+    #[serde(default)]
+    name: String,
+    #[serde(rename = "type")]
+    old_class: Option<String>,
+    class: Option<String>,
+    #[serde(default)]
+    x: f32,
+    #[serde(default)]
+    y: f32,
+    #[serde(default)]
+    width: f32,
+    #[serde(default)]
+    height: f32,
+    #[serde(default)]
+    rotation: f32,
+    #[serde(default = "yes")]
+    visible: bool,
+    gid: Option<u32>,
+    #[serde(default)]
+    ellipse: bool,
+    #[serde(default)]
+    point: bool,
+    polygon: Option<Vec<RawPoint>>,
+    polyline: Option<Vec<RawPoint>>,
+    text: Option<RawText>,
+    #[serde(default)]
+    properties: Vec<RawProperty>,
+}
+
+#[derive(Deserialize)]
+struct RawPoint {
+    x: f32,
+    y: f32,
+}
+
+#[derive(Deserialize)]
+struct RawText {
+    #[serde(default)]
+    text: String,
+}
+
+fn orthogonal() -> String {
+    "orthogonal".to_owned()
+}
+fn string() -> String {
+    "string".to_owned()
+}
+fn yes() -> bool {
+    true
+}
+fn one() -> f32 {
+    1.0
+}
+
+impl TiledMap {
+    /// Parse a map in the JSON format.
+    ///
+    /// Tilesets stored in separate files are not loaded, use `TiledMap::load` for that.
+    pub fn from_json(text: &str) -> PaddleResult<TiledMap> {
+        let raw: RawMap = serde_json::from_str(text)?;
+        if raw.infinite {
+            return Err(ErrorMessage::technical(
+                "Infinite Tiled maps are not supported".to_owned(),
+            ));
+        }
+        if raw.orientation != "orthogonal" {
+            return Err(ErrorMessage::technical(format!(
+                "Tiled maps with {} orientation are not supported",
+                raw.orientation
+            )));
+        }
+        let mut map = TiledMap {
+            width: raw.width,
+            height: raw.height,
+            tile_size: Vector::new(raw.tilewidth, raw.tileheight),
+            layers: Vec::new(),
+            tilesets: raw
+                .tilesets
+                .into_iter()
+                .map(convert_tileset)
+                .collect::<PaddleResult<_>>()?,
+            properties: convert_properties(raw.properties)?,
+            background_color: raw
+                .backgroundcolor
+                .as_deref()
+                .map(parse_color)
+                .transpose()?,
+        };
+        map.layers = raw
+            .layers
+            .into_iter()
+            .filter_map(|layer| convert_layer(&map, layer).transpose())
+            .collect::<PaddleResult<_>>()?;
+        map.update_solid_tiles();
+        Ok(map)
+    }
+}
+
+impl Tileset {
+    /// Parse a tileset stored in a separate JSON file
+    pub fn from_json(text: &str, first_gid: u32) -> PaddleResult<Tileset> {
+        let mut raw: RawTileset = serde_json::from_str(text)?;
+        raw.firstgid = Some(first_gid);
+        raw.source = None;
+        convert_tileset(raw)
+    }
+}
+
+fn convert_tileset(raw: RawTileset) -> PaddleResult<Tileset> {
+    let first_gid = raw.firstgid.unwrap_or(1);
+    if let Some(source) = raw.source {
+        return Ok(Tileset::external(first_gid, source));
+    }
+    let image_source = match raw.image {
+        Some(image) => image,
+        None => {
+            return Err(ErrorMessage::technical(format!(
+                "Tileset '{}' has no tile sheet, image collections are not supported",
+                raw.name
+            )))
+        }
+    };
+    let tiles = raw
+        .tiles
+        .into_iter()
+        .map(|tile| Ok((tile.id, convert_properties(tile.properties)?)))
+        .collect::<PaddleResult<HashMap<_, _>>>()?;
+    Ok(Tileset {
+        first_gid,
+        name: raw.name,
+        tile_size: Vector::new(raw.tilewidth, raw.tileheight),
+        columns: raw.columns,
+        tile_count: raw.tilecount,
+        spacing: raw.spacing,
+        margin: raw.margin,
+        image_source,
+        image_size: Vector::new(raw.imagewidth, raw.imageheight),
+        tiles,
+        source: None,
+        image: None,
+    })
+}
+
+/// Image layers are skipped
+fn convert_layer(map: &TiledMap, raw: RawLayer) -> PaddleResult<Option<TiledLayer>> {
+    let kind = match raw.kind.as_str() {
+        "tilelayer" => {
+            let gids = match raw.data {
+                Some(Value::Array(values)) => values
+                    .iter()
+                    .map(|v| {
+                        v.as_u64().map(|gid| gid as u32).ok_or_else(|| {
+                            ErrorMessage::technical(format!("Invalid tile id {}", v))
+                        })
+                    })
+                    .collect::<PaddleResult<Vec<u32>>>()?,
+                Some(Value::String(text)) => match raw.encoding.as_deref() {
+                    Some("base64") => decode_base64(&text, raw.compression.as_deref())?,
+                    _ => decode_csv(&text)?,
+                },
+                _ => {
+                    return Err(ErrorMessage::technical(format!(
+                        "Tile layer '{}' has no data",
+                        raw.name
+                    )))
+                }
+            };
+            TiledLayerKind::Tiles(map.tile_grid(&gids)?)
+        }
+        "objectgroup" => TiledLayerKind::Objects(
+            raw.objects
+                .into_iter()
+                .map(convert_object)
+                .collect::<PaddleResult<_>>()?,
+        ),
+        "group" => TiledLayerKind::Group(
+            raw.layers
+                .into_iter()
+                .filter_map(|layer| convert_layer(map, layer).transpose())
+                .collect::<PaddleResult<_>>()?,
+        ),
+        _ => return Ok(None),
+    };
+    Ok(Some(TiledLayer {
+        name: raw.name,
+        visible: raw.visible,
+        opacity: raw.opacity,
+        offset: Vector::new(raw.offsetx, raw.offsety),
+        properties: convert_properties(raw.properties)?,
+        kind,
+    }))
+}
+
+fn convert_object(raw: RawObject) -> PaddleResult<TiledObject> {
+    let points =
+        |points: Vec<RawPoint>| points.into_iter().map(|p| Vector::new(p.x, p.y)).collect();
+    let shape = if let Some(polygon) = raw.polygon {
+        ObjectShape::Polygon(points(polygon))
+    } else if let Some(polyline) = raw.polyline {
+        ObjectShape::Polyline(points(polyline))
+    } else if let Some(text) = raw.text {
+        ObjectShape::Text(text.text)
+    } else if raw.ellipse {
+        ObjectShape::Ellipse
+    } else if raw.point {
+        ObjectShape::Point
+    } else {
+        ObjectShape::Rectangle
+    };
+    Ok(TiledObject {
+        id: raw.id,
+        name: raw.name,
+        class: raw.class.or(raw.old_class).unwrap_or_default(),
+        pos: Vector::new(raw.x, raw.y),
+        size: Vector::new(raw.width, raw.height),
+        rotation: raw.rotation,
+        visible: raw.visible,
+        tile: raw.gid.and_then(TileRef::from_raw),
+        shape,
+        properties: convert_properties(raw.properties)?,
+    })
+}
+
+fn convert_properties(raw: Vec<RawProperty>) -> PaddleResult<Properties> {
+    raw.into_iter()
+        .map(|p| {
+            let text = match &p.value {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            Ok((p.name, parse_property(&p.kind, &text)?))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAP: &str = r##"{
+        "width": 3, "height": 2, "tilewidth": 16, "tileheight": 16,
+        "orientation": "orthogonal", "infinite": false,
+        "backgroundcolor": "#ff0000",
+        "properties": [{"name": "music", "type": "file", "value": "theme.ogg"}],
+        "tilesets": [
+            {"firstgid": 1, "name": "terrain", "tilewidth": 16, "tileheight": 16,
+             "columns": 4, "tilecount": 8, "spacing": 1, "margin": 2,
+             "image": "terrain.png", "imagewidth": 70, "imageheight": 36,
+             "tiles": [{"id": 1, "properties": [{"name": "solid", "type": "bool", "value": true}]}]},
+            {"firstgid": 9, "source": "items.tsj"}
+        ],
+        "layers": [
+            {"type": "tilelayer", "name": "ground", "width": 3, "height": 2,
+             "data": [1, 2, 0, 2147483649, 9, 1], "opacity": 0.5},
+            {"type": "group", "name": "above", "offsetx": 4, "layers": [
+                {"type": "tilelayer", "name": "decor", "encoding": "base64",
+                 "data": "AQAAAAAAAAAAAAAAAAAAAAAAAAACAAAA"}
+            ]},
+            {"type": "imagelayer", "name": "sky"},
+            {"type": "objectgroup", "name": "spawns", "objects": [
+                {"id": 1, "name": "player", "type": "spawn", "x": 8, "y": 24,
+                 "point": true, "properties": [{"name": "hp", "type": "int", "value": 3}]},
+                {"id": 2, "x": 0, "y": 0, "polygon": [{"x": 0, "y": 0}, {"x": 4, "y": 0}, {"x": 0, "y": 4}]}
+            ]}
+        ]
+    }"##;
+
+    #[test]
+    fn parse_map() {
+        let map = TiledMap::from_json(MAP).unwrap();
+        assert_eq!((map.width, map.height), (3, 2));
+        assert_eq!(map.pixel_size(), Vector::new(48, 32));
+        assert_eq!(
+            map.background_color,
+            Some(crate::quicksilver_compat::Color::RED)
+        );
+        assert_eq!(
+            map.properties["music"],
+            PropertyValue::File("theme.ogg".to_owned())
+        );
+        assert_eq!(map.layers.len(), 3);
+
+        let set = &map.tilesets[0];
+        assert_eq!(
+            set.tile_region(5),
+            crate::Rectangle::new((19, 19), (16, 16))
+        );
+        assert_eq!(map.tilesets[1].source.as_deref(), Some("items.tsj"));
+
+        let ground = match &map.layer("ground").unwrap().kind {
+            TiledLayerKind::Tiles(grid) => grid,
+            _ => panic!("Not a tile layer"),
+        };
+        let at = |x: i32, y: i32| ground.get(Vector::new(x * 16, y * 16)).unwrap();
+        assert_eq!(at(0, 0).value.unwrap().gid, 1);
+        assert!(at(0, 0).empty);
+        // Tile 2 is the one with local id 1, which is solid
+        assert!(!at(1, 0).empty);
+        assert!(at(2, 0).value.is_none());
+        let flipped = at(0, 1).value.unwrap();
+        assert_eq!(flipped.gid, 1);
+        assert!(flipped.flip_horizontal && !flipped.flip_vertical);
+        assert_eq!(at(1, 1).value.unwrap().gid, 9);
+
+        let decor = map.layer("decor").unwrap();
+        assert!(matches!(decor.kind, TiledLayerKind::Tiles(_)));
+
+        let objects: Vec<_> = map.objects().collect();
+        assert_eq!(objects.len(), 2);
+        assert_eq!(objects[0].class, "spawn");
+        assert_eq!(objects[0].shape, ObjectShape::Point);
+        assert_eq!(objects[0].properties["hp"], PropertyValue::Int(3));
+        assert!(matches!(&objects[1].shape, ObjectShape::Polygon(p) if p.len() == 3));
+    }
+
+    #[test]
+    fn unsupported() {
+        assert!(TiledMap::from_json(
+            r#"{"width": 1, "height": 1, "tilewidth": 1, "tileheight": 1, "infinite": true}"#
+        )
+        .is_err());
+        assert!(TiledMap::from_json(
+            r#"{"width": 1, "height": 1, "tilewidth": 1, "tileheight": 1, "orientation": "isometric"}"#
+        )
+        .is_err());
+        assert!(TiledMap::from_json(
+            r#"{"width": 2, "height": 1, "tilewidth": 1, "tileheight": 1,
+                "layers": [{"type": "tilelayer", "data": [1]}]}"#
+        )
+        .is_err());
+    }
+}
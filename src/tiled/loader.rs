@@ -0,0 +1,42 @@
+use super::data::resolve_path;
+use super::map::{TiledMap, Tileset};
+use crate::{load_file, Image, PaddleResult};
+
+impl TiledMap {
+    /// Load a map in the TMX or JSON format, including tilesets stored in separate files and all tile sheet images.
+    ///
+    /// Paths inside the files are resolved relative to the file that contains them.
+    pub async fn load(url: &str) -> PaddleResult<TiledMap> {
+        let text = load_text(url).await?;
+        let mut map = if is_xml(&text) {
+            TiledMap::from_tmx(&text)?
+        } else {
+            TiledMap::from_json(&text)?
+        };
+        for set in map.tilesets.iter_mut() {
+            let mut base = url.to_owned();
+            if let Some(source) = &set.source {
+                let set_url = resolve_path(url, source);
+                let text = load_text(&set_url).await?;
+                *set = if is_xml(&text) {
+                    Tileset::from_tsx(&text, set.first_gid)?
+                } else {
+                    Tileset::from_json(&text, set.first_gid)?
+                };
+                base = set_url;
+            }
+            set.image = Some(Image::load(&resolve_path(&base, &set.image_source)).await?);
+        }
+        map.update_solid_tiles();
+        Ok(map)
+    }
+}
+
+async fn load_text(url: &str) -> PaddleResult<String> {
+    let bytes = load_file(url).await?;
+    Ok(String::from_utf8(bytes)?)
+}
+
+fn is_xml(text: &str) -> bool {
+    text.trim_start().starts_with('<')
+}
@@ -0,0 +1,323 @@
+use crate::quicksilver_compat::geom::{Tile, Tilemap};
+use crate::quicksilver_compat::Color;
+use crate::{ErrorMessage, Image, PaddleResult, Rectangle, Vector};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Custom properties attached to maps, layers, tiles and objects in the Tiled editor
+pub type Properties = HashMap<String, PropertyValue>;
+
+/// Value of a custom property
+#[derive(Clone, Debug, PartialEq)]
+pub enum PropertyValue {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+    Color(Color),
+    /// Path to a file, relative to the map
+    File(String),
+    /// Id of an object on the same map
+    Object(u32),
+}
+
+/// A map created with the [Tiled](https://www.mapeditor.org/) editor.
+///
+/// Only orthogonal, finite maps are supported.
+#[derive(Clone, Debug)]
+pub struct TiledMap {
+    /// Number of tiles in horizontal direction
+    pub width: u32,
+    /// Number of tiles in vertical direction
+    pub height: u32,
+    /// Size of a grid cell, in pixels
+    pub tile_size: Vector,
+    /// Layers, ordered from bottom to top
+    pub layers: Vec<TiledLayer>,
+    pub tilesets: Vec<Tileset>,
+    pub properties: Properties,
+    pub background_color: Option<Color>,
+}
+
+/// A layer of a `TiledMap`
+#[derive(Clone, Debug)]
+pub struct TiledLayer {
+    pub name: String,
+    pub visible: bool,
+    pub opacity: f32,
+    /// Offset from the map origin, in pixels
+    pub offset: Vector,
+    pub properties: Properties,
+    pub kind: TiledLayerKind,
+}
+
+#[derive(Clone, Debug)]
+pub enum TiledLayerKind {
+    /// Grid of tiles, in pixel coordinates of the map.
+    ///
+    /// Tiles with a `solid` property set to true are solid, all other tiles are empty.
+    Tiles(Tilemap<TileRef>),
+    Objects(Vec<TiledObject>),
+    Group(Vec<TiledLayer>),
+}
+
+/// Reference to a tile of one of the tilesets, as stored in tile layers and tile objects
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub struct TileRef {
+    /// Global tile id, unique across all tilesets of the map
+    pub gid: u32,
+    pub flip_horizontal: bool,
+    pub flip_vertical: bool,
+    /// Swap x and y axis, which together with the other flags is used for rotations
+    pub flip_diagonal: bool,
+}
+
+const FLIP_HORIZONTAL: u32 = 0x8000_0000;
+const FLIP_VERTICAL: u32 = 0x4000_0000;
+const FLIP_DIAGONAL: u32 = 0x2000_0000;
+/// Only used by hexagonal maps but must be cleared as well
+const ROTATE_HEXAGONAL: u32 = 0x1000_0000;
+
+impl TileRef {
+    /// Decode a gid with flip flags, as stored in Tiled files. Gid 0 means no tile.
+    pub fn from_raw(raw: u32) -> Option<TileRef> {
+        let gid = raw & !(FLIP_HORIZONTAL | FLIP_VERTICAL | FLIP_DIAGONAL | ROTATE_HEXAGONAL);
+        if gid == 0 {
+            return None;
+        }
+        Some(TileRef {
+            gid,
+            flip_horizontal: raw & FLIP_HORIZONTAL != 0,
+            flip_vertical: raw & FLIP_VERTICAL != 0,
+            flip_diagonal: raw & FLIP_DIAGONAL != 0,
+        })
+    }
+}
+
+/// A tile sheet image together with per-tile data
+#[derive(Clone, Debug)]
+pub struct Tileset {
+    /// Global id of the first tile in this set
+    pub first_gid: u32,
+    pub name: String,
+    pub tile_size: Vector,
+    pub columns: u32,
+    pub tile_count: u32,
+    /// Pixels between neighbouring tiles in the image
+    pub spacing: f32,
+    /// Pixels around all tiles in the image
+    pub margin: f32,
+    /// Path of the tile sheet, relative to the file the tileset was defined in
+    pub image_source: String,
+    pub image_size: Vector,
+    /// Properties of individual tiles, by local tile id
+    pub tiles: HashMap<u32, Properties>,
+    /// Set for tilesets stored in a separate file which has not been loaded yet
+    pub source: Option<String>,
+    /// The tile sheet, once loaded
+    pub image: Option<Image>,
+}
+
+impl Tileset {
+    /// Placeholder for a tileset defined in a separate file
+    pub(crate) fn external(first_gid: u32, source: String) -> Tileset {
+        Tileset {
+            first_gid,
+            name: String::new(),
+            tile_size: Vector::ZERO,
+            columns: 0,
+            tile_count: 0,
+            spacing: 0.0,
+            margin: 0.0,
+            image_source: String::new(),
+            image_size: Vector::ZERO,
+            tiles: HashMap::new(),
+            source: Some(source),
+            image: None,
+        }
+    }
+    /// If the gid belongs to this tileset
+    pub fn contains(&self, gid: u32) -> bool {
+        gid >= self.first_gid && gid - self.first_gid < self.tile_count
+    }
+    /// Area of a tile in the tile sheet, in texels
+    pub fn tile_region(&self, local_id: u32) -> Rectangle {
+        let columns = self.columns.max(1);
+        let (col, row) = (local_id % columns, local_id / columns);
+        let step = self.tile_size + Vector::ONE * self.spacing;
+        Rectangle::new(
+            Vector::ONE * self.margin + step.times((col as f32, row as f32)),
+            self.tile_size,
+        )
+    }
+    /// The tile as a view into the tile sheet, once the image is loaded
+    pub fn tile_image(&self, local_id: u32) -> Option<Image> {
+        self.image
+            .as_ref()
+            .map(|img| img.subimage_texels(self.tile_region(local_id)))
+    }
+}
+
+/// An object placed on an object layer
+#[derive(Clone, Debug)]
+pub struct TiledObject {
+    pub id: u32,
+    pub name: String,
+    /// Called type in older versions of Tiled
+    pub class: String,
+    /// Top left corner for rectangles and ellipses, bottom left for tile objects
+    pub pos: Vector,
+    pub size: Vector,
+    /// Clockwise, in degrees
+    pub rotation: f32,
+    pub visible: bool,
+    /// Set for tile objects
+    pub tile: Option<TileRef>,
+    pub shape: ObjectShape,
+    pub properties: Properties,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ObjectShape {
+    Rectangle,
+    Ellipse,
+    Point,
+    /// Closed shape, points relative to the object position
+    Polygon(Vec<Vector>),
+    /// Open line, points relative to the object position
+    Polyline(Vec<Vector>),
+    Text(String),
+}
+
+impl TiledMap {
+    /// Size of the full map, in pixels
+    pub fn pixel_size(&self) -> Vector {
+        self.tile_size
+            .times((self.width as f32, self.height as f32))
+    }
+    /// Tileset and local tile id for a global tile id
+    pub fn tileset_of(&self, gid: u32) -> Option<(&Tileset, u32)> {
+        self.tilesets
+            .iter()
+            .find(|set| set.contains(gid))
+            .map(|set| (set, gid - set.first_gid))
+    }
+    /// Custom properties of a tile, if it has any
+    pub fn tile_properties(&self, tile: TileRef) -> Option<&Properties> {
+        let (set, local) = self.tileset_of(tile.gid)?;
+        set.tiles.get(&local)
+    }
+    /// Find a layer by name, also inside groups
+    pub fn layer(&self, name: &str) -> Option<&TiledLayer> {
+        fn find<'a>(layers: &'a [TiledLayer], name: &str) -> Option<&'a TiledLayer> {
+            layers.iter().find_map(|layer| match &layer.kind {
+                _ if layer.name == name => Some(layer),
+                TiledLayerKind::Group(children) => find(children, name),
+                _ => None,
+            })
+        }
+        find(&self.layers, name)
+    }
+    /// All objects of all object layers, also inside groups
+    pub fn objects(&self) -> impl Iterator<Item = &TiledObject> {
+        fn collect<'a>(layers: &'a [TiledLayer], out: &mut Vec<&'a TiledObject>) {
+            for layer in layers {
+                match &layer.kind {
+                    TiledLayerKind::Objects(objects) => out.extend(objects.iter()),
+                    TiledLayerKind::Group(children) => collect(children, out),
+                    TiledLayerKind::Tiles(_) => {}
+                }
+            }
+        }
+        let mut out = Vec::new();
+        collect(&self.layers, &mut out);
+        out.into_iter()
+    }
+    /// An empty tile layer matching the map dimensions
+    pub(crate) fn new_tile_grid(&self) -> Tilemap<TileRef> {
+        Tilemap::new(self.pixel_size(), self.tile_size)
+    }
+    /// Fill a tile layer from raw gids in row-major order
+    pub(crate) fn tile_grid(&self, gids: &[u32]) -> PaddleResult<Tilemap<TileRef>> {
+        let expected = (self.width * self.height) as usize;
+        if gids.len() != expected {
+            return Err(ErrorMessage::technical(format!(
+                "Tile layer has {} tiles, expected {}",
+                gids.len(),
+                expected
+            )));
+        }
+        let mut grid = self.new_tile_grid();
+        for (i, raw) in gids.iter().enumerate() {
+            if let Some(tile) = TileRef::from_raw(*raw) {
+                let coord = (i as u32 % self.width, i as u32 / self.width);
+                grid.set_tile(coord, Tile::empty(Some(tile)));
+            }
+        }
+        Ok(grid)
+    }
+    /// Mark tiles as solid according to their `solid` property.
+    ///
+    /// Must be called again when tilesets change, for example after loading external tilesets.
+    pub(crate) fn update_solid_tiles(&mut self) {
+        let mut layers = std::mem::take(&mut self.layers);
+        for layer in layers.iter_mut() {
+            self.update_solid_in_layer(layer);
+        }
+        self.layers = layers;
+    }
+    fn update_solid_in_layer(&self, layer: &mut TiledLayer) {
+        match &mut layer.kind {
+            TiledLayerKind::Tiles(grid) => {
+                for (_, tile) in grid.tiles_mut() {
+                    if let Some(tile_ref) = tile.value {
+                        let solid = self.tile_properties(tile_ref).and_then(|p| p.get("solid"))
+                            == Some(&PropertyValue::Bool(true));
+                        tile.empty = !solid;
+                    }
+                }
+            }
+            TiledLayerKind::Group(children) => {
+                for child in children.iter_mut() {
+                    self.update_solid_in_layer(child);
+                }
+            }
+            TiledLayerKind::Objects(_) => {}
+        }
+    }
+}
+
+/// Parse colors in the formats `#RRGGBB` and `#AARRGGBB` used by Tiled
+pub(crate) fn parse_color(text: &str) -> PaddleResult<Color> {
+    let hex = text.trim_start_matches('#');
+    let channel = |i: usize| {
+        hex.get(i..i + 2)
+            .and_then(|c| u8::from_str_radix(c, 16).ok())
+            .ok_or_else(|| ErrorMessage::technical(format!("Invalid color '{}'", text)))
+    };
+    match hex.len() {
+        6 => Ok(Color::from_rgba(channel(0)?, channel(2)?, channel(4)?, 1.0)),
+        8 => Ok(Color::from_rgba(
+            channel(2)?,
+            channel(4)?,
+            channel(6)?,
+            channel(0)? as f32 / 255.0,
+        )),
+        _ => Err(ErrorMessage::technical(format!("Invalid color '{}'", text))),
+    }
+}
+
+/// Convert a property from its type name and textual value, as stored in both file formats
+pub(crate) fn parse_property(kind: &str, value: &str) -> PaddleResult<PropertyValue> {
+    let invalid = || ErrorMessage::technical(format!("Invalid {} property '{}'", kind, value));
+    Ok(match kind {
+        "bool" => PropertyValue::Bool(value.parse().map_err(|_| invalid())?),
+        "int" => PropertyValue::Int(value.parse().map_err(|_| invalid())?),
+        "float" => PropertyValue::Float(value.parse().map_err(|_| invalid())?),
+        "color" if value.is_empty() => PropertyValue::Color(Color::BLACK.with_alpha(0.0)),
+        "color" => PropertyValue::Color(parse_color(value)?),
+        "file" => PropertyValue::File(value.to_owned()),
+        "object" => PropertyValue::Object(value.parse().map_err(|_| invalid())?),
+        _ => PropertyValue::String(value.to_owned()),
+    })
+}
@@ -0,0 +1,238 @@
+use super::map::*;
+use crate::quicksilver_compat::Color;
+use crate::{
+    AbstractMesh, AbstractTriangle, AbstractVertex, DisplayArea, Image, Rectangle, Transform,
+    Vector,
+};
+
+/// Draws the tile layers of a `TiledMap`.
+///
+/// Only tiles inside the view are tessellated, into one mesh per layer.
+/// The meshes are kept and reused until the view moves far enough to show different tiles.
+/// Object layers are not drawn, they are meant to be interpreted by the game.
+pub struct TiledRenderer {
+    layers: Vec<AbstractMesh>,
+    cached_range: Option<TileRange>,
+}
+
+/// Tiles from `min` (inclusive) to `max` (exclusive), in tile coordinates
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct TileRange {
+    min: (u32, u32),
+    max: (u32, u32),
+}
+
+impl TiledRenderer {
+    pub fn new() -> Self {
+        TiledRenderer {
+            layers: Vec::new(),
+            cached_range: None,
+        }
+    }
+    /// Rebuild the meshes on the next draw, necessary after the map has been modified
+    pub fn invalidate(&mut self) {
+        self.cached_range = None;
+    }
+    /// Draw the part of the map inside `view`, in map pixels, stretched over the full display area
+    pub fn draw(&mut self, map: &TiledMap, display: &mut DisplayArea, view: Rectangle) {
+        let scale = display.size().times(view.size().recip());
+        let trans = Transform::scale(scale) * Transform::translate(-view.pos);
+        self.draw_ex(map, display, view, trans, 0);
+    }
+    /// Draw the part of the map inside `view`, with a custom transformation from map pixels to the display area
+    pub fn draw_ex(
+        &mut self,
+        map: &TiledMap,
+        display: &mut DisplayArea,
+        view: Rectangle,
+        trans: Transform,
+        z: i16,
+    ) {
+        let range = visible_range(map, view, Vector::ZERO);
+        if self.cached_range != Some(range) {
+            self.rebuild(map, view);
+            self.cached_range = Some(range);
+        }
+        for mesh in &self.layers {
+            display.draw_mesh_ex(mesh, trans, z);
+        }
+    }
+    fn rebuild(&mut self, map: &TiledMap, view: Rectangle) {
+        let mut meshes = std::mem::take(&mut self.layers);
+        meshes.iter_mut().for_each(AbstractMesh::clear);
+        let mut used = 0;
+        let mut stack: Vec<(&TiledLayer, Vector, f32)> = map
+            .layers
+            .iter()
+            .rev()
+            .map(|layer| (layer, Vector::ZERO, 1.0))
+            .collect();
+        while let Some((layer, parent_offset, parent_opacity)) = stack.pop() {
+            if !layer.visible {
+                continue;
+            }
+            let offset = parent_offset + layer.offset;
+            let opacity = parent_opacity * layer.opacity;
+            match &layer.kind {
+                TiledLayerKind::Tiles(grid) => {
+                    if used == meshes.len() {
+                        meshes.push(AbstractMesh::new());
+                    }
+                    let mesh = &mut meshes[used];
+                    used += 1;
+                    // Padded by a tile, because the cache only tracks the range without layer offsets
+                    let padded =
+                        Rectangle::new(view.pos - map.tile_size, view.size + map.tile_size * 2.0);
+                    let range = visible_range(map, padded, offset);
+                    let color = Color::WHITE.with_alpha(opacity);
+                    for y in range.min.1..range.max.1 {
+                        for x in range.min.0..range.max.0 {
+                            let cell = map.tile_size.times((x as f32, y as f32));
+                            if let Some(tile) = grid.tile((x, y)).and_then(|t| t.value) {
+                                add_tile(mesh, map, tile, cell + offset, color);
+                            }
+                        }
+                    }
+                }
+                TiledLayerKind::Group(children) => {
+                    stack.extend(children.iter().rev().map(|c| (c, offset, opacity)));
+                }
+                TiledLayerKind::Objects(_) => {}
+            }
+        }
+        meshes.truncate(used);
+        self.layers = meshes;
+    }
+}
+
+impl Default for TiledRenderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Tiles of a layer with the given offset that overlap the view.
+///
+/// Tiles larger than the grid are anchored at the bottom left of their cell, so the range is extended accordingly.
+fn visible_range(map: &TiledMap, view: Rectangle, offset: Vector) -> TileRange {
+    let largest = map
+        .tilesets
+        .iter()
+        .fold(map.tile_size, |size, set| size.max(set.tile_size));
+    let overhang = largest - map.tile_size;
+    let min = (view.pos - offset - overhang.x_comp()).times(map.tile_size.recip());
+    let max = (view.pos + view.size - offset + overhang.y_comp()).times(map.tile_size.recip());
+    let clamp = |v: f32, limit: u32| (v.max(0.0) as u32).min(limit);
+    TileRange {
+        min: (
+            clamp(min.x.floor(), map.width),
+            clamp(min.y.floor(), map.height),
+        ),
+        max: (
+            clamp(max.x.ceil(), map.width),
+            clamp(max.y.ceil(), map.height),
+        ),
+    }
+}
+
+fn add_tile(mesh: &mut AbstractMesh, map: &TiledMap, tile: TileRef, cell: Vector, col: Color) {
+    let (set, local) = match map.tileset_of(tile.gid) {
+        Some(found) => found,
+        None => return,
+    };
+    let image: &Image = match &set.image {
+        Some(img) => img,
+        None => return,
+    };
+    let tex_trans = image
+        .subimage_texels(set.tile_region(local))
+        .texture_transform();
+    let top_left = cell + Vector::new(0.0, map.tile_size.y - set.tile_size.y);
+    let offset = mesh.vertices.len() as u32;
+    mesh.vertices.extend(
+        [Vector::ZERO, Vector::X, Vector::ONE, Vector::Y]
+            .iter()
+            .map(|corner| AbstractVertex {
+                pos: top_left + corner.times(set.tile_size),
+                tex_pos: Some(tex_trans * flip_corner(*corner, tile)),
+                col,
+            }),
+    );
+    for indices in [[0, 1, 2], [2, 3, 0]] {
+        mesh.triangles.push(AbstractTriangle {
+            indices: indices.map(|i| i + offset),
+            image: Some(image.clone()),
+        });
+    }
+}
+
+/// Texture coordinate shown at a corner of the unit square.
+///
+/// Tiled flips the image diagonally first, then horizontally and vertically.
+/// Mapping screen corners back to the texture undoes them in reverse order.
+fn flip_corner(corner: Vector, tile: TileRef) -> Vector {
+    let mut uv = corner;
+    if tile.flip_horizontal {
+        uv.x = 1.0 - uv.x;
+    }
+    if tile.flip_vertical {
+        uv.y = 1.0 - uv.y;
+    }
+    if tile.flip_diagonal {
+        uv = Vector::new(uv.y, uv.x);
+    }
+    uv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map() -> TiledMap {
+        TiledMap::from_json(
+            r#"{"width": 10, "height": 8, "tilewidth": 16, "tileheight": 16,
+                "tilesets": [{"firstgid": 1, "tilewidth": 16, "tileheight": 32,
+                              "columns": 1, "tilecount": 1, "image": "tall.png"}]}"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn visible_tiles() {
+        let map = map();
+        let range = visible_range(&map, Rectangle::new((20, 20), (40, 20)), Vector::ZERO);
+        // Tiles one row below the view reach into it with their upper half
+        assert_eq!(
+            range,
+            TileRange {
+                min: (1, 1),
+                max: (4, 4)
+            }
+        );
+        let range = visible_range(&map, Rectangle::new((-50, 100), (500, 500)), Vector::ZERO);
+        assert_eq!(
+            range,
+            TileRange {
+                min: (0, 6),
+                max: (10, 8)
+            }
+        );
+        let shifted = visible_range(&map, Rectangle::new((20, 20), (40, 20)), Vector::new(16, 0));
+        assert_eq!(shifted.min, (0, 1));
+        assert_eq!(shifted.max, (3, 4));
+    }
+
+    #[test]
+    fn flips() {
+        let mut tile = TileRef::from_raw(1).unwrap();
+        assert_eq!(flip_corner(Vector::X, tile), Vector::X);
+        tile.flip_horizontal = true;
+        assert_eq!(flip_corner(Vector::ZERO, tile), Vector::X);
+        // Diagonal and horizontal flip together rotate by 90 degrees clockwise
+        // The bottom left of the image ends up in the top left corner
+        tile.flip_diagonal = true;
+        assert_eq!(flip_corner(Vector::ZERO, tile), Vector::Y);
+        assert_eq!(flip_corner(Vector::X, tile), Vector::ZERO);
+        assert_eq!(flip_corner(Vector::ONE, tile), Vector::X);
+    }
+}
@@ -0,0 +1,389 @@
+//! Tiled maps and tilesets in the XML based TMX and TSX formats
+
+use super::data::{decode_base64, decode_csv};
+use super::map::*;
+use crate::{ErrorMessage, PaddleResult, Vector};
+use roxmltree::{Document, Node};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+impl TiledMap {
+    /// Parse a map in the TMX format.
+    ///
+    /// Tilesets stored in separate files are not loaded, use `TiledMap::load` for that.
+    pub fn from_tmx(text: &str) -> PaddleResult<TiledMap> {
+        let doc = Document::parse(text)?;
+        let root = expect_tag(doc.root_element(), "map")?;
+        if attr_or(root, "infinite", 0u8)? != 0 {
+            return Err(ErrorMessage::technical(
+                "Infinite Tiled maps are not supported".to_owned(),
+            ));
+        }
+        let orientation = root.attribute("orientation").unwrap_or("orthogonal");
+        if orientation != "orthogonal" {
+            return Err(ErrorMessage::technical(format!(
+                "Tiled maps with {} orientation are not supported",
+                orientation
+            )));
+        }
+        let mut map = TiledMap {
+            width: attr(root, "width")?,
+            height: attr(root, "height")?,
+            tile_size: Vector::new(
+                attr::<f32>(root, "tilewidth")?,
+                attr::<f32>(root, "tileheight")?,
+            ),
+            layers: Vec::new(),
+            tilesets: root
+                .children()
+                .filter(|n| n.has_tag_name("tileset"))
+                .map(|n| parse_tileset(n, attr(n, "firstgid")?))
+                .collect::<PaddleResult<_>>()?,
+            properties: parse_properties(root)?,
+            background_color: root
+                .attribute("backgroundcolor")
+                .map(parse_color)
+                .transpose()?,
+        };
+        map.layers = parse_layers(&map, root)?;
+        map.update_solid_tiles();
+        Ok(map)
+    }
+}
+
+impl Tileset {
+    /// Parse a tileset stored in a separate TSX file
+    pub fn from_tsx(text: &str, first_gid: u32) -> PaddleResult<Tileset> {
+        let doc = Document::parse(text)?;
+        let root = expect_tag(doc.root_element(), "tileset")?;
+        if root.attribute("source").is_some() {
+            return Err(ErrorMessage::technical(
+                "A TSX file cannot reference another tileset".to_owned(),
+            ));
+        }
+        parse_tileset(root, first_gid)
+    }
+}
+
+fn parse_tileset(node: Node, first_gid: u32) -> PaddleResult<Tileset> {
+    if let Some(source) = node.attribute("source") {
+        return Ok(Tileset::external(first_gid, source.to_owned()));
+    }
+    let name = node.attribute("name").unwrap_or_default().to_owned();
+    let image = child(node, "image").ok_or_else(|| {
+        ErrorMessage::technical(format!(
+            "Tileset '{}' has no tile sheet, image collections are not supported",
+            name
+        ))
+    })?;
+    let tiles = node
+        .children()
+        .filter(|n| n.has_tag_name("tile"))
+        .map(|tile| Ok((attr(tile, "id")?, parse_properties(tile)?)))
+        .collect::<PaddleResult<HashMap<_, _>>>()?;
+    Ok(Tileset {
+        first_gid,
+        name,
+        tile_size: Vector::new(
+            attr::<f32>(node, "tilewidth")?,
+            attr::<f32>(node, "tileheight")?,
+        ),
+        columns: attr_or(node, "columns", 0)?,
+        tile_count: attr_or(node, "tilecount", 0)?,
+        spacing: attr_or(node, "spacing", 0.0)?,
+        margin: attr_or(node, "margin", 0.0)?,
+        image_source: attr(image, "source")?,
+        image_size: Vector::new(
+            attr_or::<f32>(image, "width", 0.0)?,
+            attr_or::<f32>(image, "height", 0.0)?,
+        ),
+        tiles,
+        source: None,
+        image: None,
+    })
+}
+
+/// Layers are children of the map or of a group, image layers are skipped
+fn parse_layers(map: &TiledMap, parent: Node) -> PaddleResult<Vec<TiledLayer>> {
+    let mut layers = Vec::new();
+    for node in parent.children().filter(Node::is_element) {
+        let kind = match node.tag_name().name() {
+            "layer" => TiledLayerKind::Tiles(map.tile_grid(&parse_tile_data(node)?)?),
+            "objectgroup" => TiledLayerKind::Objects(
+                node.children()
+                    .filter(|n| n.has_tag_name("object"))
+                    .map(parse_object)
+                    .collect::<PaddleResult<_>>()?,
+            ),
+            "group" => TiledLayerKind::Group(parse_layers(map, node)?),
+            _ => continue,
+        };
+        layers.push(TiledLayer {
+            name: node.attribute("name").unwrap_or_default().to_owned(),
+            visible: attr_or(node, "visible", 1u8)? != 0,
+            opacity: attr_or(node, "opacity", 1.0)?,
+            offset: Vector::new(
+                attr_or::<f32>(node, "offsetx", 0.0)?,
+                attr_or::<f32>(node, "offsety", 0.0)?,
+            ),
+            properties: parse_properties(node)?,
+            kind,
+        });
+    }
+    Ok(layers)
+}
+
+fn parse_tile_data(layer: Node) -> PaddleResult<Vec<u32>> {
+    let data = child(layer, "data")
+        .ok_or_else(|| ErrorMessage::technical("Tile layer has no data".to_owned()))?;
+    if child(data, "chunk").is_some() {
+        return Err(ErrorMessage::technical(
+            "Infinite Tiled maps are not supported".to_owned(),
+        ));
+    }
+    let text = data.text().unwrap_or_default();
+    match data.attribute("encoding") {
+        Some("csv") => decode_csv(text),
+        Some("base64") => decode_base64(text, data.attribute("compression")),
+        Some(other) => Err(ErrorMessage::technical(format!(
+            "Unsupported tile data encoding '{}'",
+            other
+        ))),
+        None => data
+            .children()
+            .filter(|n| n.has_tag_name("tile"))
+            .map(|tile| attr_or(tile, "gid", 0))
+            .collect(),
+    }
+}
+
+fn parse_object(node: Node) -> PaddleResult<TiledObject> {
+    let shape = if let Some(polygon) = child(node, "polygon") {
+        ObjectShape::Polygon(parse_points(polygon)?)
+    } else if let Some(polyline) = child(node, "polyline") {
+        ObjectShape::Polyline(parse_points(polyline)?)
+    } else if let Some(text) = child(node, "text") {
+        ObjectShape::Text(text.text().unwrap_or_default().to_owned())
+    } else if child(node, "ellipse").is_some() {
+        ObjectShape::Ellipse
+    } else if child(node, "point").is_some() {
+        ObjectShape::Point
+    } else {
+        ObjectShape::Rectangle
+    };
+    Ok(TiledObject {
+        id: attr_or(node, "id", 0)?,
+        name: node.attribute("name").unwrap_or_default().to_owned(),
+        class: node
+            .attribute("class")
+            .or_else(|| node.attribute("type"))
+            .unwrap_or_default()
+            .to_owned(),
+        pos: Vector::new(
+            attr_or::<f32>(node, "x", 0.0)?,
+            attr_or::<f32>(node, "y", 0.0)?,
+        ),
+        size: Vector::new(
+            attr_or::<f32>(node, "width", 0.0)?,
+            attr_or::<f32>(node, "height", 0.0)?,
+        ),
+        rotation: attr_or(node, "rotation", 0.0)?,
+        visible: attr_or(node, "visible", 1u8)? != 0,
+        tile: node
+            .attribute("gid")
+            .map(|_| attr(node, "gid"))
+            .transpose()?
+            .and_then(TileRef::from_raw),
+        shape,
+        properties: parse_properties(node)?,
+    })
+}
+
+/// Points in the format `x1,y1 x2,y2 ...`
+fn parse_points(node: Node) -> PaddleResult<Vec<Vector>> {
+    let text: String = attr(node, "points")?;
+    text.split_whitespace()
+        .map(|pair| {
+            let mut coords = pair.split(',').map(f32::from_str);
+            match (coords.next(), coords.next(), coords.next()) {
+                (Some(Ok(x)), Some(Ok(y)), None) => Ok(Vector::new(x, y)),
+                _ => Err(ErrorMessage::technical(format!("Invalid point '{}'", pair))),
+            }
+        })
+        .collect()
+}
+
+/// Properties of an element, multi-line strings are stored as text instead of an attribute
+fn parse_properties(node: Node) -> PaddleResult<Properties> {
+    let properties = match child(node, "properties") {
+        Some(p) => p,
+        None => return Ok(Properties::new()),
+    };
+    properties
+        .children()
+        .filter(|n| n.has_tag_name("property"))
+        .map(|p| {
+            let name: String = attr(p, "name")?;
+            let kind = p.attribute("type").unwrap_or("string");
+            let value = p
+                .attribute("value")
+                .or_else(|| p.text())
+                .unwrap_or_default();
+            Ok((name, parse_property(kind, value)?))
+        })
+        .collect()
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, tag: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|n| n.has_tag_name(tag))
+}
+
+fn expect_tag<'a, 'input>(node: Node<'a, 'input>, tag: &str) -> PaddleResult<Node<'a, 'input>> {
+    if node.has_tag_name(tag) {
+        Ok(node)
+    } else {
+        Err(ErrorMessage::technical(format!(
+            "Expected <{}> but found <{}>",
+            tag,
+            node.tag_name().name()
+        )))
+    }
+}
+
+fn attr<T: FromStr>(node: Node, name: &str) -> PaddleResult<T> {
+    let value = node.attribute(name).ok_or_else(|| {
+        ErrorMessage::technical(format!(
+            "<{}> is missing attribute '{}'",
+            node.tag_name().name(),
+            name
+        ))
+    })?;
+    parse_attr(node, name, value)
+}
+
+fn attr_or<T: FromStr>(node: Node, name: &str, default: T) -> PaddleResult<T> {
+    match node.attribute(name) {
+        Some(value) => parse_attr(node, name, value),
+        None => Ok(default),
+    }
+}
+
+fn parse_attr<T: FromStr>(node: Node, name: &str, value: &str) -> PaddleResult<T> {
+    value.parse().map_err(|_| {
+        ErrorMessage::technical(format!(
+            "<{}> has invalid attribute {}=\"{}\"",
+            node.tag_name().name(),
+            name,
+            value
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAP: &str = r##"<?xml version="1.0" encoding="UTF-8"?>
+<map version="1.10" orientation="orthogonal" renderorder="right-down" width="2" height="2" tilewidth="32" tileheight="32" infinite="0">
+ <properties>
+  <property name="title" value="Cave"/>
+  <property name="story">Once upon
+a time</property>
+ </properties>
+ <tileset firstgid="1" name="cave" tilewidth="32" tileheight="32" tilecount="4" columns="2">
+  <image source="cave.png" width="64" height="64"/>
+  <tile id="3">
+   <properties>
+    <property name="solid" type="bool" value="true"/>
+   </properties>
+  </tile>
+ </tileset>
+ <tileset firstgid="5" source="props.tsx"/>
+ <layer id="1" name="floor" width="2" height="2">
+  <data encoding="csv">
+1,2,
+0,4
+</data>
+ </layer>
+ <group name="top" opacity="0.5">
+  <layer id="2" name="zlib" width="2" height="2" visible="0">
+   <data encoding="base64" compression="zlib">eJxjYEAAViAGAAAkAAY=</data>
+  </layer>
+ </group>
+ <objectgroup name="things">
+  <object id="4" name="door" class="portal" x="10" y="20" width="32" height="64">
+   <properties>
+    <property name="target" type="object" value="7"/>
+   </properties>
+  </object>
+  <object id="5" x="0" y="0">
+   <polyline points="0,0 10,5 20,0"/>
+  </object>
+  <object id="6" gid="1073741829" x="0" y="32" width="32" height="32"/>
+ </objectgroup>
+</map>"##;
+
+    #[test]
+    fn parse_map() {
+        // 3 tiles do not match the map size
+        let broken = MAP.replace("eJxjYEAAViAGAAAkAAY=", "eJxjZGBgYAJiZgaGBgAAtACH");
+        assert!(TiledMap::from_tmx(&broken).is_err());
+        let map = TiledMap::from_tmx(MAP).unwrap();
+        assert_eq!(map.tile_size, Vector::new(32, 32));
+        assert_eq!(
+            map.properties["title"],
+            PropertyValue::String("Cave".to_owned())
+        );
+        assert_eq!(
+            map.properties["story"],
+            PropertyValue::String("Once upon\na time".to_owned())
+        );
+        assert_eq!(map.tilesets.len(), 2);
+        assert_eq!(map.tilesets[0].image_source, "cave.png");
+        assert_eq!(map.tilesets[1].source.as_deref(), Some("props.tsx"));
+
+        let floor = match &map.layers[0].kind {
+            TiledLayerKind::Tiles(grid) => grid,
+            _ => panic!("Not a tile layer"),
+        };
+        assert_eq!(floor.get((32, 0)).unwrap().value.unwrap().gid, 2);
+        assert!(floor.get((0, 32)).unwrap().value.is_none());
+        assert!(!floor.get((32, 32)).unwrap().empty);
+
+        let top = &map.layers[1];
+        assert_eq!(top.opacity, 0.5);
+        let zlib = map.layer("zlib").unwrap();
+        assert!(!zlib.visible);
+        match &zlib.kind {
+            TiledLayerKind::Tiles(grid) => {
+                assert_eq!(grid.get((32, 32)).unwrap().value.unwrap().gid, 5)
+            }
+            _ => panic!("Not a tile layer"),
+        }
+
+        let objects: Vec<_> = map.objects().collect();
+        assert_eq!(objects[0].class, "portal");
+        assert_eq!(objects[0].properties["target"], PropertyValue::Object(7));
+        assert_eq!(
+            objects[1].shape,
+            ObjectShape::Polyline(vec![
+                Vector::new(0, 0),
+                Vector::new(10, 5),
+                Vector::new(20, 0)
+            ])
+        );
+        let tile = objects[2].tile.unwrap();
+        assert_eq!(tile.gid, 5);
+        assert!(tile.flip_vertical);
+    }
+
+    #[test]
+    fn external_tileset() {
+        let tsx = r#"<tileset name="props" tilewidth="16" tileheight="8" spacing="2" tilecount="6" columns="3">
+            <image source="props.png" width="52" height="18"/>
+        </tileset>"#;
+        let set = Tileset::from_tsx(tsx, 5).unwrap();
+        assert!(set.contains(5) && set.contains(10) && !set.contains(11));
+        assert_eq!(set.tile_region(4), crate::Rectangle::new((18, 10), (16, 8)));
+    }
+}
@@ -0,0 +1,243 @@
+//! Immediate-mode UI widgets, drawn inside frames.
+//!
+//! A `Ui` is stored as a field of a frame. Pointer and key events are fed to it from the frame's `pointer` and `key` methods.
+//! Inside `draw`, calling `Ui::begin` gives a `UiPainter` which draws widgets and reports interactions right away.
+//!
+//! ```no_run
+//! # use paddle::*;
+//! struct Menu {
+//!     ui: Ui,
+//!     volume: f32,
+//! }
+//! impl Frame for Menu {
+//!     type State = ();
+//!     const WIDTH: u32 = 400;
+//!     const HEIGHT: u32 = 300;
+//!     fn draw(&mut self, _state: &mut (), display: &mut DisplayArea, _timestamp: f64) {
+//!         let mut rows = VerticalStack::new(Self::area().padded(20.0), 10.0);
+//!         let mut ui = self.ui.begin(display);
+//!         if ui.button("start", rows.next(50.0), "Start") {
+//!             // start the game
+//!         }
+//!         ui.slider("volume", rows.next(30.0), &mut self.volume, 0.0, 1.0);
+//!     }
+//!     fn pointer(&mut self, _state: &mut (), event: PointerEvent) {
+//!         self.ui.track_pointer_event(&event);
+//!     }
+//!     fn key(&mut self, _state: &mut (), event: KeyEvent) {
+//!         self.ui.track_key_event(&event);
+//!     }
+//! }
+//! ```
+
+mod interaction;
+mod layout;
+mod painter;
+mod text_input;
+mod theme;
+
+pub use interaction::*;
+pub use layout::*;
+pub use painter::*;
+pub use theme::*;
+
+use crate::quicksilver_compat::Shape;
+use crate::{DisplayArea, KeyEvent, PointerEvent, PointerTracker, Rectangle, TextPool, Vector};
+
+/// State of all widgets of one frame that has to survive between draw calls.
+pub struct Ui {
+    theme: Theme,
+    pointer: PointerTracker,
+    input: UiInput,
+    /// Widget that is currently held down by the pointer
+    active: Option<WidgetId>,
+    /// Widget that receives keyboard input
+    focused: Option<WidgetId>,
+    /// Area of the focused widget in the last draw, to detect clicks outside of it
+    focus_area: Option<Rectangle>,
+    labels: TextPool,
+}
+
+impl Ui {
+    pub fn new() -> Self {
+        Self::with_theme(Theme::default())
+    }
+    pub fn with_theme(theme: Theme) -> Self {
+        let labels = theme.text_pool();
+        Self {
+            theme,
+            pointer: PointerTracker::new(),
+            input: UiInput::default(),
+            active: None,
+            focused: None,
+            focus_area: None,
+            labels,
+        }
+    }
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+    /// Replace the theme. Labels are recreated to pick up the new text style.
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.labels.hide();
+        self.labels = theme.text_pool();
+        self.theme = theme;
+    }
+    /// Call this from `Frame::pointer` with every event the frame receives.
+    pub fn track_pointer_event(&mut self, event: &PointerEvent) {
+        self.pointer.track_pointer_event(event);
+        self.input.track_pointer_event(event);
+    }
+    /// Call this from `Frame::key` with every event the frame receives.
+    ///
+    /// Key events are only recorded while a widget has the keyboard focus.
+    pub fn track_key_event(&mut self, event: &KeyEvent) {
+        self.input.track_key_event(event, self.focused.is_some());
+    }
+    /// Last known pointer position, in frame coordinates
+    pub fn pointer_pos(&self) -> Option<Vector> {
+        self.pointer.pos()
+    }
+    /// The widget that currently receives keyboard input, if any.
+    pub fn focused(&self) -> Option<WidgetId> {
+        self.focused
+    }
+    pub fn set_focus(&mut self, id: impl Into<WidgetId>) {
+        self.focused = Some(id.into());
+    }
+    pub fn clear_focus(&mut self) {
+        self.focused = None;
+        self.focus_area = None;
+        self.input.keys.clear();
+    }
+    /// Start drawing widgets to the display area.
+    ///
+    /// Interactions are finalized when the returned painter is dropped.
+    pub fn begin<'a>(&'a mut self, display: &'a mut DisplayArea) -> UiPainter<'a> {
+        self.labels.reset();
+        UiPainter::new(self, display)
+    }
+
+    /// Computes the interaction state of a widget placed at `area` (in frame coordinates) for the current draw.
+    ///
+    /// This is the building block of all widgets and can be used to implement custom widgets.
+    pub fn interact(&mut self, id: impl Into<WidgetId>, area: &Rectangle) -> Interaction {
+        let id = id.into();
+        let hovered = self.pointer.pos().is_some_and(|p| area.contains(p));
+        if self.active.is_none() {
+            if let Some(p) = self.input.pressed_at {
+                if area.contains(p) {
+                    self.active = Some(id);
+                }
+            }
+        }
+        let state = if self.active == Some(id) && self.pointer.is_pressed() {
+            WidgetState::Pressed
+        } else if hovered {
+            WidgetState::Hovered
+        } else {
+            WidgetState::Normal
+        };
+        let clicked = match self.input.click {
+            Some(p) if area.contains(p) => {
+                self.input.click = None;
+                true
+            }
+            _ => false,
+        };
+        Interaction { state, clicked }
+    }
+
+    /// Clears all per-draw input. Called automatically when a `UiPainter` is dropped.
+    pub fn end_frame(&mut self) {
+        if let Some(click) = self.input.frame_click.take() {
+            let inside_focus = self.focus_area.is_some_and(|area| area.contains(click));
+            if !inside_focus {
+                self.clear_focus();
+            }
+        }
+        self.input.pressed_at = None;
+        self.input.click = None;
+        if !self.pointer.is_pressed() {
+            self.active = None;
+        }
+        self.labels.finish_draw();
+    }
+}
+
+impl Default for Ui {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Key, KeyEventType, PointerEventType};
+
+    fn event(t: PointerEventType, x: f32, y: f32) -> PointerEvent {
+        PointerEvent(t, Vector::new(x, y))
+    }
+
+    #[test]
+    fn hover_and_press() {
+        let mut ui = Ui::new();
+        let area = Rectangle::new((10, 10), (50, 20));
+        assert_eq!(ui.interact("b", &area).state, WidgetState::Normal);
+        ui.end_frame();
+
+        ui.track_pointer_event(&event(PointerEventType::Move, 20.0, 15.0));
+        assert_eq!(ui.interact("b", &area).state, WidgetState::Hovered);
+        ui.end_frame();
+
+        ui.track_pointer_event(&event(PointerEventType::Down, 20.0, 15.0));
+        assert_eq!(ui.interact("b", &area).state, WidgetState::Pressed);
+        ui.end_frame();
+
+        // Stays pressed while the pointer is held, even outside the widget
+        ui.track_pointer_event(&event(PointerEventType::Move, 200.0, 15.0));
+        assert_eq!(ui.interact("b", &area).state, WidgetState::Pressed);
+        ui.end_frame();
+
+        ui.track_pointer_event(&event(PointerEventType::Up, 200.0, 15.0));
+        assert_eq!(ui.interact("b", &area).state, WidgetState::Normal);
+        ui.end_frame();
+    }
+
+    #[test]
+    fn click_is_consumed_once() {
+        let mut ui = Ui::new();
+        let area = Rectangle::new((0, 0), (50, 50));
+        ui.track_pointer_event(&event(PointerEventType::PrimaryClick, 5.0, 5.0));
+        assert!(ui.interact("a", &area).clicked);
+        assert!(!ui.interact("b", &area).clicked);
+        ui.end_frame();
+        assert!(!ui.interact("a", &area).clicked);
+    }
+
+    #[test]
+    fn press_outside_does_not_activate() {
+        let mut ui = Ui::new();
+        let area = Rectangle::new((0, 0), (50, 50));
+        ui.track_pointer_event(&event(PointerEventType::Down, 100.0, 100.0));
+        ui.track_pointer_event(&event(PointerEventType::Move, 10.0, 10.0));
+        assert_eq!(ui.interact("a", &area).state, WidgetState::Hovered);
+    }
+
+    #[test]
+    fn focus_is_lost_on_click_elsewhere() {
+        let mut ui = Ui::new();
+        ui.set_focus("text");
+        ui.focus_area = Some(Rectangle::new((0, 0), (50, 20)));
+        ui.track_key_event(&KeyEvent(KeyEventType::KeyDown, Key::KeyA));
+        assert_eq!(ui.input.keys.len(), 1);
+        ui.track_pointer_event(&event(PointerEventType::PrimaryClick, 5.0, 5.0));
+        ui.end_frame();
+        assert_eq!(ui.focused(), Some(WidgetId::from("text")));
+        ui.track_pointer_event(&event(PointerEventType::PrimaryClick, 5.0, 500.0));
+        ui.end_frame();
+        assert_eq!(ui.focused(), None);
+        assert!(ui.input.keys.is_empty());
+    }
+}
@@ -0,0 +1,91 @@
+use crate::{Key, KeyEvent, KeyEventType, PointerEvent, PointerEventType, Vector};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// Identifies a widget across draw calls.
+///
+/// Any hashable value can be used, as long as it is unique within the `Ui`.
+/// For lists of widgets, a tuple like `("item", index)` works well.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct WidgetId(u64);
+
+impl WidgetId {
+    pub fn new(key: impl Hash) -> Self {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        WidgetId(hasher.finish())
+    }
+}
+
+impl From<&str> for WidgetId {
+    fn from(key: &str) -> Self {
+        WidgetId::new(key)
+    }
+}
+impl From<usize> for WidgetId {
+    fn from(key: usize) -> Self {
+        WidgetId::new(key)
+    }
+}
+impl From<(&str, usize)> for WidgetId {
+    fn from(key: (&str, usize)) -> Self {
+        WidgetId::new(key)
+    }
+}
+
+/// Visual state of a widget
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum WidgetState {
+    Normal,
+    /// Pointer is above the widget
+    Hovered,
+    /// Pointer went down on the widget and has not been released, yet
+    Pressed,
+}
+
+/// Result of `Ui::interact` for a single widget in a single draw.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Interaction {
+    pub state: WidgetState,
+    /// A primary click landed on the widget since the last draw
+    pub clicked: bool,
+}
+
+/// Input collected between two draws
+#[derive(Default)]
+pub(super) struct UiInput {
+    /// Position of a pointer down event since the last draw
+    pub pressed_at: Option<Vector>,
+    /// Click that has not been consumed by a widget, yet
+    pub click: Option<Vector>,
+    /// Any click since the last draw, consumed or not
+    pub frame_click: Option<Vector>,
+    /// Key presses for the focused widget
+    pub keys: Vec<Key>,
+    pub shift: bool,
+}
+
+impl UiInput {
+    pub fn track_pointer_event(&mut self, event: &PointerEvent) {
+        match event.event_type() {
+            PointerEventType::Down => self.pressed_at = Some(event.pos()),
+            PointerEventType::PrimaryClick => {
+                self.click = Some(event.pos());
+                self.frame_click = Some(event.pos());
+            }
+            _ => { /* NOP */ }
+        }
+    }
+    pub fn track_key_event(&mut self, event: &KeyEvent, has_focus: bool) {
+        match (event.event_type(), event.key()) {
+            (KeyEventType::KeyDown, Key::ShiftLeft) | (KeyEventType::KeyDown, Key::ShiftRight) => {
+                self.shift = true
+            }
+            (KeyEventType::KeyUp, Key::ShiftLeft) | (KeyEventType::KeyUp, Key::ShiftRight) => {
+                self.shift = false
+            }
+            (KeyEventType::KeyDown, key) if has_focus => self.keys.push(key),
+            _ => { /* NOP */ }
+        }
+    }
+}
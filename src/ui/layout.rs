@@ -0,0 +1,96 @@
+//! Small helpers to place widgets without computing coordinates by hand.
+
+use crate::Rectangle;
+
+/// Hands out rows from top to bottom, with a fixed spacing in between.
+pub struct VerticalStack {
+    rest: Rectangle,
+    spacing: f32,
+}
+
+impl VerticalStack {
+    pub fn new(area: Rectangle, spacing: f32) -> Self {
+        Self {
+            rest: area,
+            spacing,
+        }
+    }
+    /// Takes the next row with the given height.
+    pub fn next(&mut self, h: f32) -> Rectangle {
+        let h = h.min(self.rest.height()).max(0.0);
+        let (row, rest) = self.rest.cut_horizontal(h);
+        let spacing = self.spacing.min(rest.height()).max(0.0);
+        self.rest = rest.cut_horizontal(spacing).1;
+        row
+    }
+    /// The area that has not been handed out, yet.
+    pub fn remaining(&self) -> Rectangle {
+        self.rest
+    }
+}
+
+/// Hands out columns from left to right, with a fixed spacing in between.
+pub struct HorizontalStack {
+    rest: Rectangle,
+    spacing: f32,
+}
+
+impl HorizontalStack {
+    pub fn new(area: Rectangle, spacing: f32) -> Self {
+        Self {
+            rest: area,
+            spacing,
+        }
+    }
+    /// Takes the next column with the given width.
+    pub fn next(&mut self, w: f32) -> Rectangle {
+        let w = w.min(self.rest.width()).max(0.0);
+        let (col, rest) = self.rest.cut_vertical(w);
+        let spacing = self.spacing.min(rest.width()).max(0.0);
+        self.rest = rest.cut_vertical(spacing).1;
+        col
+    }
+    /// The area that has not been handed out, yet.
+    pub fn remaining(&self) -> Rectangle {
+        self.rest
+    }
+}
+
+/// Splits an area into equally sized cells, row by row, leaving a gap between cells.
+pub fn grid_cells(area: &Rectangle, cols: usize, rows: usize, gap: f32) -> Vec<Rectangle> {
+    area.grid(cols, rows)
+        .map(|cell| cell.padded(gap / 2.0))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Vector;
+
+    #[test]
+    fn vertical_stack() {
+        let mut stack = VerticalStack::new(Rectangle::new((10, 10), (100, 100)), 5.0);
+        assert_eq!(stack.next(20.0), Rectangle::new((10, 10), (100, 20)));
+        assert_eq!(stack.next(20.0), Rectangle::new((10, 35), (100, 20)));
+        assert_eq!(stack.remaining(), Rectangle::new((10, 60), (100, 50)));
+        assert_eq!(stack.next(80.0), Rectangle::new((10, 60), (100, 50)));
+        assert_eq!(stack.next(10.0).size(), Vector::new(100, 0));
+    }
+
+    #[test]
+    fn horizontal_stack() {
+        let mut stack = HorizontalStack::new(Rectangle::new_sized((100, 10)), 10.0);
+        assert_eq!(stack.next(40.0), Rectangle::new((0, 0), (40, 10)));
+        assert_eq!(stack.next(40.0), Rectangle::new((50, 0), (40, 10)));
+        assert_eq!(stack.remaining().width(), 0.0);
+    }
+
+    #[test]
+    fn grid() {
+        let cells = grid_cells(&Rectangle::new_sized((100, 50)), 2, 1, 10.0);
+        assert_eq!(cells.len(), 2);
+        assert_eq!(cells[0], Rectangle::new((5, 5), (40, 40)));
+        assert_eq!(cells[1], Rectangle::new((55, 5), (40, 40)));
+    }
+}
@@ -0,0 +1,389 @@
+use super::text_input::apply_keys;
+use super::{Ui, WidgetId, WidgetState};
+use crate::quicksilver_compat::{Color, Shape};
+use crate::{DisplayArea, FitStrategy, NutsCheck, Rectangle, Vector};
+
+/// Maximum number of characters a text field accepts
+const TEXT_FIELD_MAX_LEN: usize = 256;
+
+/// Draws widgets of a `Ui` to a display area. Obtained from `Ui::begin`.
+///
+/// All areas are in frame coordinates.
+/// Each widget method draws the widget and reports what the user did with it since the last draw.
+pub struct UiPainter<'a> {
+    ui: &'a mut Ui,
+    display: &'a mut DisplayArea,
+    /// Shift applied to all widgets, used inside scroll areas
+    offset: Vector,
+    /// Widgets completely outside of this area are skipped
+    clip: Option<Rectangle>,
+}
+
+/// Scroll position of a scroll area, to be kept between draws.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct ScrollState {
+    offset: f32,
+}
+
+impl ScrollState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// How far the content has been scrolled down
+    pub fn offset(&self) -> f32 {
+        self.offset
+    }
+    /// Scroll by `dy` (positive values scroll down), limited to the content size.
+    pub fn scroll(&mut self, dy: f32, visible_height: f32, content_height: f32) {
+        let max = (content_height - visible_height).max(0.0);
+        self.offset = (self.offset + dy).max(0.0).min(max);
+    }
+}
+
+impl<'a> UiPainter<'a> {
+    pub(super) fn new(ui: &'a mut Ui, display: &'a mut DisplayArea) -> Self {
+        Self {
+            ui,
+            display,
+            offset: Vector::ZERO,
+            clip: None,
+        }
+    }
+    /// Direct access to the display area, to draw custom content in between widgets.
+    pub fn display(&mut self) -> &mut DisplayArea {
+        self.display
+    }
+    pub fn ui(&mut self) -> &mut Ui {
+        self.ui
+    }
+
+    /// Fills the area with the theme's background color
+    pub fn panel(&mut self, area: Rectangle) {
+        if let Some(area) = self.place(area) {
+            let col = self.ui.theme.background;
+            self.display.draw(&area, col);
+        }
+    }
+
+    /// Text without any interaction
+    pub fn label(&mut self, area: Rectangle, text: &str, fit: FitStrategy) {
+        if let Some(area) = self.place(area) {
+            self.write(&area, text, fit);
+        }
+    }
+
+    /// A clickable button. Returns true when it has been clicked.
+    pub fn button(&mut self, id: impl Into<WidgetId>, area: Rectangle, text: &str) -> bool {
+        let area = match self.place(area) {
+            Some(area) => area,
+            None => return false,
+        };
+        let interaction = self.ui.interact(id, &area);
+        let col = self.ui.theme.widget_color(interaction.state);
+        self.display.draw(&area, col);
+        self.write(&area, text, FitStrategy::Center);
+        interaction.clicked
+    }
+
+    /// A check box with a label to the right. Returns true when the value has been changed.
+    pub fn toggle(
+        &mut self,
+        id: impl Into<WidgetId>,
+        area: Rectangle,
+        value: &mut bool,
+        text: &str,
+    ) -> bool {
+        let area = match self.place(area) {
+            Some(area) => area,
+            None => return false,
+        };
+        let interaction = self.ui.interact(id, &area);
+        if interaction.clicked {
+            *value = !*value;
+        }
+        let theme = &self.ui.theme;
+        let box_area = area.fit_square(FitStrategy::LeftCenter);
+        let box_col = theme.widget_color(interaction.state);
+        let check_col = theme.accent;
+        let check_area = box_area.shrink_to_center(0.6);
+        self.display.draw(&box_area, box_col);
+        if *value {
+            self.display.draw(&check_area, check_col);
+        }
+        let (_, label_area) = area.cut_vertical(box_area.width() * 1.25);
+        self.write(&label_area, text, FitStrategy::LeftCenter);
+        interaction.clicked
+    }
+
+    /// A horizontal slider to pick a value between `min` and `max`. Returns true when the value has been changed.
+    pub fn slider(
+        &mut self,
+        id: impl Into<WidgetId>,
+        area: Rectangle,
+        value: &mut f32,
+        min: f32,
+        max: f32,
+    ) -> bool {
+        let area = match self.place(area) {
+            Some(area) => area,
+            None => return false,
+        };
+        let interaction = self.ui.interact(id, &area);
+        let mut changed = false;
+        if interaction.state == WidgetState::Pressed || interaction.clicked {
+            if let Some(pos) = self.ui.pointer.pos() {
+                let new_value = slider_value(&area, pos, min, max);
+                changed = new_value != *value;
+                *value = new_value;
+            }
+        }
+        let theme = &self.ui.theme;
+        let ratio = if max > min {
+            ((*value - min) / (max - min)).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let knob_w = area.height() / 2.0;
+        let track = Rectangle::new(
+            (area.x(), area.center().y - theme.stroke),
+            (area.width(), 2.0 * theme.stroke),
+        );
+        let (filled, _) = track.cut_vertical(track.width() * ratio);
+        let knob = Rectangle::new(
+            (area.x() + (area.width() - knob_w) * ratio, area.y()),
+            (knob_w, area.height()),
+        );
+        let (track_col, accent_col, knob_col) = (
+            theme.track,
+            theme.accent,
+            theme.widget_color(interaction.state),
+        );
+        self.display.draw(&track, track_col);
+        self.display.draw(&filled, accent_col);
+        self.display.draw(&knob, knob_col);
+        changed
+    }
+
+    /// A single line of editable text. Clicking on it gives it the keyboard focus.
+    ///
+    /// Returns true when the text has been changed. Pressing enter removes the focus.
+    pub fn text_field(
+        &mut self,
+        id: impl Into<WidgetId>,
+        area: Rectangle,
+        text: &mut String,
+    ) -> bool {
+        let id = id.into();
+        let area = match self.place(area) {
+            Some(area) => area,
+            None => return false,
+        };
+        let interaction = self.ui.interact(id, &area);
+        if interaction.clicked {
+            self.ui.focused = Some(id);
+        }
+        let mut changed = false;
+        let focused = self.ui.focused == Some(id);
+        if focused {
+            self.ui.focus_area = Some(area);
+            let keys = std::mem::take(&mut self.ui.input.keys);
+            let (c, submitted) = apply_keys(text, &keys, self.ui.input.shift, TEXT_FIELD_MAX_LEN);
+            changed = c;
+            if submitted {
+                self.ui.clear_focus();
+            }
+        }
+        let theme = &self.ui.theme;
+        let (field_col, focus_col, stroke) = (theme.field, theme.focus, theme.stroke);
+        if focused {
+            self.display.draw(&area, focus_col);
+            self.display.draw(&area.padded(stroke), field_col);
+        } else {
+            self.display.draw(&area, field_col);
+        }
+        self.write(&area.padded(stroke * 2.0), text, FitStrategy::LeftCenter);
+        changed
+    }
+
+    /// A vertically scrollable area. Content is placed with `content`, in coordinates relative to the top left of the scrolled content.
+    ///
+    /// Scrolling works by dragging. Widgets fully outside the visible area are skipped.
+    /// Note that other drawings are not clipped, anything partially visible is drawn completely.
+    pub fn scroll_area(
+        &mut self,
+        id: impl Into<WidgetId>,
+        area: Rectangle,
+        content_height: f32,
+        scroll: &mut ScrollState,
+        content: impl FnOnce(&mut UiPainter),
+    ) {
+        let area = match self.place(area) {
+            Some(area) => area,
+            None => return,
+        };
+        let id = id.into();
+        drag_scroll(self.ui, id, &area, content_height, scroll);
+
+        let outer_offset = self.offset;
+        let outer_clip = self.clip;
+        self.offset = area.pos - Vector::new(0.0, scroll.offset());
+        self.clip = Some(area);
+        content(self);
+        self.offset = outer_offset;
+        self.clip = outer_clip;
+
+        // Widgets inside have been placed first, so the area only takes presses and clicks they left
+        claim_scroll(self.ui, id, &area);
+
+        if content_height > area.height() {
+            let theme = &self.ui.theme;
+            let bar_w = theme.stroke * 2.0;
+            let bar_h = area.height() * area.height() / content_height;
+            let bar_y = area.y()
+                + (area.height() - bar_h) * scroll.offset() / (content_height - area.height());
+            let bar = Rectangle::new((area.x() + area.width() - bar_w, bar_y), (bar_w, bar_h));
+            let col = theme.track;
+            self.display.draw(&bar, col);
+        }
+    }
+
+    /// Applies scroll offset and clipping
+    fn place(&self, area: Rectangle) -> Option<Rectangle> {
+        let area = area.translate(self.offset);
+        match self.clip {
+            Some(clip) if !clip.overlaps_rectangle(&area) => None,
+            _ => Some(area),
+        }
+    }
+    fn write(&mut self, area: &Rectangle, text: &str, fit: FitStrategy) {
+        if text.is_empty() {
+            return;
+        }
+        self.ui
+            .labels
+            .allocate()
+            .write(self.display, area, 0, fit, text)
+            .nuts_check();
+    }
+    /// Draws a plain colored rectangle, respecting scroll offset and clipping.
+    pub fn rectangle(&mut self, area: Rectangle, col: Color) {
+        if let Some(area) = self.place(area) {
+            self.display.draw(&area, col);
+        }
+    }
+}
+
+impl Drop for UiPainter<'_> {
+    fn drop(&mut self) {
+        self.ui.end_frame();
+    }
+}
+
+/// Scrolls by the pointer movement while the scroll area is held down
+fn drag_scroll(
+    ui: &mut Ui,
+    id: WidgetId,
+    area: &Rectangle,
+    content_height: f32,
+    scroll: &mut ScrollState,
+) {
+    if ui.active == Some(id) && ui.pointer.is_pressed() {
+        if let Some((from, to)) = ui.pointer.take_drag() {
+            scroll.scroll(from.y - to.y, area.height(), content_height);
+        }
+    }
+    // Re-apply limits in case the content shrunk
+    scroll.scroll(0.0, area.height(), content_height);
+}
+
+/// Interaction of the scroll area itself, after all widgets inside it
+fn claim_scroll(ui: &mut Ui, id: WidgetId, area: &Rectangle) {
+    let was_active = ui.active == Some(id);
+    ui.interact(id, area);
+    if !was_active && ui.active == Some(id) {
+        // Movement left over from dragging other widgets must not scroll
+        ui.pointer.take_drag();
+    }
+}
+
+/// Value of a slider for a pointer at `pos`, clamped to the slider range
+fn slider_value(area: &Rectangle, pos: Vector, min: f32, max: f32) -> f32 {
+    let ratio = ((pos.x - area.x()) / area.width()).clamp(0.0, 1.0);
+    min + ratio * (max - min)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::Interaction;
+    use crate::{PointerEvent, PointerEventType};
+
+    #[test]
+    fn slider_values() {
+        let area = Rectangle::new((100, 0), (200, 10));
+        assert_eq!(slider_value(&area, Vector::new(100, 5), 0.0, 1.0), 0.0);
+        assert_eq!(slider_value(&area, Vector::new(200, 5), 0.0, 1.0), 0.5);
+        assert_eq!(slider_value(&area, Vector::new(500, 5), 0.0, 1.0), 1.0);
+        assert_eq!(slider_value(&area, Vector::new(0, 5), -10.0, 10.0), -10.0);
+        assert_eq!(slider_value(&area, Vector::new(150, 5), -10.0, 10.0), -5.0);
+    }
+
+    #[test]
+    fn scroll_limits() {
+        let mut scroll = ScrollState::new();
+        scroll.scroll(50.0, 100.0, 300.0);
+        assert_eq!(scroll.offset(), 50.0);
+        scroll.scroll(500.0, 100.0, 300.0);
+        assert_eq!(scroll.offset(), 200.0);
+        scroll.scroll(-500.0, 100.0, 300.0);
+        assert_eq!(scroll.offset(), 0.0);
+        scroll.scroll(50.0, 100.0, 80.0);
+        assert_eq!(scroll.offset(), 0.0);
+    }
+
+    /// Interactions of a scroll area with a button and a slider inside, in the order `scroll_area` runs them
+    fn scroll_frame(ui: &mut Ui, scroll: &mut ScrollState) -> (Interaction, Interaction) {
+        let area = Rectangle::new((0, 0), (100, 100));
+        drag_scroll(ui, WidgetId::from("scroll"), &area, 300.0, scroll);
+        let offset = Vector::new(0.0, -scroll.offset());
+        let button = ui.interact(
+            "button",
+            &Rectangle::new((10, 10), (50, 20)).translate(offset),
+        );
+        let slider = ui.interact(
+            "slider",
+            &Rectangle::new((10, 50), (80, 20)).translate(offset),
+        );
+        claim_scroll(ui, WidgetId::from("scroll"), &area);
+        ui.end_frame();
+        (button, slider)
+    }
+
+    #[test]
+    fn widgets_inside_scroll_area() {
+        let event = |t, x, y| PointerEvent(t, Vector::new(x, y));
+        let mut ui = Ui::new();
+        let mut scroll = ScrollState::new();
+
+        ui.track_pointer_event(&event(PointerEventType::PrimaryClick, 20.0, 15.0));
+        assert!(scroll_frame(&mut ui, &mut scroll).0.clicked);
+
+        ui.track_pointer_event(&event(PointerEventType::Down, 20.0, 60.0));
+        let (_, slider) = scroll_frame(&mut ui, &mut scroll);
+        assert_eq!(slider.state, WidgetState::Pressed);
+        // Dragging the slider does not scroll
+        ui.track_pointer_event(&event(PointerEventType::Move, 20.0, 30.0));
+        let (_, slider) = scroll_frame(&mut ui, &mut scroll);
+        assert_eq!(slider.state, WidgetState::Pressed);
+        assert_eq!(scroll.offset(), 0.0);
+        ui.track_pointer_event(&event(PointerEventType::Up, 20.0, 30.0));
+        scroll_frame(&mut ui, &mut scroll);
+
+        // Dragging the area outside of widgets scrolls
+        ui.track_pointer_event(&event(PointerEventType::Down, 95.0, 90.0));
+        scroll_frame(&mut ui, &mut scroll);
+        ui.track_pointer_event(&event(PointerEventType::Move, 95.0, 50.0));
+        scroll_frame(&mut ui, &mut scroll);
+        assert_eq!(scroll.offset(), 40.0);
+    }
+}
@@ -0,0 +1,125 @@
+//! Conversion of key presses to text edits, for text fields.
+
+use crate::Key;
+
+/// What a single key press does to the content of a text field
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(super) enum TextEdit {
+    Insert(char),
+    DeleteBack,
+    Submit,
+}
+
+pub(super) fn text_edit(key: Key, shift: bool) -> Option<TextEdit> {
+    use Key::*;
+    let c = match key {
+        Backspace | Delete => return Some(TextEdit::DeleteBack),
+        Enter => return Some(TextEdit::Submit),
+        Space => ' ',
+        Digit0 | Numpad0 => '0',
+        Digit1 | Numpad1 => '1',
+        Digit2 | Numpad2 => '2',
+        Digit3 | Numpad3 => '3',
+        Digit4 | Numpad4 => '4',
+        Digit5 | Numpad5 => '5',
+        Digit6 | Numpad6 => '6',
+        Digit7 | Numpad7 => '7',
+        Digit8 | Numpad8 => '8',
+        Digit9 | Numpad9 => '9',
+        KeyA => 'a',
+        KeyB => 'b',
+        KeyC => 'c',
+        KeyD => 'd',
+        KeyE => 'e',
+        KeyF => 'f',
+        KeyG => 'g',
+        KeyH => 'h',
+        KeyI => 'i',
+        KeyJ => 'j',
+        KeyK => 'k',
+        KeyL => 'l',
+        KeyM => 'm',
+        KeyN => 'n',
+        KeyO => 'o',
+        KeyP => 'p',
+        KeyQ => 'q',
+        KeyR => 'r',
+        KeyS => 's',
+        KeyT => 't',
+        KeyU => 'u',
+        KeyV => 'v',
+        KeyW => 'w',
+        KeyX => 'x',
+        KeyY => 'y',
+        KeyZ => 'z',
+        _ => return None,
+    };
+    if shift {
+        Some(TextEdit::Insert(c.to_ascii_uppercase()))
+    } else {
+        Some(TextEdit::Insert(c))
+    }
+}
+
+/// Applies key presses to a string. Returns (changed, submitted).
+pub(super) fn apply_keys(
+    text: &mut String,
+    keys: &[Key],
+    shift: bool,
+    max_len: usize,
+) -> (bool, bool) {
+    let mut changed = false;
+    let mut submitted = false;
+    for key in keys {
+        match text_edit(*key, shift) {
+            Some(TextEdit::Insert(c)) if text.chars().count() < max_len => {
+                text.push(c);
+                changed = true;
+            }
+            Some(TextEdit::DeleteBack) => {
+                changed |= text.pop().is_some();
+            }
+            Some(TextEdit::Submit) => submitted = true,
+            _ => {}
+        }
+    }
+    (changed, submitted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn typing() {
+        let mut text = String::new();
+        let keys = [Key::KeyH, Key::KeyI, Key::Space, Key::Digit2];
+        assert_eq!(apply_keys(&mut text, &keys, false, 32), (true, false));
+        assert_eq!(text, "hi 2");
+        assert_eq!(apply_keys(&mut text, &[Key::KeyX], true, 32), (true, false));
+        assert_eq!(text, "hi 2X");
+    }
+
+    #[test]
+    fn delete_and_submit() {
+        let mut text = "ab".to_owned();
+        let keys = [Key::Backspace, Key::Enter];
+        assert_eq!(apply_keys(&mut text, &keys, false, 32), (true, true));
+        assert_eq!(text, "a");
+        let mut empty = String::new();
+        assert_eq!(
+            apply_keys(&mut empty, &[Key::Backspace], false, 32),
+            (false, false)
+        );
+    }
+
+    #[test]
+    fn max_len() {
+        let mut text = "abc".to_owned();
+        assert_eq!(
+            apply_keys(&mut text, &[Key::KeyD], false, 3),
+            (false, false)
+        );
+        assert_eq!(text, "abc");
+    }
+}
@@ -0,0 +1,68 @@
+use super::WidgetState;
+use crate::quicksilver_compat::Color;
+use crate::TextPool;
+
+/// Colors and text style used to draw widgets.
+///
+/// Labels are HTML text, styled with the CSS in `label_styles` and `label_classes`.
+#[derive(Clone, Debug)]
+pub struct Theme {
+    pub background: Color,
+    pub widget: Color,
+    pub widget_hovered: Color,
+    pub widget_pressed: Color,
+    /// Highlights like the filled part of a slider or a checked toggle
+    pub accent: Color,
+    /// Outline of a widget with keyboard focus
+    pub focus: Color,
+    /// Background of text fields
+    pub field: Color,
+    /// Slider tracks and scroll bars
+    pub track: Color,
+    /// Width of outlines and slider tracks
+    pub stroke: f32,
+    pub label_styles: Vec<(&'static str, &'static str)>,
+    pub label_classes: Vec<&'static str>,
+}
+
+impl Theme {
+    /// Color of a widget body in the given state
+    pub fn widget_color(&self, state: WidgetState) -> Color {
+        match state {
+            WidgetState::Normal => self.widget,
+            WidgetState::Hovered => self.widget_hovered,
+            WidgetState::Pressed => self.widget_pressed,
+        }
+    }
+    pub(super) fn text_pool(&self) -> TextPool {
+        TextPool::new(
+            String::new(),
+            &self.label_styles,
+            &self.label_classes,
+            Default::default(),
+        )
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            background: Color::new(0.275, 0.325, 0.384),
+            widget: Color::new(0.004, 0.098, 0.212),
+            widget_hovered: Color::new(0.078, 0.2, 0.35),
+            widget_pressed: Color::new(0.0, 0.05, 0.12),
+            accent: Color::new(0.008, 0.918, 0.741),
+            focus: Color::new(0.976, 0.863, 0.361),
+            field: Color::new(0.02, 0.02, 0.05),
+            track: Color::new(0.6, 0.6, 0.6),
+            stroke: 2.0,
+            label_styles: vec![
+                ("color", "white"),
+                ("font-family", "sans-serif"),
+                ("overflow", "hidden"),
+                ("white-space", "nowrap"),
+            ],
+            label_classes: vec![],
+        }
+    }
+}